@@ -0,0 +1,263 @@
+//! Non-standard key-wrapping using NIST AES Key Wrap.
+//!
+//! This is a custom PASERK wrap protocol with the `aeskw` prefix, for environments that
+//! require SP 800-38F key-wrapping instead of [`pie`](crate::PieWrappedKey).
+//!
+//! <https://github.com/paseto-standard/paserk/blob/master/operations/Wrap.md>
+//! <https://www.rfc-editor.org/rfc/rfc3394>
+
+use std::{fmt, str::FromStr};
+
+use cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use digest::Mac;
+use generic_array::{
+    typenum::{U40, U56},
+    ArrayLength, GenericArray,
+};
+use rusty_paseto::core::{PasetoError, V3};
+use subtle::ConstantTimeEq;
+
+use crate::{read_b64, wrap::WrapType, write_b64, Algorithm, Key, KeyType, Local, Secret, Version};
+
+const DEFAULT_IV: [u8; 8] = [0xa6; 8];
+
+/// A key wrapped with AES-256 Key Wrap (RFC 3394, NIST SP 800-38F).
+///
+/// This is not a standard PASERK type. The serialization uses the custom
+/// `k3.local-wrap.aeskw.`/`k3.secret-wrap.aeskw.` headers, so only implementations
+/// that know about this protocol can unwrap it.
+///
+/// ```
+/// use rusty_paserk::{AesKwWrappedKey, Key, Local, V3};
+///
+/// let wrapping_key = Key::<V3, Local>::new_os_random();
+///
+/// let local_key = Key::<V3, Local>::new_os_random();
+///
+/// let wrapped_local = local_key.wrap_aes_kw(&wrapping_key).to_string();
+/// // => "k3.local-wrap.aeskw.I6hyDnIg8BIOKCmQjYNfONlBdQZlOQgAmde2FuxAijPZ5c7z1DZENQ"
+///
+/// let wrapped_local: AesKwWrappedKey<Local> = wrapped_local.parse().unwrap();
+/// let local_key2 = wrapped_local.unwrap_key(&wrapping_key).unwrap();
+/// assert_eq!(local_key, local_key2);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "aes-kw")))]
+pub struct AesKwWrappedKey<K: AesKwWrapType> {
+    wrapped_key: GenericArray<u8, K::WrappedLen>,
+}
+
+impl<K: AesKwWrapType> super::SafeForFooter for AesKwWrappedKey<K> {}
+impl<K: AesKwWrapType> super::FromPaserkBytes for AesKwWrappedKey<K> {}
+
+impl<K: AesKwWrapType> Key<V3, K> {
+    /// Wrap this key with AES-256 Key Wrap. See [`AesKwWrappedKey`]
+    pub fn wrap_aes_kw(&self, wrapping_key: &Key<V3, Local>) -> AesKwWrappedKey<K> {
+        let mut wrapped_key = GenericArray::<u8, K::WrappedLen>::default();
+        wrapped_key[8..].copy_from_slice(&self.key);
+        wrap(&K::kek(wrapping_key), &mut wrapped_key);
+
+        AesKwWrappedKey { wrapped_key }
+    }
+}
+
+impl<K: AesKwWrapType> AesKwWrappedKey<K> {
+    /// The algorithm used to wrap these keys
+    pub const ALGORITHM: Algorithm = Algorithm::WrapAesKw;
+
+    /// Unwrap the key, checking the integrity value in constant time.
+    pub fn unwrap_key(self, wrapping_key: &Key<V3, Local>) -> Result<Key<V3, K>, PasetoError> {
+        let Self { mut wrapped_key } = self;
+
+        if unwrap(&K::kek(wrapping_key), &mut wrapped_key)
+            .ct_ne(&DEFAULT_IV)
+            .into()
+        {
+            return Err(PasetoError::InvalidSignature);
+        }
+
+        Ok(Key {
+            key: GenericArray::clone_from_slice(&wrapped_key[8..]),
+        })
+    }
+}
+
+/// RFC 3394 section 2.2.1. `buf` holds the 8 byte IV slot followed by the plaintext.
+fn wrap(kek: &aes::Aes256, buf: &mut [u8]) {
+    let n = buf.len() / 8 - 1;
+    let mut a = DEFAULT_IV;
+
+    for j in 0..6 {
+        for i in 1..=n {
+            let mut b = GenericArray::default();
+            b[..8].copy_from_slice(&a);
+            b[8..].copy_from_slice(&buf[i * 8..i * 8 + 8]);
+            kek.encrypt_block(&mut b);
+
+            let t = ((n * j + i) as u64).to_be_bytes();
+            for k in 0..8 {
+                a[k] = b[k] ^ t[k];
+            }
+            buf[i * 8..i * 8 + 8].copy_from_slice(&b[8..]);
+        }
+    }
+    buf[..8].copy_from_slice(&a);
+}
+
+/// RFC 3394 section 2.2.2. Returns the recovered IV, which the caller must check.
+fn unwrap(kek: &aes::Aes256, buf: &mut [u8]) -> [u8; 8] {
+    let n = buf.len() / 8 - 1;
+    let mut a = [0; 8];
+    a.copy_from_slice(&buf[..8]);
+
+    for j in (0..6).rev() {
+        for i in (1..=n).rev() {
+            let t = ((n * j + i) as u64).to_be_bytes();
+            let mut b = GenericArray::default();
+            for k in 0..8 {
+                b[k] = a[k] ^ t[k];
+            }
+            b[8..].copy_from_slice(&buf[i * 8..i * 8 + 8]);
+            kek.decrypt_block(&mut b);
+
+            a.copy_from_slice(&b[..8]);
+            buf[i * 8..i * 8 + 8].copy_from_slice(&b[8..]);
+        }
+    }
+    a
+}
+
+impl<K: AesKwWrapType> FromStr for AesKwWrappedKey<K> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V3::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(K::WRAP_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s.strip_prefix("aeskw.").ok_or(PasetoError::WrongHeader)?;
+
+        let wrapped_key = read_b64(s)?;
+
+        Ok(Self { wrapped_key })
+    }
+}
+
+impl<K: AesKwWrapType> fmt::Display for AesKwWrappedKey<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V3::KEY_HEADER)?;
+        f.write_str(K::WRAP_HEADER)?;
+        f.write_str("aeskw.")?;
+        write_b64(&self.wrapped_key, f)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "arbitrary")]
+impl<'a, K: AesKwWrapType> arbitrary::Arbitrary<'a> for AesKwWrappedKey<K> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            wrapped_key: crate::arbitrary_bytes(u)?,
+        })
+    }
+}
+
+/// Helper trait for configuring AES key wrapping
+pub trait AesKwWrapType: KeyType<V3> + WrapType {
+    #[doc(hidden)]
+    type WrappedLen: ArrayLength<u8>;
+
+    /// The key encryption key is bound to the header, so a wrapping key used for
+    /// local keys cannot be confused with one used for secret keys.
+    #[doc(hidden)]
+    fn kek(wrapping_key: &Key<V3, Local>) -> aes::Aes256 {
+        let k = <hmac::Hmac<sha2::Sha384> as Mac>::new_from_slice(wrapping_key.as_ref())
+            .unwrap()
+            .chain_update([0x82])
+            .chain_update(V3::KEY_HEADER)
+            .chain_update(Self::WRAP_HEADER)
+            .chain_update("aeskw.")
+            .finalize()
+            .into_bytes();
+        aes::Aes256::new_from_slice(&k[..32]).unwrap()
+    }
+}
+
+impl AesKwWrapType for Local {
+    // 32 + 8
+    type WrappedLen = U40;
+}
+
+impl AesKwWrapType for Secret {
+    // 48 + 8
+    type WrappedLen = U56;
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<K: AesKwWrapType> serde::Serialize for AesKwWrappedKey<K> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, K: AesKwWrapType> serde::Deserialize<'de> for AesKwWrappedKey<K> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<K>(std::marker::PhantomData<K>);
+        impl<'de, K: AesKwWrapType> serde::de::Visitor<'de> for FromStrVisitor<K> {
+            type Value = AesKwWrappedKey<K>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a \"{}{}aeskw.\" serialized key",
+                    V3::KEY_HEADER,
+                    K::WRAP_HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cipher::KeyInit;
+
+    // RFC 3394 section 4.6: Wrap 256 bits of Key Data with a 256-bit KEK
+    #[test]
+    fn rfc3394_256_bit_kek_256_bit_key() {
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F")
+            .unwrap();
+        let kek = aes::Aes256::new_from_slice(&kek).unwrap();
+        let key = hex::decode("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F")
+            .unwrap();
+        let expected = hex::decode(
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21",
+        )
+        .unwrap();
+
+        let mut buf = [0; 40];
+        buf[8..].copy_from_slice(&key);
+        super::wrap(&kek, &mut buf);
+        assert_eq!(buf[..], expected[..]);
+
+        assert_eq!(super::unwrap(&kek, &mut buf), super::DEFAULT_IV);
+        assert_eq!(buf[8..], key[..]);
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// The cryptographic mechanism behind a PASERK operation.
+///
+/// The names returned by [`Algorithm::name`] are stable and can be used in
+/// policies and logs without needing to parse PASERK headers.
+///
+/// ```
+/// use rusty_paserk::{Algorithm, SealedKey, V4};
+///
+/// assert_eq!(SealedKey::<V4>::ALGORITHM, Algorithm::SealX25519);
+/// assert_eq!(Algorithm::SealX25519.name(), "seal-x25519");
+/// assert_eq!(Algorithm::from_name("seal-x25519"), Some(Algorithm::SealX25519));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// `k3.seal`: P-384 ECDH, AES-256-CTR and HMAC-SHA384
+    SealP384,
+    /// `k4.seal`: X25519, XChaCha20 and BLAKE2b-MAC
+    SealX25519,
+    /// `k3.local-wrap.pie`/`k3.secret-wrap.pie`: AES-256-CTR and HMAC-SHA384
+    WrapPieAesCtr,
+    /// `k4.local-wrap.pie`/`k4.secret-wrap.pie`: XChaCha20 and BLAKE2b-MAC
+    WrapPieXChaCha20,
+    /// `k3.local-wrap.aeskw`/`k3.secret-wrap.aeskw`: AES-256 Key Wrap (non-standard)
+    WrapAesKw,
+    /// `k3.local-pw`/`k3.secret-pw`: PBKDF2-SHA384, AES-256-CTR and HMAC-SHA384
+    PwPbkdf2,
+    /// `k4.local-pw`/`k4.secret-pw`: Argon2id, XChaCha20 and BLAKE2b-MAC
+    PwArgon2id,
+}
+
+impl Algorithm {
+    /// All known algorithms
+    pub const ALL: &'static [Algorithm] = &[
+        Algorithm::SealP384,
+        Algorithm::SealX25519,
+        Algorithm::WrapPieAesCtr,
+        Algorithm::WrapPieXChaCha20,
+        Algorithm::WrapAesKw,
+        Algorithm::PwPbkdf2,
+        Algorithm::PwArgon2id,
+    ];
+
+    /// The stable name of this algorithm
+    pub const fn name(self) -> &'static str {
+        match self {
+            Algorithm::SealP384 => "seal-p384",
+            Algorithm::SealX25519 => "seal-x25519",
+            Algorithm::WrapPieAesCtr => "wrap-pie-aes-ctr",
+            Algorithm::WrapPieXChaCha20 => "wrap-pie-xchacha20",
+            Algorithm::WrapAesKw => "wrap-aes-kw",
+            Algorithm::PwPbkdf2 => "pw-pbkdf2",
+            Algorithm::PwArgon2id => "pw-argon2id",
+        }
+    }
+
+    /// Find the algorithm with the given stable name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
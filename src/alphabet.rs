@@ -0,0 +1,59 @@
+//! Choosing how strictly to parse the base64 in PASERK strings.
+
+use std::{borrow::Cow, fmt};
+
+/// How strictly to parse the base64 payload of a PASERK string.
+///
+/// PASERK always uses unpadded base64url. Some implementations emit standard base64 by mistake,
+/// so with the `lenient-base64` feature, [`Base64Mode::Lenient`] can be selected per call
+/// to accept `+`, `/` and `=` padding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Base64Mode {
+    /// Only accept unpadded base64url, as the spec requires
+    #[default]
+    Strict,
+    /// Also accept the standard base64 alphabet and padding, reporting a [`Base64Warning`]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lenient-base64")))]
+    #[cfg(feature = "lenient-base64")]
+    Lenient,
+}
+
+/// A PASERK string was only accepted by [`Base64Mode::Lenient`].
+///
+/// Log this so the partner emitting it can be asked to fix their encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Base64Warning {
+    _private: (),
+}
+
+impl fmt::Display for Base64Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PASERK string used standard base64 instead of unpadded base64url")
+    }
+}
+
+impl Base64Mode {
+    /// Rewrite `s` into strict form if this mode allows it
+    pub(crate) fn normalize(self, s: &str) -> (Cow<'_, str>, Option<Base64Warning>) {
+        match self {
+            Base64Mode::Strict => (Cow::Borrowed(s), None),
+            #[cfg(feature = "lenient-base64")]
+            Base64Mode::Lenient => {
+                if !s.contains(['+', '/', '=']) {
+                    return (Cow::Borrowed(s), None);
+                }
+                let s = s
+                    .chars()
+                    .filter(|c| *c != '=')
+                    .map(|c| match c {
+                        '+' => '-',
+                        '/' => '_',
+                        c => c,
+                    })
+                    .collect();
+                (Cow::Owned(s), Some(Base64Warning { _private: () }))
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Parsing PASERK strings without knowing their type up front.
+
+use std::{fmt, str::FromStr};
+
+use rusty_paseto::core::PasetoError;
+
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use crate::{
+    internal::{PieVersion, PieWrapType, PwVersion, PwWrapType, SealedVersion},
+    Key, KeyId, Local, PieWrappedKey, PlaintextKey, Public, PwWrappedKey, SealedKey, Secret,
+};
+
+/// Any PASERK type of a single version, chosen by the type header.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, Paserk, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+///
+/// match key.to_id().to_string().parse::<Paserk<V4>>().unwrap() {
+///     Paserk::Lid(id) => assert_eq!(id, key.to_id()),
+///     _ => panic!("expected a local key id"),
+/// }
+/// ```
+#[non_exhaustive]
+pub enum Paserk<V: SealedVersion + PieVersion + PwVersion>
+where
+    Local: PwWrapType<V>,
+    Secret: PwWrapType<V>,
+{
+    /// `local.`
+    Local(Key<V, Local>),
+    /// `public.`
+    Public(Key<V, Public>),
+    /// `secret.`
+    Secret(Key<V, Secret>),
+    /// `lid.`
+    Lid(KeyId<V, Local>),
+    /// `pid.`
+    Pid(KeyId<V, Public>),
+    /// `sid.`
+    Sid(KeyId<V, Secret>),
+    /// `seal.`
+    Seal(SealedKey<V>),
+    /// `local-wrap.pie.`
+    LocalWrap(PieWrappedKey<V, Local>),
+    /// `secret-wrap.pie.`
+    SecretWrap(PieWrappedKey<V, Secret>),
+    /// `local-pw.`
+    LocalPw(PwWrappedKey<V, Local>),
+    /// `secret-pw.`
+    SecretPw(PwWrappedKey<V, Secret>),
+}
+
+impl<V: SealedVersion + PieVersion + PwVersion> FromStr for Paserk<V>
+where
+    Local: PieWrapType<V> + PwWrapType<V>,
+    Secret: PieWrapType<V> + PwWrapType<V>,
+{
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix(V::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let (ty, _) = rest.split_once('.').ok_or(PasetoError::WrongHeader)?;
+
+        Ok(match ty {
+            "local" => Paserk::Local(s.parse::<PlaintextKey<V, Local>>()?.0),
+            "public" => Paserk::Public(s.parse::<PlaintextKey<V, Public>>()?.0),
+            "secret" => Paserk::Secret(s.parse::<PlaintextKey<V, Secret>>()?.0),
+            "lid" => Paserk::Lid(s.parse()?),
+            "pid" => Paserk::Pid(s.parse()?),
+            "sid" => Paserk::Sid(s.parse()?),
+            "seal" => Paserk::Seal(s.parse()?),
+            "local-wrap" => Paserk::LocalWrap(s.parse()?),
+            "secret-wrap" => Paserk::SecretWrap(s.parse()?),
+            "local-pw" => Paserk::LocalPw(s.parse()?),
+            "secret-pw" => Paserk::SecretPw(s.parse()?),
+            _ => return Err(PasetoError::WrongHeader),
+        })
+    }
+}
+
+impl<V: SealedVersion + PieVersion + PwVersion> fmt::Display for Paserk<V>
+where
+    Local: PieWrapType<V> + PwWrapType<V>,
+    Secret: PieWrapType<V> + PwWrapType<V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Paserk::Local(key) => PlaintextKey(key.clone()).fmt(f),
+            Paserk::Public(key) => PlaintextKey(key.clone()).fmt(f),
+            Paserk::Secret(key) => PlaintextKey(key.clone()).fmt(f),
+            Paserk::Lid(id) => id.fmt(f),
+            Paserk::Pid(id) => id.fmt(f),
+            Paserk::Sid(id) => id.fmt(f),
+            Paserk::Seal(sealed) => sealed.fmt(f),
+            Paserk::LocalWrap(wrapped) => wrapped.fmt(f),
+            Paserk::SecretWrap(wrapped) => wrapped.fmt(f),
+            Paserk::LocalPw(wrapped) => wrapped.fmt(f),
+            Paserk::SecretPw(wrapped) => wrapped.fmt(f),
+        }
+    }
+}
+
+/// Any PASERK string of any version supported by this build, chosen by the version and
+/// type headers.
+///
+/// Use [`PaserkVersion::of_paserk`](crate::PaserkVersion::of_paserk) to find out why a
+/// string with an unsupported version was rejected.
+///
+/// ```
+/// use rusty_paserk::{AnyPaserk, Key, Paserk, Secret, V4};
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let pid = secret_key.public_key().to_id().to_string();
+///
+/// match pid.parse::<AnyPaserk>().unwrap() {
+///     AnyPaserk::V4(Paserk::Pid(id)) => assert_eq!(id, secret_key.public_key().to_id()),
+///     _ => panic!("expected a V4 public key id"),
+/// }
+///
+/// assert!("k1.local.AAAA".parse::<AnyPaserk>().is_err());
+/// ```
+#[non_exhaustive]
+pub enum AnyPaserk {
+    /// `k2.`
+    #[cfg(feature = "v2")]
+    V2(Paserk<V2>),
+    /// `k3.`
+    #[cfg(feature = "v3")]
+    V3(Paserk<V3>),
+    /// `k4.`
+    #[cfg(feature = "v4")]
+    V4(Paserk<V4>),
+}
+
+impl FromStr for AnyPaserk {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(feature = "v2")]
+        if s.starts_with("k2.") {
+            return s.parse().map(AnyPaserk::V2);
+        }
+        #[cfg(feature = "v3")]
+        if s.starts_with("k3.") {
+            return s.parse().map(AnyPaserk::V3);
+        }
+        #[cfg(feature = "v4")]
+        if s.starts_with("k4.") {
+            return s.parse().map(AnyPaserk::V4);
+        }
+
+        Err(PasetoError::WrongHeader)
+    }
+}
+
+impl fmt::Display for AnyPaserk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "v2")]
+            AnyPaserk::V2(paserk) => paserk.fmt(f),
+            #[cfg(feature = "v3")]
+            AnyPaserk::V3(paserk) => paserk.fmt(f),
+            #[cfg(feature = "v4")]
+            AnyPaserk::V4(paserk) => paserk.fmt(f),
+        }
+    }
+}
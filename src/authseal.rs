@@ -0,0 +1,297 @@
+//! Non-standard authenticated key sealing.
+//!
+//! [`seal`](crate::Key::seal) is anonymous: anyone with the recipient's public key can produce
+//! a valid sealed key. This variant also mixes an X25519 exchange between the sender's static
+//! secret key and the recipient's public key into the KDF, so a successful unseal proves the
+//! sealed key came from the holder of the sender's secret key.
+
+use std::{fmt, str::FromStr};
+
+use cipher::{inout::InOutBuf, KeyIvInit, StreamCipher};
+use curve25519_dalek::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::{clamp_integer, Scalar},
+    MontgomeryPoint,
+};
+use digest::{Digest, Mac};
+use ed25519_dalek::hazmat::ExpandedSecretKey;
+use generic_array::{
+    sequence::{Concat, Split},
+    typenum::{U32, U96},
+    GenericArray,
+};
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::{PasetoError, V4};
+use subtle::ConstantTimeEq;
+
+use crate::{read_b64, write_b64, Key, Local, Public, Secret, Version};
+
+const HEADER: &str = "seal-auth.";
+
+/// A local key sealed to a recipient's public key and authenticated by the sender's secret key.
+///
+/// This is not a standard PASERK type. The serialization uses the custom `k4.seal-auth.`
+/// header, so only implementations that know about this extension can unseal it.
+///
+/// The layout matches [`SealedKey`](crate::SealedKey) (tag, ephemeral public key, encrypted
+/// key), but the encryption and authentication keys are derived from both the ephemeral and the
+/// sender's static exchange with the recipient, as well as the sender's public key. Unsealing
+/// with the wrong sender public key fails.
+///
+/// Like any static Diffie-Hellman scheme, this does not stop the recipient from creating sealed
+/// keys that appear to come from any sender *to themselves*, so it only authenticates the sender
+/// to the recipient, not to third parties.
+///
+/// ```
+/// use rusty_paserk::{AuthSealedKey, Key, Local, Secret, V4};
+///
+/// let sender = Key::<V4, Secret>::new_os_random();
+/// let recipient = Key::<V4, Secret>::new_os_random();
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let sealed = key
+///     .seal_authenticated(&sender, &recipient.public_key())
+///     .to_string();
+/// // => "k4.seal-auth.Yp7dvJ8V7cSXJtJ9JdGyNV8K..."
+///
+/// let sealed: AuthSealedKey = sealed.parse().unwrap();
+/// let key2 = sealed.clone().unseal(&recipient, &sender.public_key()).unwrap();
+/// assert_eq!(key, key2);
+///
+/// let impostor = Key::<V4, Secret>::new_os_random();
+/// assert!(sealed.unseal(&recipient, &impostor.public_key()).is_err());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "auth-seal")))]
+#[derive(Clone)]
+pub struct AuthSealedKey {
+    tag: GenericArray<u8, U32>,
+    ephemeral_public_key: GenericArray<u8, U32>,
+    encrypted_data_key: GenericArray<u8, U32>,
+}
+
+impl super::SafeForFooter for AuthSealedKey {}
+impl super::FromPaserkBytes for AuthSealedKey {}
+impl super::PaserkLen for AuthSealedKey {
+    fn paserk_len(&self) -> usize {
+        V4::KEY_HEADER.len() + HEADER.len() + crate::encoding::encoded_len(96)
+    }
+}
+
+impl Key<V4, Local> {
+    /// Seal this key to `recipient`, authenticated by `sender`. See [`AuthSealedKey`]
+    pub fn seal_authenticated(
+        &self,
+        sender: &Key<V4, Secret>,
+        recipient: &Key<V4, Public>,
+    ) -> AuthSealedKey {
+        self.seal_authenticated_with_rng(sender, recipient, &mut OsRng)
+    }
+
+    /// Seal this key to `recipient`, authenticated by `sender`.
+    ///
+    /// The ephemeral key is generated from the provided random source.
+    pub fn seal_authenticated_with_rng(
+        &self,
+        sender: &Key<V4, Secret>,
+        recipient: &Key<V4, Public>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> AuthSealedKey {
+        let xpk = CompressedEdwardsY(recipient.key.into())
+            .decompress()
+            .unwrap()
+            .to_montgomery();
+        let (sender_xsk, sender_xpk) = expand_secret(sender);
+
+        let esk = Scalar::from_bytes_mod_order(clamp_integer({
+            let mut esk = [0; 32];
+            rng.fill_bytes(&mut esk);
+            esk
+        }));
+        let epk = EdwardsPoint::mul_base(&esk).to_montgomery();
+
+        let xk = esk * xpk;
+        let sk = sender_xsk * xpk;
+        let (ek, ak, n) = derive(&xk, &sk, &epk, &sender_xpk, &xpk);
+
+        let mut edk = GenericArray::<u8, U32>::default();
+        chacha20::XChaCha20::new(&ek, &n)
+            .apply_keystream_inout(InOutBuf::new(self.as_ref(), &mut edk).unwrap());
+
+        AuthSealedKey {
+            tag: tag(&ak, &epk, &edk),
+            ephemeral_public_key: epk.to_bytes().into(),
+            encrypted_data_key: edk,
+        }
+    }
+}
+
+impl AuthSealedKey {
+    /// Unseal the key with the recipient's secret key, checking it was sealed by `sender`.
+    ///
+    /// Returns [`PasetoError::InvalidSignature`] if the key was sealed to another recipient or
+    /// by another sender.
+    pub fn unseal(
+        mut self,
+        recipient: &Key<V4, Secret>,
+        sender: &Key<V4, Public>,
+    ) -> Result<Key<V4, Local>, PasetoError> {
+        let epk = MontgomeryPoint(self.ephemeral_public_key.into());
+        let sender_xpk = CompressedEdwardsY(sender.key.into())
+            .decompress()
+            .ok_or(PasetoError::InvalidKey)?
+            .to_montgomery();
+        let (xsk, xpk) = expand_secret(recipient);
+
+        let xk = xsk * epk;
+        let sk = xsk * sender_xpk;
+        let (ek, ak, n) = derive(&xk, &sk, &epk, &sender_xpk, &xpk);
+
+        let t2 = tag(&ak, &epk, &self.encrypted_data_key);
+        if self.tag.ct_ne(&t2).into() {
+            return Err(PasetoError::InvalidSignature);
+        }
+
+        chacha20::XChaCha20::new(&ek, &n).apply_keystream(&mut self.encrypted_data_key);
+        Ok(Key {
+            key: self.encrypted_data_key,
+        })
+    }
+}
+
+/// The X25519 scalar and public key for an Ed25519 secret key
+fn expand_secret(key: &Key<V4, Secret>) -> (Scalar, MontgomeryPoint) {
+    let (sk, pk) = key.key.split();
+    let xpk = CompressedEdwardsY(pk.into())
+        .decompress()
+        .unwrap()
+        .to_montgomery();
+    let sk: ed25519_dalek::SecretKey = sk.into();
+    (ExpandedSecretKey::from(&sk).scalar, xpk)
+}
+
+type Derived = (
+    GenericArray<u8, U32>,
+    GenericArray<u8, U32>,
+    GenericArray<u8, generic_array::typenum::U24>,
+);
+
+/// Derive the encryption key, authentication key and nonce from both exchanges
+fn derive(
+    xk: &MontgomeryPoint,
+    sk: &MontgomeryPoint,
+    epk: &MontgomeryPoint,
+    sender_xpk: &MontgomeryPoint,
+    xpk: &MontgomeryPoint,
+) -> Derived {
+    let kdf = |domain: u8| {
+        blake2::Blake2b::<U32>::new()
+            .chain_update([domain])
+            .chain_update(V4::KEY_HEADER)
+            .chain_update(HEADER)
+            .chain_update(xk.as_bytes())
+            .chain_update(sk.as_bytes())
+            .chain_update(epk.as_bytes())
+            .chain_update(sender_xpk.as_bytes())
+            .chain_update(xpk.as_bytes())
+            .finalize()
+    };
+
+    let n = blake2::Blake2b::new()
+        .chain_update(epk.as_bytes())
+        .chain_update(sender_xpk.as_bytes())
+        .chain_update(xpk.as_bytes())
+        .finalize();
+
+    (kdf(0x01), kdf(0x02), n)
+}
+
+fn tag(
+    ak: &GenericArray<u8, U32>,
+    epk: &MontgomeryPoint,
+    edk: &GenericArray<u8, U32>,
+) -> GenericArray<u8, U32> {
+    blake2::Blake2bMac::<U32>::new_from_slice(ak)
+        .unwrap()
+        .chain_update(V4::KEY_HEADER)
+        .chain_update(HEADER)
+        .chain_update(epk.as_bytes())
+        .chain_update(edk)
+        .finalize()
+        .into_bytes()
+}
+
+impl FromStr for AuthSealedKey {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V4::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s.strip_prefix(HEADER).ok_or(PasetoError::WrongHeader)?;
+
+        let total: GenericArray<u8, U96> = read_b64(s)?;
+        let (tag, rest) = total.split();
+        let (ephemeral_public_key, encrypted_data_key) = rest.split();
+
+        Ok(Self {
+            tag,
+            ephemeral_public_key,
+            encrypted_data_key,
+        })
+    }
+}
+
+impl fmt::Display for AuthSealedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V4::KEY_HEADER)?;
+        f.write_str(HEADER)?;
+
+        let total = self
+            .tag
+            .concat(self.ephemeral_public_key)
+            .concat(self.encrypted_data_key);
+        write_b64(&total, f)
+    }
+}
+
+impl fmt::Debug for AuthSealedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl serde::Serialize for AuthSealedKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AuthSealedKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor;
+        impl serde::de::Visitor<'_> for FromStrVisitor {
+            type Value = AuthSealedKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a \"k4.{HEADER}\" serialized key")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor)
+    }
+}
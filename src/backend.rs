@@ -0,0 +1,153 @@
+//! Fetching wrapped keys from a secrets manager, with caching.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{Clock, SystemClock};
+
+/// A secrets manager that stores key material by name, such as AWS Secrets Manager or GCP
+/// Secret Manager.
+///
+/// Implementations make the requests to the service, with whatever credentials the service is
+/// running as, such as an IAM role scoped to its own secrets. The stored values should be
+/// protected key material, such as a [`KeyStoreFile`](crate::KeyStoreFile) or a
+/// [`RemoteWrappedKey`](crate::RemoteWrappedKey), so the secrets manager alone is not enough
+/// to get the keys.
+///
+/// The crate does not include implementations for specific services yet. Implement this trait
+/// with the service's own SDK, such as `aws-sdk-secretsmanager` or
+/// `google-cloud-secretmanager`.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub trait KeyStoreBackend {
+    /// The current value of the secret called `name`
+    fn fetch(&self, name: &str) -> impl Future<Output = Result<String, PasetoError>> + Send;
+}
+
+/// A [`KeyStoreBackend`] that keeps each value it fetches for a time to live, so a service can
+/// look up its keys on every request without calling the secrets manager each time.
+///
+/// ```
+/// use std::{future::Future, pin::pin, sync::atomic::{AtomicUsize, Ordering}, task::{Context, Poll, Waker}, time::Duration};
+/// use rusty_paserk::{CachedKeyStore, Key, KeyRing, KeyStoreBackend, KeyStoreFile, Local, PasetoError, Secret, V4};
+///
+/// /// A secrets manager with one secret, for illustration only
+/// struct OneSecret(String, AtomicUsize);
+///
+/// impl KeyStoreBackend for OneSecret {
+///     async fn fetch(&self, name: &str) -> Result<String, PasetoError> {
+///         self.1.fetch_add(1, Ordering::Relaxed);
+///         match name {
+///             "prod/paserk" => Ok(self.0.clone()),
+///             _ => Err(PasetoError::InvalidKey),
+///         }
+///     }
+/// }
+///
+/// # fn block_on<F: Future>(f: F) -> F::Output {
+/// #     let mut f = pin!(f);
+/// #     loop {
+/// #         if let Poll::Ready(out) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+/// #             return out;
+/// #         }
+/// #     }
+/// # }
+/// # block_on(async {
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let unsealing_key = Key::<V4, Secret>::new_os_random();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// let lid = ring.insert(local_key);
+/// let file = KeyStoreFile::seal(&ring, &unsealing_key.public_key()).to_string();
+///
+/// let store = CachedKeyStore::new(OneSecret(file, AtomicUsize::new(0)), Duration::from_secs(300));
+///
+/// // at boot, and on every request after that
+/// for _ in 0..3 {
+///     let file: KeyStoreFile<V4> = store.get_parsed("prod/paserk").await.unwrap();
+///     let ring = file.unseal(&unsealing_key).unwrap();
+///     assert_eq!(ring.get_local(&lid), Some(&local_key));
+/// }
+/// assert_eq!(store.backend().1.load(Ordering::Relaxed), 1);
+///
+/// assert!(store.get("dev/paserk").await.is_err());
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct CachedKeyStore<B, C: Clock = SystemClock> {
+    backend: B,
+    ttl: Duration,
+    clock: C,
+    cache: Mutex<HashMap<String, (Arc<str>, SystemTime)>>,
+}
+
+impl<B: KeyStoreBackend> CachedKeyStore<B> {
+    /// Cache the values from `backend` for `ttl`
+    pub fn new(backend: B, ttl: Duration) -> Self {
+        Self::with_clock(backend, ttl, SystemClock)
+    }
+}
+
+impl<B: KeyStoreBackend, C: Clock> CachedKeyStore<B, C> {
+    /// Cache the values from `backend` for `ttl`, using the given clock to expire them
+    pub fn with_clock(backend: B, ttl: Duration, clock: C) -> Self {
+        Self {
+            backend,
+            ttl,
+            clock,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The secrets manager this fetches from
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The value of the secret called `name`, from the cache if it was fetched less than the
+    /// time to live ago.
+    ///
+    /// Errors are not cached. Concurrent calls for a value that is not cached may each fetch
+    /// it.
+    pub async fn get(&self, name: &str) -> Result<Arc<str>, PasetoError> {
+        let now = self.clock.now();
+        if let Some((value, expires)) = self.lock().get(name) {
+            if now < *expires {
+                return Ok(value.clone());
+            }
+        }
+
+        let value: Arc<str> = self.backend.fetch(name).await?.into();
+        let expires = now.checked_add(self.ttl).unwrap_or(now);
+        self.lock()
+            .insert(name.to_owned(), (value.clone(), expires));
+        Ok(value)
+    }
+
+    /// The value of the secret called `name`, parsed as `T`, such as a
+    /// [`KeyStoreFile`](crate::KeyStoreFile) or a wrapped key.
+    ///
+    /// The parsed value is not cached, only the text.
+    pub async fn get_parsed<T: FromStr<Err = PasetoError>>(
+        &self,
+        name: &str,
+    ) -> Result<T, PasetoError> {
+        self.get(name).await?.parse()
+    }
+
+    /// Drop the cached value of the secret called `name`, so the next
+    /// [`get`](Self::get) fetches it again, such as after a rotation
+    pub fn invalidate(&self, name: &str) {
+        self.lock().remove(name);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Arc<str>, SystemTime)>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
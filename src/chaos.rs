@@ -0,0 +1,242 @@
+//! Injecting faults into remote key services, to test how an application handles them.
+//!
+//! [`FaultInjector`] wraps a [`RemoteWrapper`] or a [`KeyStoreBackend`] and makes some of its
+//! calls slow, fail or return corrupted output, following a [`FaultPlan`]. The plan is
+//! deterministic, so tests of retries and fallbacks are reproducible.
+//!
+//! ```
+//! use std::{future::Future, pin::pin, task::{Context, Poll, Waker}, time::Duration};
+//! use rusty_paserk::{chaos::{FaultInjector, FaultPlan}, Key, Local, PasetoError, RemoteWrapper, V4};
+//!
+//! /// Not secure, for illustration only
+//! struct ReverseKms;
+//!
+//! impl RemoteWrapper<V4> for ReverseKms {
+//!     const PROTOCOL: &'static str = "reverse-kms.";
+//!
+//!     async fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PasetoError> {
+//!         Ok(key.iter().rev().copied().collect())
+//!     }
+//!
+//!     async fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, PasetoError> {
+//!         Ok(wrapped.iter().rev().copied().collect())
+//!     }
+//! }
+//!
+//! # fn block_on<F: Future>(f: F) -> F::Output {
+//! #     let mut f = pin!(f);
+//! #     loop {
+//! #         if let Poll::Ready(out) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+//! #             return out;
+//! #         }
+//! #     }
+//! # }
+//! # block_on(async {
+//! let key = Key::<V4, Local>::new_os_random();
+//!
+//! // the application retries up to 3 times
+//! let kms = FaultInjector::new(
+//!     ReverseKms,
+//!     FaultPlan::new().delay(Duration::from_millis(1)).fail_first(2),
+//! );
+//! let mut wrapped = None;
+//! for _ in 0..3 {
+//!     if let Ok(w) = key.wrap_remote(&kms).await {
+//!         wrapped = Some(w);
+//!         break;
+//!     }
+//! }
+//! assert_eq!(kms.calls(), 3);
+//! assert_eq!(wrapped.unwrap().unwrap_key(&kms).await.unwrap(), key);
+//!
+//! // a service returning corrupted keys must not go unnoticed
+//! let kms = FaultInjector::new(ReverseKms, FaultPlan::new().corrupt_every(2));
+//! let wrapped = key.wrap_remote(&kms).await.unwrap();
+//! assert_ne!(wrapped.unwrap_key(&kms).await.unwrap(), key);
+//! # });
+//! ```
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{KeyStoreBackend, RemoteWrapper, Version};
+
+/// Which calls a [`FaultInjector`] interferes with. Calls are counted from 1.
+///
+/// By default, no faults are injected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaultPlan {
+    delay: Option<Duration>,
+    fail_first: u64,
+    fail_every: u64,
+    corrupt_every: u64,
+}
+
+impl FaultPlan {
+    /// A plan that injects no faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay every call by `delay`. The delay uses a background thread, so it works with any
+    /// executor
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fail the first `calls` calls, as a service that is still starting up would
+    pub fn fail_first(mut self, calls: u64) -> Self {
+        self.fail_first = calls;
+        self
+    }
+
+    /// Fail every `n`th call, as a flaky service would. 0 never fails
+    pub fn fail_every(mut self, n: u64) -> Self {
+        self.fail_every = n;
+        self
+    }
+
+    /// Corrupt the output of every `n`th call that does not fail. 0 never corrupts.
+    ///
+    /// Wrapped and unwrapped keys have the lowest bit of their last byte flipped. Values from
+    /// a [`KeyStoreBackend`] lose their last character.
+    pub fn corrupt_every(mut self, n: u64) -> Self {
+        self.corrupt_every = n;
+        self
+    }
+}
+
+/// A [`RemoteWrapper`] or [`KeyStoreBackend`] that injects the faults in a [`FaultPlan`].
+///
+/// Injected failures return [`PasetoError::Cryption`], without calling the wrapped service.
+#[derive(Debug)]
+pub struct FaultInjector<T> {
+    inner: T,
+    plan: FaultPlan,
+    calls: AtomicU64,
+}
+
+impl<T> FaultInjector<T> {
+    /// Wrap `inner`, injecting the faults in `plan`
+    pub fn new(inner: T, plan: FaultPlan) -> Self {
+        Self {
+            inner,
+            plan,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped service
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// The number of calls so far, including failed ones
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    async fn inject<O>(
+        &self,
+        call: impl Future<Output = Result<O, PasetoError>>,
+        corrupt: impl FnOnce(&mut O),
+    ) -> Result<O, PasetoError> {
+        let n = self.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(delay) = self.plan.delay {
+            Delay::new(delay).await;
+        }
+        if n <= self.plan.fail_first || n.checked_rem(self.plan.fail_every) == Some(0) {
+            return Err(PasetoError::Cryption);
+        }
+        let mut out = call.await?;
+        if n.checked_rem(self.plan.corrupt_every) == Some(0) {
+            corrupt(&mut out);
+        }
+        Ok(out)
+    }
+}
+
+impl<V: Version, W: RemoteWrapper<V> + Sync> RemoteWrapper<V> for FaultInjector<W> {
+    const PROTOCOL: &'static str = W::PROTOCOL;
+
+    async fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PasetoError> {
+        self.inject(self.inner.wrap_key(key), flip_last_bit).await
+    }
+
+    async fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, PasetoError> {
+        self.inject(self.inner.unwrap_key(wrapped), flip_last_bit)
+            .await
+    }
+}
+
+impl<B: KeyStoreBackend + Sync> KeyStoreBackend for FaultInjector<B> {
+    async fn fetch(&self, name: &str) -> Result<String, PasetoError> {
+        self.inject(self.inner.fetch(name), |value| {
+            value.pop();
+        })
+        .await
+    }
+}
+
+fn flip_last_bit(bytes: &mut Vec<u8>) {
+    match bytes.last_mut() {
+        Some(last) => *last ^= 1,
+        None => bytes.push(1),
+    }
+}
+
+/// A future that completes once `deadline` has passed, woken by a timer thread
+struct Delay {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl Delay {
+    fn new(delay: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + delay,
+            waker: None,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(waker) => waker
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone_from(cx.waker()),
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                let deadline = self.deadline;
+                let timer = waker.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    timer
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .wake_by_ref();
+                });
+                self.waker = Some(waker);
+            }
+        }
+        Poll::Pending
+    }
+}
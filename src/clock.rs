@@ -0,0 +1,56 @@
+//! Time sources for the time-based features of this crate.
+
+use std::time::SystemTime;
+
+/// A source of the current time.
+///
+/// Everything in this crate that needs the current time takes a clock, so tests and
+/// deterministic simulations can control time.
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use rusty_paserk::{FixedClock, Key, Local, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let sealed = key.seal_timestamped_with_clock(
+///     &secret_key.public_key(),
+///     Duration::from_secs(3600),
+///     &FixedClock(start),
+/// );
+///
+/// let later = FixedClock(start + Duration::from_secs(7200));
+/// assert!(sealed.unseal_with_clock(&secret_key, &later).is_err());
+/// ```
+pub trait Clock {
+    /// The current time
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock, using [`SystemTime::now`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that is stopped at a fixed time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
@@ -0,0 +1,185 @@
+//! Committing to a local key before revealing it.
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use generic_array::{typenum::U33, GenericArray};
+use rusty_paseto::core::PasetoError;
+use subtle::{Choice, ConstantTimeEq};
+
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use crate::{write_b64, Key, Local, Version};
+
+/// A commitment to a local key, for protocols that need to commit to a key before revealing it.
+///
+/// It is derived like a [`KeyId`](crate::KeyId) but with a separate domain, so a published
+/// commitment cannot be linked to the `lid` of the same key. It is serialized with a
+/// `k4.commit.` header, which is not part of the PASERK spec.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyCommitment, Local, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let commitment = key.commitment().to_string();
+/// // => "k4.commit.3OtyXU0dNkjUHEmtkQ6ELzByHv9Or86xvTTxGL3eF9XC"
+///
+/// // later, once the key is revealed
+/// let commitment: KeyCommitment<V4> = commitment.parse().unwrap();
+/// assert!(bool::from(commitment.verify(&key)));
+///
+/// let other = Key::<V4, Local>::new_os_random();
+/// assert!(!bool::from(commitment.verify(&other)));
+/// ```
+pub struct KeyCommitment<V: Version> {
+    commitment: GenericArray<u8, U33>,
+    version: PhantomData<V>,
+}
+
+const HEADER: &str = "commit.";
+
+impl<V: Version> Key<V, Local>
+where
+    KeyCommitment<V>: for<'a> From<&'a Key<V, Local>>,
+{
+    /// Commit to this key. See [`KeyCommitment`]
+    pub fn commitment(&self) -> KeyCommitment<V> {
+        self.into()
+    }
+}
+
+impl<V: Version> KeyCommitment<V>
+where
+    Self: for<'a> From<&'a Key<V, Local>>,
+{
+    /// Check in constant time whether this is a commitment to the given key.
+    pub fn verify(&self, key: &Key<V, Local>) -> Choice {
+        self.commitment.ct_eq(&Self::from(key).commitment)
+    }
+}
+
+#[cfg(feature = "v3")]
+impl From<&Key<V3, Local>> for KeyCommitment<V3> {
+    fn from(key: &Key<V3, Local>) -> Self {
+        use base64ct::{Base64UrlUnpadded, Encoding};
+
+        let mut output = [0; 32 * 4 / 3 + 3];
+        let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
+
+        KeyCommitment {
+            commitment: crate::id::v3_id(&[V3::KEY_HEADER, HEADER, V3::KEY_HEADER, "local.", p]),
+            version: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "v4")]
+impl From<&Key<V4, Local>> for KeyCommitment<V4> {
+    fn from(key: &Key<V4, Local>) -> Self {
+        use base64ct::{Base64UrlUnpadded, Encoding};
+
+        let mut output = [0; 32 * 4 / 3 + 3];
+        let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
+
+        KeyCommitment {
+            commitment: crate::id::v4_id(&[V4::KEY_HEADER, HEADER, V4::KEY_HEADER, "local.", p]),
+            version: PhantomData,
+        }
+    }
+}
+
+impl<V: Version> Clone for KeyCommitment<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<V: Version> Copy for KeyCommitment<V> {}
+
+impl<V: Version> PartialEq for KeyCommitment<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.commitment == other.commitment
+    }
+}
+impl<V: Version> Eq for KeyCommitment<V> {}
+
+impl<V: Version> fmt::Debug for KeyCommitment<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<V: Version> fmt::Display for KeyCommitment<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V::KEY_HEADER)?;
+        f.write_str(HEADER)?;
+        write_b64(&self.commitment, f)
+    }
+}
+
+impl<V: Version> FromStr for KeyCommitment<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s.strip_prefix(HEADER).ok_or(PasetoError::WrongHeader)?;
+
+        Ok(KeyCommitment {
+            commitment: crate::read_b64(s)?,
+            version: PhantomData,
+        })
+    }
+}
+
+impl<V: Version> crate::SafeForFooter for KeyCommitment<V> {}
+impl<V: Version> crate::FromPaserkBytes for KeyCommitment<V> {}
+
+impl<V: Version> crate::PaserkLen for KeyCommitment<V> {
+    fn paserk_len(&self) -> usize {
+        V::KEY_HEADER.len() + HEADER.len() + crate::encoding::encoded_len(33)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: Version> serde::Serialize for KeyCommitment<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: Version> serde::Deserialize<'de> for KeyCommitment<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: Version> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = KeyCommitment<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a \"{}{}\" key commitment",
+                    V::KEY_HEADER,
+                    HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
@@ -0,0 +1,43 @@
+//! Constant-time comparisons for truncated tags and IDs.
+//!
+//! Comparing secret-derived bytes with `==` or [`slice::starts_with`] returns early at the first
+//! mismatch, which leaks how many leading bytes matched. These compare every byte. Only the
+//! lengths of the inputs are treated as public.
+
+use subtle::{Choice, ConstantTimeEq};
+
+/// Whether `data` starts with `prefix`, in constant time.
+///
+/// ```
+/// use rusty_paserk::ct::ct_starts_with;
+///
+/// assert!(bool::from(ct_starts_with(b"k4.lid.abc", b"k4.lid.")));
+/// assert!(!bool::from(ct_starts_with(b"k4.lid.abc", b"k4.pid.")));
+/// assert!(!bool::from(ct_starts_with(b"k4", b"k4.lid.")));
+/// ```
+pub fn ct_starts_with(data: &[u8], prefix: &[u8]) -> Choice {
+    match data.get(..prefix.len()) {
+        Some(head) => head.ct_eq(prefix),
+        None => Choice::from(0),
+    }
+}
+
+/// Whether the first `len` bytes of `a` and `b` are equal, in constant time.
+///
+/// This is for checking tags that are sent truncated. Returns false if either input is shorter
+/// than `len`.
+///
+/// ```
+/// use rusty_paserk::ct::ct_eq_truncated;
+///
+/// let tag = [1, 2, 3, 4, 5, 6, 7, 8];
+/// assert!(bool::from(ct_eq_truncated(&tag, &[1, 2, 3, 4], 4)));
+/// assert!(!bool::from(ct_eq_truncated(&tag, &[1, 2, 3, 5], 4)));
+/// assert!(!bool::from(ct_eq_truncated(&tag, &[1, 2, 3], 4)));
+/// ```
+pub fn ct_eq_truncated(a: &[u8], b: &[u8], len: usize) -> Choice {
+    match (a.get(..len), b.get(..len)) {
+        (Some(a), Some(b)) => a.ct_eq(b),
+        _ => Choice::from(0),
+    }
+}
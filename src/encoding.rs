@@ -0,0 +1,88 @@
+//! Unpadded base64url, as used by every PASERK type.
+//!
+//! These are the helpers used by this crate, for downstream code that builds custom
+//! footer layouts. Encoding and decoding is constant time with respect to the data.
+//!
+//! ```
+//! use generic_array::{typenum::U4, GenericArray};
+//! use rusty_paserk::encoding;
+//!
+//! let footer = format!("tenant.{}", encoding::display_b64(b"acme"));
+//! assert_eq!(footer, "tenant.YWNtZQ");
+//!
+//! let tenant: GenericArray<u8, U4> = encoding::read_b64("YWNtZQ").unwrap();
+//! assert_eq!(&*tenant, b"acme");
+//!
+//! let mut buf = [0; 16];
+//! assert_eq!(encoding::read_b64_into("YWNtZQ", &mut buf).unwrap(), b"acme");
+//! ```
+
+use std::{fmt, ops::DerefMut};
+
+use base64ct::Encoding;
+use generic_array::{sequence::GenericSequence, typenum::Unsigned};
+use rusty_paseto::core::PasetoError;
+
+/// Write `b` as unpadded base64url, streaming it to `w` without allocating
+pub fn write_b64<W: fmt::Write>(b: &[u8], w: &mut W) -> fmt::Result {
+    let mut buffer = [0; 64];
+    for chunk in b.chunks(48) {
+        let s = base64ct::Base64UrlUnpadded::encode(chunk, &mut buffer).unwrap();
+        w.write_str(s)?;
+    }
+    Ok(())
+}
+
+/// Format `b` as unpadded base64url, for use with `format!` or `write!`
+pub fn display_b64(b: &[u8]) -> impl fmt::Display + '_ {
+    struct B64<'a>(&'a [u8]);
+    impl fmt::Display for B64<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_b64(self.0, f)
+        }
+    }
+    B64(b)
+}
+
+/// The length of `n` bytes encoded as unpadded base64
+pub const fn encoded_len(n: usize) -> usize {
+    (n * 4).div_ceil(3)
+}
+
+/// Read unpadded base64url that must decode to exactly the length of `L`
+pub fn read_b64<L: GenericSequence<u8> + DerefMut<Target = [u8]> + Default>(
+    s: &str,
+) -> Result<L, PasetoError> {
+    // reject too short and too long inputs before decoding anything
+    if s.len() != encoded_len(<L::Length as Unsigned>::USIZE) {
+        return Err(PasetoError::PayloadBase64Decode {
+            source: base64::DecodeError::InvalidLength(s.len()),
+        });
+    }
+
+    let mut total = L::default();
+
+    let len = base64ct::Base64UrlUnpadded::decode(s, &mut total)
+        .map_err(|_| PasetoError::PayloadBase64Decode {
+            source: base64::DecodeError::InvalidLength(s.len()),
+        })?
+        .len();
+
+    if len != <L::Length as Unsigned>::USIZE {
+        return Err(PasetoError::PayloadBase64Decode {
+            source: base64::DecodeError::InvalidLength(s.len()),
+        });
+    }
+
+    Ok(total)
+}
+
+/// Read unpadded base64url of any length into `buf`, returning the decoded bytes
+pub fn read_b64_into<'a>(s: &str, buf: &'a mut [u8]) -> Result<&'a [u8], PasetoError> {
+    let b = base64ct::Base64UrlUnpadded::decode(s, buf).map_err(|_| {
+        PasetoError::PayloadBase64Decode {
+            source: base64::DecodeError::InvalidLength(s.len()),
+        }
+    })?;
+    Ok(b)
+}
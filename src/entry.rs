@@ -0,0 +1,250 @@
+//! Keys bundled with metadata about how they may be used.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Clock, KeyId, KeyType, SafeForFooter, SystemClock, Version};
+
+/// A key, or its sealed or wrapped form, with its ID, creation time, optional expiry and an
+/// application-defined purpose.
+///
+/// `T` is the form the key is stored in, such as [`PieWrappedKey<V, K>`](crate::PieWrappedKey),
+/// [`SealedKey<V>`](crate::SealedKey) or just the [`KeyId<V, K>`] for a key that lives
+/// elsewhere.
+///
+/// [`to_footer_json`](Self::to_footer_json) writes the entry as a JSON object with the `kid`,
+/// `key`, `iat`, `exp` and `purpose` fields. Times are in seconds since the Unix epoch. With the
+/// `serde` feature, the entry serializes to the same structure.
+///
+/// ```
+/// use std::time::Duration;
+/// use rusty_paserk::{Key, KeyEntry, Local, SealedKey, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let entry = KeyEntry::new(key.to_id(), key.seal(&secret_key.public_key()), "session")
+///     .expires_after(Duration::from_secs(86400));
+///
+/// let footer = entry.to_footer_json();
+/// // => {"kid":"k4.lid...","key":"k4.seal...","iat":1700000000,"exp":1700086400,"purpose":"session"}
+/// assert!(footer.starts_with(&format!(r#"{{"kid":"{}","key":"k4.seal."#, key.to_id())));
+///
+/// assert!(!entry.is_expired());
+/// assert_eq!(entry.key.clone().unseal(&secret_key).unwrap(), key);
+///
+/// let entry = entry.expires_after(Duration::MAX);
+/// assert_eq!(entry.expires, None);
+/// ```
+pub struct KeyEntry<V: Version, K: KeyType<V>, T> {
+    /// The ID of the key
+    pub id: KeyId<V, K>,
+    /// The key, in the form it is stored
+    pub key: T,
+    /// When the key was created
+    pub created: SystemTime,
+    /// When the key should stop being used, if ever
+    pub expires: Option<SystemTime>,
+    /// What the application uses the key for
+    pub purpose: String,
+}
+
+impl<V: Version, K: KeyType<V>, T> KeyEntry<V, K, T> {
+    /// A new entry, created now, that never expires
+    pub fn new(id: KeyId<V, K>, key: T, purpose: impl Into<String>) -> Self {
+        Self::new_with_clock(id, key, purpose, &SystemClock)
+    }
+
+    /// A new entry, created at the current time of `clock`, that never expires
+    pub fn new_with_clock(
+        id: KeyId<V, K>,
+        key: T,
+        purpose: impl Into<String>,
+        clock: &impl Clock,
+    ) -> Self {
+        Self {
+            id,
+            key,
+            created: clock.now(),
+            expires: None,
+            purpose: purpose.into(),
+        }
+    }
+
+    /// Expire the key `max_age` after it was created.
+    ///
+    /// If that time can't be represented, such as for [`Duration::MAX`], the key never expires.
+    pub fn expires_after(mut self, max_age: Duration) -> Self {
+        self.expires = self.created.checked_add(max_age);
+        self
+    }
+
+    /// Whether the key has expired
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_with_clock(&SystemClock)
+    }
+
+    /// Whether the key has expired at the current time of `clock`
+    pub fn is_expired_with_clock(&self, clock: &impl Clock) -> bool {
+        self.expires.is_some_and(|expires| clock.now() >= expires)
+    }
+}
+
+impl<V: Version, K: KeyType<V>, T: SafeForFooter> KeyEntry<V, K, T> {
+    /// The entry as a JSON object, safe to use as a token footer.
+    ///
+    /// The purpose is escaped as a JSON string.
+    pub fn to_footer_json(&self) -> String {
+        use fmt::Write;
+
+        let mut s = String::from(r#"{"kid":""#);
+        self.id
+            .write_footer_fragment(&mut s)
+            .expect("writing to a string should not fail");
+        s.push_str(r#"","key":""#);
+        self.key
+            .write_footer_fragment(&mut s)
+            .expect("writing to a string should not fail");
+        write!(s, r#"","iat":{}"#, unix_secs(self.created)).unwrap();
+        if let Some(expires) = self.expires {
+            write!(s, r#","exp":{}"#, unix_secs(expires)).unwrap();
+        }
+        s.push_str(r#","purpose":"#);
+        crate::push_json_str(&mut s, &self.purpose);
+        s.push('}');
+        s
+    }
+}
+
+impl<V: Version, K: KeyType<V>, T: Clone> Clone for KeyEntry<V, K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            key: self.key.clone(),
+            created: self.created,
+            expires: self.expires,
+            purpose: self.purpose.clone(),
+        }
+    }
+}
+
+impl<V: Version, K: KeyType<V>, T> fmt::Debug for KeyEntry<V, K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyEntry")
+            .field("id", &self.id)
+            .field("created", &self.created)
+            .field("expires", &self.expires)
+            .field("purpose", &self.purpose)
+            .finish_non_exhaustive()
+    }
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: Version, K: KeyType<V>, T: fmt::Display> serde::Serialize for KeyEntry<V, K, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        struct AsStr<'a, T>(&'a T);
+        impl<T: fmt::Display> serde::Serialize for AsStr<'_, T> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self.0)
+            }
+        }
+
+        let len = if self.expires.is_some() { 5 } else { 4 };
+        let mut s = serializer.serialize_struct("KeyEntry", len)?;
+        s.serialize_field("kid", &AsStr(&self.id))?;
+        s.serialize_field("key", &AsStr(&self.key))?;
+        s.serialize_field("iat", &unix_secs(self.created))?;
+        match self.expires {
+            Some(expires) => s.serialize_field("exp", &unix_secs(expires))?,
+            None => s.skip_field("exp")?,
+        }
+        s.serialize_field("purpose", &self.purpose)?;
+        s.end()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V, K, T> serde::Deserialize<'de> for KeyEntry<V, K, T>
+where
+    V: Version,
+    K: KeyType<V>,
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, MapAccess, Visitor};
+        use std::{borrow::Cow, marker::PhantomData};
+
+        const FIELDS: &[&str] = &["kid", "key", "iat", "exp", "purpose"];
+
+        struct EntryVisitor<V, K, T>(PhantomData<(V, K, T)>);
+        impl<'de, V, K, T> Visitor<'de> for EntryVisitor<V, K, T>
+        where
+            V: Version,
+            K: KeyType<V>,
+            T: std::str::FromStr,
+            T::Err: fmt::Display,
+        {
+            type Value = KeyEntry<V, K, T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a key entry")
+            }
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let (mut id, mut key, mut created, mut expires, mut purpose) =
+                    (None, None, None, None, None);
+                while let Some(field) = map.next_key::<Cow<'de, str>>()? {
+                    match &*field {
+                        "kid" => {
+                            let v = map.next_value::<Cow<'de, str>>()?;
+                            id = Some(v.parse::<KeyId<V, K>>().map_err(A::Error::custom)?);
+                        }
+                        "key" => {
+                            let v = map.next_value::<Cow<'de, str>>()?;
+                            key = Some(v.parse::<T>().map_err(A::Error::custom)?);
+                        }
+                        "iat" => created = Some(map.next_value::<u64>()?),
+                        "exp" => expires = map.next_value::<Option<u64>>()?,
+                        "purpose" => purpose = Some(map.next_value::<String>()?),
+                        other => return Err(A::Error::unknown_field(other, FIELDS)),
+                    }
+                }
+
+                let from_secs = |secs| {
+                    UNIX_EPOCH
+                        .checked_add(Duration::from_secs(secs))
+                        .ok_or_else(|| {
+                            A::Error::custom(format!("timestamp {secs} is out of range"))
+                        })
+                };
+                Ok(KeyEntry {
+                    id: id.ok_or_else(|| A::Error::missing_field("kid"))?,
+                    key: key.ok_or_else(|| A::Error::missing_field("key"))?,
+                    created: from_secs(created.ok_or_else(|| A::Error::missing_field("iat"))?)?,
+                    expires: expires.map(from_secs).transpose()?,
+                    purpose: purpose.ok_or_else(|| A::Error::missing_field("purpose"))?,
+                })
+            }
+        }
+        deserializer.deserialize_struct("KeyEntry", FIELDS, EntryVisitor(PhantomData))
+    }
+}
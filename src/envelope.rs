@@ -0,0 +1,173 @@
+//! A complete small protocol for encrypting messages to several recipients.
+
+use std::{fmt, str::FromStr};
+
+use base64::Engine;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::PasetoError;
+
+use crate::{
+    internal::OnionVersion, FromPaserkBytes, Key, KeyId, Local, Public, SealedKeyEnvelope, Secret,
+};
+
+const FOOTER_START: &str = r#"{"wpk":["#;
+const FOOTER_END: &str = "]}";
+
+/// A message encrypted to a list of public keys, as a single `local` PASETO token.
+///
+/// This composes the pieces of this crate into a small protocol:
+/// 1. Generate a fresh [`Key<V, Local>`](Key).
+/// 2. [Seal](Key::seal_for) it to each recipient.
+/// 3. Encrypt the payload as a `local` token, with the sealed keys in the footer as
+///    `{"wpk":["<pid>:<seal>",...]}`.
+///
+/// The footer is authenticated by the token, so recipients cannot be added or removed without
+/// opening failing. Any PASETO library can read the footer to find the sealed keys.
+///
+/// ```
+/// use rusty_paserk::{Envelope, Key, Secret, V4};
+///
+/// let alice = Key::<V4, Secret>::new_os_random();
+/// let bob = Key::<V4, Secret>::new_os_random();
+///
+/// let envelope = Envelope::seal("meet at noon", &[alice.public_key(), bob.public_key()])
+///     .unwrap()
+///     .to_string();
+/// // => "v4.local.KqIAoq0DKIRn6Thk7eBb..."
+///
+/// let envelope: Envelope<V4> = envelope.parse().unwrap();
+/// assert_eq!(envelope.open(&alice).unwrap(), "meet at noon");
+/// assert_eq!(envelope.open(&bob).unwrap(), "meet at noon");
+///
+/// let eve = Key::<V4, Secret>::new_os_random();
+/// assert!(envelope.open(&eve).is_err());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "envelope")))]
+pub struct Envelope<V: OnionVersion> {
+    recipients: Vec<SealedKeyEnvelope<V>>,
+    footer: String,
+    token: String,
+}
+
+impl<V: OnionVersion> Envelope<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Encrypt `payload` so that any of `recipients` can open it
+    pub fn seal(payload: &str, recipients: &[Key<V, Public>]) -> Result<Self, PasetoError> {
+        Self::seal_with_rng(payload, recipients, &mut OsRng)
+    }
+
+    /// Encrypt `payload` so that any of `recipients` can open it,
+    /// using the provided random source.
+    pub fn seal_with_rng(
+        payload: &str,
+        recipients: &[Key<V, Public>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, PasetoError> {
+        let key = Key::<V, Local>::new_random(rng);
+        let recipients: Vec<_> = recipients
+            .iter()
+            .map(|pk| SealedKeyEnvelope {
+                recipient: pk.to_id(),
+                sealed: key.seal_with_rng(pk, rng),
+            })
+            .collect();
+        let footer = footer(&recipients);
+
+        let mut nonce = [0; 32];
+        rng.fill_bytes(&mut nonce);
+        let token = V::encrypt(&key, &nonce, payload, &footer)?;
+
+        Ok(Self {
+            recipients,
+            footer,
+            token,
+        })
+    }
+
+    /// The IDs of the keys that can open this envelope
+    pub fn recipients(&self) -> impl Iterator<Item = &KeyId<V, Public>> {
+        self.recipients.iter().map(|r| &r.recipient)
+    }
+
+    /// Decrypt the payload with one of the recipient's secret keys
+    pub fn open(&self, identity: &Key<V, Secret>) -> Result<String, PasetoError>
+    where
+        Key<V, Public>: for<'a> From<&'a Key<V, Secret>>,
+    {
+        let id = Key::<V, Public>::from(identity).to_id();
+        let envelope = self
+            .recipients
+            .iter()
+            .find(|r| r.recipient == id)
+            .ok_or(PasetoError::InvalidKey)?;
+
+        let key = envelope.sealed.clone().unseal(identity)?;
+        V::decrypt(&key, &self.token, &self.footer)
+    }
+}
+
+fn footer<V: OnionVersion>(recipients: &[SealedKeyEnvelope<V>]) -> String {
+    let mut footer = String::from(FOOTER_START);
+    for (i, recipient) in recipients.iter().enumerate() {
+        if i > 0 {
+            footer.push(',');
+        }
+        footer.push('"');
+        footer.push_str(&recipient.to_string());
+        footer.push('"');
+    }
+    footer.push_str(FOOTER_END);
+    footer
+}
+
+impl<V: OnionVersion> FromStr for Envelope<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix(V::TOKEN_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let rest = rest
+            .strip_prefix("local.")
+            .ok_or(PasetoError::WrongHeader)?;
+        let (_payload, footer) = rest.split_once('.').ok_or(PasetoError::IncorrectSize)?;
+        let footer = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(footer)
+            .map_err(|_| PasetoError::PayloadBase64Decode {
+                source: base64::DecodeError::InvalidLength(footer.len()),
+            })?;
+        let footer = String::from_utf8(footer).map_err(|_| PasetoError::InvalidKey)?;
+
+        let list = footer
+            .strip_prefix(FOOTER_START)
+            .and_then(|f| f.strip_suffix(FOOTER_END))
+            .ok_or(PasetoError::InvalidKey)?;
+        let recipients = if list.is_empty() {
+            Vec::new()
+        } else {
+            list.split(',')
+                .map(|r| {
+                    let r = r
+                        .strip_prefix('"')
+                        .and_then(|r| r.strip_suffix('"'))
+                        .ok_or(PasetoError::InvalidKey)?;
+                    SealedKeyEnvelope::from_paserk_bytes(r.as_bytes())
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(Self {
+            recipients,
+            footer,
+            token: s.to_owned(),
+        })
+    }
+}
+
+impl<V: OnionVersion> fmt::Display for Envelope<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
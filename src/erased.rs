@@ -0,0 +1,90 @@
+//! Keys with their version and type erased, for storing different keys in one collection.
+
+use std::fmt;
+
+use crate::{AnyKeyId, Key, KeyId, KeyType, PaserkVersion, PlaintextKey, Version};
+
+/// The type of a key, known at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyKind {
+    /// `local.` symmetric keys
+    Local,
+    /// `public.` verification or sealing keys
+    Public,
+    /// `secret.` signing or unsealing keys
+    Secret,
+}
+
+/// An object safe view of a [`Key`], for plugin systems that store keys of any version and type
+/// in one collection.
+///
+/// ```
+/// use rusty_paserk::{ErasedKey, Key, KeyKind, Local, PaserkVersion, Secret, V4};
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let keys: Vec<Box<dyn ErasedKey>> = vec![
+///     Box::new(Key::<V4, Local>::new_os_random()),
+///     Box::new(secret_key.public_key()),
+///     Box::new(secret_key),
+/// ];
+///
+/// assert_eq!(keys[1].version(), PaserkVersion::V4);
+/// assert_eq!(keys[1].kind(), KeyKind::Public);
+/// assert_eq!(keys[1].id().to_string(), secret_key.public_key().to_id().to_string());
+/// assert!(keys[2].to_plaintext().starts_with("k4.secret."));
+/// ```
+pub trait ErasedKey {
+    /// The version of the key
+    fn version(&self) -> PaserkVersion;
+
+    /// The type of the key
+    fn kind(&self) -> KeyKind;
+
+    /// The ID of the key
+    fn id(&self) -> AnyKeyId;
+
+    /// The key as a [`PlaintextKey`] string. This is not a secure serialization.
+    fn to_plaintext(&self) -> String;
+}
+
+impl<V: Version, K: KeyType<V>> ErasedKey for Key<V, K>
+where
+    KeyId<V, K>: From<Key<V, K>>,
+    AnyKeyId: From<KeyId<V, K>>,
+{
+    fn version(&self) -> PaserkVersion {
+        match V::KEY_HEADER {
+            #[cfg(feature = "v2")]
+            "k2." => PaserkVersion::V2,
+            "k3." => PaserkVersion::V3,
+            "k4." => PaserkVersion::V4,
+            header => unreachable!("unknown version header {header}"),
+        }
+    }
+
+    fn kind(&self) -> KeyKind {
+        match K::HEADER {
+            "local." => KeyKind::Local,
+            "public." => KeyKind::Public,
+            "secret." => KeyKind::Secret,
+            header => unreachable!("unknown key header {header}"),
+        }
+    }
+
+    fn id(&self) -> AnyKeyId {
+        self.to_id().into()
+    }
+
+    fn to_plaintext(&self) -> String {
+        PlaintextKey(self.clone()).to_string()
+    }
+}
+
+impl fmt::Debug for dyn ErasedKey + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErasedKey")
+            .field("id", &self.id())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,61 @@
+use rusty_paseto::core::PasetoError;
+
+/// Stable codes for [`PasetoError`]s, for alerting rules and client facing error mapping.
+///
+/// The codes group errors by cause and do not change when the error variants or messages do.
+///
+/// | code | numeric | cause |
+/// |---|---|---|
+/// | `crypto` | 100 | encryption, decryption or signing failed |
+/// | `invalid_key` | 200 | key material was the wrong length or format |
+/// | `invalid_signature` | 300 | a signature did not verify |
+/// | `wrong_header` | 400 | the PASERK or PASETO header was unexpected |
+/// | `malformed` | 401 | the value could not be decoded |
+/// | `invalid_footer` | 402 | the token footer did not match |
+///
+/// ```
+/// use rusty_paserk::{ErrorCode, Local, PlaintextKey, V4};
+///
+/// let err = "k4.public.AAAA".parse::<PlaintextKey<V4, Local>>().err().unwrap();
+/// assert_eq!(err.code(), "wrong_header");
+/// assert_eq!(err.numeric_code(), 400);
+/// ```
+pub trait ErrorCode {
+    /// The stable string code for this error
+    fn code(&self) -> &'static str;
+
+    /// The stable numeric code for this error
+    fn numeric_code(&self) -> u16;
+}
+
+impl ErrorCode for PasetoError {
+    fn code(&self) -> &'static str {
+        match self.numeric_code() {
+            100 => "crypto",
+            200 => "invalid_key",
+            300 => "invalid_signature",
+            400 => "wrong_header",
+            402 => "invalid_footer",
+            _ => "malformed",
+        }
+    }
+
+    // some variants only exist with some features of rusty_paseto
+    #[allow(unreachable_patterns)]
+    fn numeric_code(&self) -> u16 {
+        match self {
+            PasetoError::InvalidKey
+            | PasetoError::KeyRejected { .. }
+            | PasetoError::TryFromSlice { .. } => 200,
+            PasetoError::InvalidSignature => 300,
+            PasetoError::WrongHeader => 400,
+            PasetoError::IncorrectSize
+            | PasetoError::PayloadBase64Decode { .. }
+            | PasetoError::Utf8Error { .. }
+            | PasetoError::FromUtf8Error { .. }
+            | PasetoError::Infallibale { .. } => 401,
+            PasetoError::FooterInvalid => 402,
+            _ => 100,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! One-shot session key exchange built on [`seal`](crate::Key::seal).
+//!
+//! The initiator generates a fresh local key and seals it to the peer's public key.
+//! Only the holder of the matching secret key can recover it, but the peer learns nothing about
+//! who sent it. If the initiator needs to be authenticated too, sign the sealed key or send it
+//! inside a public token.
+//!
+//! ```
+//! use rusty_paserk::{exchange, Key, Secret, V4};
+//!
+//! let peer_sk = Key::<V4, Secret>::new_os_random();
+//! let peer_pk = peer_sk.public_key();
+//!
+//! // initiator
+//! let (session_key, sealed) = exchange::initiate(&peer_pk);
+//! let message = sealed.to_string();
+//!
+//! // peer
+//! let session_key2 = exchange::accept(message.parse().unwrap(), &peer_sk).unwrap();
+//! assert_eq!(session_key, session_key2);
+//! ```
+
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::PasetoError;
+
+use crate::{internal::SealedVersion, Key, Local, Public, SealedKey, Secret};
+
+/// Generate a new local session key, sealed to `peer_pk`.
+///
+/// Keep the returned key, and send the [`SealedKey`] to the peer.
+pub fn initiate<V: SealedVersion>(peer_pk: &Key<V, Public>) -> (Key<V, Local>, SealedKey<V>) {
+    initiate_with_rng(peer_pk, &mut OsRng)
+}
+
+/// Generate a new local session key, sealed to `peer_pk`, using the provided random source.
+pub fn initiate_with_rng<V: SealedVersion>(
+    peer_pk: &Key<V, Public>,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> (Key<V, Local>, SealedKey<V>) {
+    let key = Key::<V, Local>::new_random(rng);
+    let sealed = key.seal_with_rng(peer_pk, rng);
+    (key, sealed)
+}
+
+/// Recover the local session key sent by [`initiate`].
+pub fn accept<V: SealedVersion>(
+    sealed: SealedKey<V>,
+    my_sk: &Key<V, Secret>,
+) -> Result<Key<V, Local>, PasetoError> {
+    sealed.unseal(my_sk)
+}
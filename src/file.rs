@@ -0,0 +1,143 @@
+//! Encrypting small files to a team of recipients.
+
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::PasetoError;
+
+use crate::{
+    internal::OnionVersion, FromPaserkBytes, Key, KeyId, Local, Public, SealedKeyEnvelope, Secret,
+};
+
+const BEGIN: &str = "-----BEGIN PASERK ENCRYPTED FILE-----";
+const END: &str = "-----END PASERK ENCRYPTED FILE-----";
+
+/// A small text file, such as a config file, encrypted to a list of public keys.
+///
+/// A fresh local key encrypts the contents as a `local` PASETO token, and is sealed to each
+/// recipient. The sealed keys are listed in a header block, one [`SealedKeyEnvelope`] per line,
+/// so changes to the recipients show up clearly in code review.
+///
+/// ```
+/// use rusty_paserk::{EncryptedFile, Key, Secret, V4};
+///
+/// let alice = Key::<V4, Secret>::new_os_random();
+/// let bob = Key::<V4, Secret>::new_os_random();
+///
+/// let file = EncryptedFile::encrypt(
+///     "database_password = hunter2\n",
+///     &[alice.public_key(), bob.public_key()],
+/// )
+/// .unwrap()
+/// .to_string();
+/// // => "-----BEGIN PASERK ENCRYPTED FILE-----
+/// // k4.pid.yMgldRRLHBLkhmcp8NG8yZrtyldbYoAjQWPv_Ma1rzRu:k4.seal.23KlrMHZLW4muL75Rnuqtaro9F16mqDNvmCbgDXi2IdNyWmjrbTVBEih1DhSI_5xp7b7mCHSFo1DMv-9GtZUSpyi4646XBxpbFShHjJihF_Af8maWsDqdzOof76ia0Cv
+/// // k4.pid.eWXbNfpYA4bVZcLp3s1hBYY5uYNpHnZtbjhp7z3MYMNQ:k4.seal.ZaxhHe4xvK3RsCfZJjJIx6QiQ8mScKxjzXKmPvYRxkEgDh3N7ZkcXwvX1tzlwR6Yw0z_ZVmvFVGsFygmIvJw2qBVcgMpEPJqbtKzmijmqPXdsBiUiWLy8Sbl6mAdfyGR
+/// // -----END PASERK ENCRYPTED FILE-----
+/// // v4.local.zU2Zgnf4sLLOpdb4f3q9...
+/// // "
+///
+/// let file: EncryptedFile<V4> = file.parse().unwrap();
+/// assert_eq!(file.decrypt(&bob).unwrap(), "database_password = hunter2\n");
+///
+/// let eve = Key::<V4, Secret>::new_os_random();
+/// assert!(file.decrypt(&eve).is_err());
+/// ```
+pub struct EncryptedFile<V: OnionVersion> {
+    recipients: Vec<SealedKeyEnvelope<V>>,
+    token: String,
+}
+
+impl<V: OnionVersion> EncryptedFile<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Encrypt `contents` so that any of `recipients` can decrypt it
+    pub fn encrypt(contents: &str, recipients: &[Key<V, Public>]) -> Result<Self, PasetoError> {
+        Self::encrypt_with_rng(contents, recipients, &mut OsRng)
+    }
+
+    /// Encrypt `contents` so that any of `recipients` can decrypt it,
+    /// using the provided random source.
+    pub fn encrypt_with_rng(
+        contents: &str,
+        recipients: &[Key<V, Public>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, PasetoError> {
+        let key = Key::<V, Local>::new_random(rng);
+        let recipients = recipients
+            .iter()
+            .map(|pk| SealedKeyEnvelope {
+                recipient: pk.to_id(),
+                sealed: key.seal_with_rng(pk, rng),
+            })
+            .collect();
+
+        let mut nonce = [0; 32];
+        rng.fill_bytes(&mut nonce);
+        let token = V::encrypt(&key, &nonce, contents, "")?;
+
+        Ok(Self { recipients, token })
+    }
+
+    /// The IDs of the keys that can decrypt this file
+    pub fn recipients(&self) -> impl Iterator<Item = &KeyId<V, Public>> {
+        self.recipients.iter().map(|r| &r.recipient)
+    }
+
+    /// Decrypt the contents with one of the recipient's secret keys
+    pub fn decrypt(&self, secret_key: &Key<V, Secret>) -> Result<String, PasetoError>
+    where
+        Key<V, Public>: for<'a> From<&'a Key<V, Secret>>,
+    {
+        let id = Key::<V, Public>::from(secret_key).to_id();
+        let envelope = self
+            .recipients
+            .iter()
+            .find(|r| r.recipient == id)
+            .ok_or(PasetoError::InvalidKey)?;
+
+        let key = envelope.sealed.clone().unseal(secret_key)?;
+        V::decrypt(&key, &self.token, "")
+    }
+}
+
+impl<V: OnionVersion> FromStr for EncryptedFile<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim);
+        if lines.next() != Some(BEGIN) {
+            return Err(PasetoError::WrongHeader);
+        }
+
+        let mut recipients = Vec::new();
+        for line in lines.by_ref() {
+            if line == END {
+                break;
+            }
+            recipients.push(SealedKeyEnvelope::from_paserk_bytes(line.as_bytes())?);
+        }
+
+        let token = lines.next().ok_or(PasetoError::IncorrectSize)?;
+        if !token.starts_with(V::TOKEN_HEADER) || lines.any(|line| !line.is_empty()) {
+            return Err(PasetoError::IncorrectSize);
+        }
+
+        Ok(Self {
+            recipients,
+            token: token.to_owned(),
+        })
+    }
+}
+
+impl<V: OnionVersion> fmt::Display for EncryptedFile<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{BEGIN}")?;
+        for recipient in &self.recipients {
+            writeln!(f, "{recipient}")?;
+        }
+        writeln!(f, "{END}")?;
+        writeln!(f, "{}", self.token)
+    }
+}
@@ -0,0 +1,98 @@
+//! Sample values for testing other PASERK implementations against this one.
+
+use std::fmt::Write;
+
+use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use crate::{
+    internal::{OnionVersion, PieVersion, PieWrapType},
+    Key, KeyId, Local, PlaintextKey, Public, Secret,
+};
+
+/// Generate a JSON bundle of sample keys, IDs, sealed keys, wrapped keys and tokens
+/// for every version enabled in this build.
+///
+/// The output only depends on `seed`, so partner teams can regenerate the same bundle.
+/// The bundle has one object per version, keyed by `k3`/`k4`.
+///
+/// ```
+/// let json = rusty_paserk::fixtures::interop_fixtures([7; 32]);
+/// assert_eq!(json, rusty_paserk::fixtures::interop_fixtures([7; 32]));
+///
+/// let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+/// let k4 = &json["k4"];
+/// assert!(k4["local"].as_str().unwrap().starts_with("k4.local."));
+/// assert!(k4["token"].as_str().unwrap().starts_with("v4.local."));
+/// assert_eq!(k4["payload"], "paserk interop fixture");
+/// ```
+pub fn interop_fixtures(seed: [u8; 32]) -> String {
+    let mut rng = StdRng::from_seed(seed);
+    let mut versions = Vec::<String>::new();
+
+    #[cfg(feature = "v3")]
+    {
+        let secret = Key::<V3, Secret>::new_random(&mut rng);
+        versions.push(version_fixtures(secret, &mut rng));
+    }
+    #[cfg(feature = "v4")]
+    {
+        let secret = Key::<V4, Secret>::new_random(&mut rng);
+        versions.push(version_fixtures(secret, &mut rng));
+    }
+
+    format!("{{\n{}\n}}\n", versions.join(",\n"))
+}
+
+const PAYLOAD: &str = "paserk interop fixture";
+
+fn version_fixtures<V>(secret: Key<V, Secret>, rng: &mut (impl RngCore + CryptoRng)) -> String
+where
+    V: OnionVersion + PieVersion,
+    Local: PieWrapType<V>,
+    KeyId<V, Local>: From<Key<V, Local>>,
+    KeyId<V, Public>: From<Key<V, Public>>,
+    KeyId<V, Secret>: From<Key<V, Secret>>,
+    Key<V, Public>: for<'a> From<&'a Key<V, Secret>>,
+{
+    let local = Key::<V, Local>::new_random(rng);
+    let wrapping = Key::<V, Local>::new_random(rng);
+    let public = Key::<V, Public>::from(&secret);
+
+    let mut nonce = [0; 32];
+    rng.fill_bytes(&mut nonce);
+    let token =
+        V::encrypt(&local, &nonce, PAYLOAD, "").expect("encrypting a fixture should not fail");
+
+    let fields = [
+        ("local", PlaintextKey(local.clone()).to_string()),
+        ("lid", local.to_id().to_string()),
+        ("secret", PlaintextKey(secret.clone()).to_string()),
+        ("sid", secret.to_id().to_string()),
+        ("public", PlaintextKey(public.clone()).to_string()),
+        ("pid", public.to_id().to_string()),
+        ("seal", local.seal_with_rng(&public, rng).to_string()),
+        ("wrapping-key", PlaintextKey(wrapping.clone()).to_string()),
+        (
+            "local-wrap.pie",
+            local.wrap_pie_with_rng(&wrapping, rng).to_string(),
+        ),
+        ("payload", PAYLOAD.to_owned()),
+        ("token", token),
+    ];
+
+    // all of the values are base64url and headers, so they never need escaping
+    let mut out = String::new();
+    writeln!(out, "  \"{}\": {{", V::KEY_HEADER.trim_end_matches('.')).unwrap();
+    let fields = fields
+        .iter()
+        .map(|(name, value)| format!("    \"{name}\": \"{value}\""))
+        .collect::<Vec<_>>();
+    out.push_str(&fields.join(",\n"));
+    out.push_str("\n  }");
+    out
+}
@@ -0,0 +1,74 @@
+//! Referring to keys by ID, and only loading them when they are used.
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{Key, KeyId, KeyType, Version};
+
+/// A [`KeyId`] together with a way to load the key it refers to.
+///
+/// APIs can be written against handles, so that the key material is only loaded at the
+/// moment it is used, and dropped again immediately after.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyHandle, Local, PlaintextKey, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let stored = PlaintextKey(key).to_string();
+///
+/// let handle = KeyHandle::new(key.to_id(), move |_id| {
+///     // in practice, read this from a secrets manager or HSM
+///     let PlaintextKey(key) = stored.parse()?;
+///     Ok(key)
+/// });
+///
+/// let id = handle.with_key(|key| Ok(key.to_id())).unwrap();
+/// assert_eq!(&id, handle.id());
+///
+/// // a resolver that returns the wrong key is rejected
+/// let wrong = KeyHandle::new(key.to_id(), |_id| Ok(Key::<V4, Local>::new_os_random()));
+/// assert!(wrong.with_key(|_key| Ok(())).is_err());
+/// ```
+pub struct KeyHandle<V: Version, K: KeyType<V>, R> {
+    id: KeyId<V, K>,
+    resolver: R,
+}
+
+impl<V, K, R> KeyHandle<V, K, R>
+where
+    V: Version,
+    K: KeyType<V>,
+    KeyId<V, K>: From<Key<V, K>>,
+    R: Fn(&KeyId<V, K>) -> Result<Key<V, K>, PasetoError>,
+{
+    /// A handle to the key with this ID, loaded by `resolver` when it is needed
+    pub fn new(id: KeyId<V, K>, resolver: R) -> Self {
+        Self { id, resolver }
+    }
+
+    /// The ID of the key this handle refers to
+    pub fn id(&self) -> &KeyId<V, K> {
+        &self.id
+    }
+
+    /// Load the key, use it, and drop it again.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the resolver returns a key with a different ID.
+    pub fn with_key<T>(
+        &self,
+        f: impl FnOnce(&Key<V, K>) -> Result<T, PasetoError>,
+    ) -> Result<T, PasetoError> {
+        let key = (self.resolver)(&self.id)?;
+        if !bool::from(self.id.is_id_of(&key)) {
+            return Err(PasetoError::InvalidKey);
+        }
+        f(&key)
+    }
+}
+
+impl<V: Version, K: KeyType<V>, R> std::fmt::Debug for KeyHandle<V, K, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyHandle")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,232 @@
+//! Compile time checks for PASERK headers.
+//!
+//! Every header is checked against the [PASERK spec](https://github.com/paseto-standard/paserk#paserk)
+//! when this crate is compiled, so adding a version or key type with a mismatched prefix fails
+//! to build instead of silently producing incompatible keys.
+//!
+//! ```
+//! use rusty_paserk::{headers, KeyType, Local, Version, V4};
+//!
+//! const _: () = assert!(headers::is_version_header(V4::KEY_HEADER, 'k'));
+//! const _: () = assert!(headers::is_concat(
+//!     &[V4::KEY_HEADER, <Local as KeyType<V4>>::ID],
+//!     "k4.lid."
+//! ));
+//! ```
+
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use generic_array::typenum::Unsigned;
+
+use crate::{KeyType, Local, PaserkVersion, Public, Secret, Version};
+
+/// Whether `header` is a version header, such as `k4.` for keys or `v4.` for tokens
+pub const fn is_version_header(header: &str, prefix: char) -> bool {
+    let b = header.as_bytes();
+    if b.len() < 3 || b[0] != prefix as u8 || b[b.len() - 1] != b'.' {
+        return false;
+    }
+    let mut i = 1;
+    while i < b.len() - 1 {
+        if !b[i].is_ascii_digit() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Whether `header` is a type header, such as `local.` or `secret-wrap.`
+pub const fn is_type_header(header: &str) -> bool {
+    let b = header.as_bytes();
+    if b.len() < 2 || b[b.len() - 1] != b'.' {
+        return false;
+    }
+    let mut i = 0;
+    while i < b.len() - 1 {
+        if !(b[i].is_ascii_lowercase() || b[i] == b'-') {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Whether the concatenation of `parts` is exactly `expected`
+pub const fn is_concat(parts: &[&str], expected: &str) -> bool {
+    let expected = expected.as_bytes();
+    let mut n = 0;
+    let mut p = 0;
+    while p < parts.len() {
+        let part = parts[p].as_bytes();
+        let mut i = 0;
+        while i < part.len() {
+            if n >= expected.len() || part[i] != expected[n] {
+                return false;
+            }
+            i += 1;
+            n += 1;
+        }
+        p += 1;
+    }
+    n == expected.len()
+}
+
+/// Whether the key and token headers of a version agree, eg `k4.` and `v4.`
+pub const fn is_version_pair(key_header: &str, token_header: &str) -> bool {
+    if !is_version_header(key_header, 'k') || !is_version_header(token_header, 'v') {
+        return false;
+    }
+    let k = key_header.as_bytes();
+    let v = token_header.as_bytes();
+    if k.len() != v.len() {
+        return false;
+    }
+    let mut i = 1;
+    while i < k.len() {
+        if k[i] != v[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn check_version<V: Version>(version: PaserkVersion) -> bool {
+    is_version_pair(V::KEY_HEADER, V::TOKEN_HEADER) && is_concat(&[V::KEY_HEADER], version.header())
+}
+
+const fn check_key_types<V: Version>() -> bool {
+    is_type_header(<Local as KeyType<V>>::HEADER)
+        && is_type_header(<Public as KeyType<V>>::HEADER)
+        && is_type_header(<Secret as KeyType<V>>::HEADER)
+        && is_type_header(<Local as KeyType<V>>::ID)
+        && is_type_header(<Public as KeyType<V>>::ID)
+        && is_type_header(<Secret as KeyType<V>>::ID)
+}
+
+const fn check_key_lengths<V: Version>(local: usize, public: usize, secret: usize) -> bool {
+    <Local as KeyType<V>>::KeyLen::USIZE == local
+        && <Public as KeyType<V>>::KeyLen::USIZE == public
+        && <Secret as KeyType<V>>::KeyLen::USIZE == secret
+}
+
+#[cfg(feature = "v2")]
+const _: () = {
+    assert!(check_version::<V2>(PaserkVersion::V2));
+    assert!(check_key_types::<V2>());
+    assert!(check_key_lengths::<V2>(32, 32, 64));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Local as KeyType<V2>>::HEADER],
+        "k2.local."
+    ));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Public as KeyType<V2>>::HEADER],
+        "k2.public."
+    ));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Secret as KeyType<V2>>::HEADER],
+        "k2.secret."
+    ));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Local as KeyType<V2>>::ID],
+        "k2.lid."
+    ));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Public as KeyType<V2>>::ID],
+        "k2.pid."
+    ));
+    assert!(is_concat(
+        &[V2::KEY_HEADER, <Secret as KeyType<V2>>::ID],
+        "k2.sid."
+    ));
+};
+
+#[cfg(feature = "v3")]
+const _: () = {
+    assert!(check_version::<V3>(PaserkVersion::V3));
+    assert!(check_key_types::<V3>());
+    assert!(check_key_lengths::<V3>(32, 49, 48));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Local as KeyType<V3>>::HEADER],
+        "k3.local."
+    ));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Public as KeyType<V3>>::HEADER],
+        "k3.public."
+    ));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Secret as KeyType<V3>>::HEADER],
+        "k3.secret."
+    ));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Local as KeyType<V3>>::ID],
+        "k3.lid."
+    ));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Public as KeyType<V3>>::ID],
+        "k3.pid."
+    ));
+    assert!(is_concat(
+        &[V3::KEY_HEADER, <Secret as KeyType<V3>>::ID],
+        "k3.sid."
+    ));
+};
+
+#[cfg(feature = "v4")]
+const _: () = {
+    assert!(check_version::<V4>(PaserkVersion::V4));
+    assert!(check_key_types::<V4>());
+    assert!(check_key_lengths::<V4>(32, 32, 64));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Local as KeyType<V4>>::HEADER],
+        "k4.local."
+    ));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Public as KeyType<V4>>::HEADER],
+        "k4.public."
+    ));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Secret as KeyType<V4>>::HEADER],
+        "k4.secret."
+    ));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Local as KeyType<V4>>::ID],
+        "k4.lid."
+    ));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Public as KeyType<V4>>::ID],
+        "k4.pid."
+    ));
+    assert!(is_concat(
+        &[V4::KEY_HEADER, <Secret as KeyType<V4>>::ID],
+        "k4.sid."
+    ));
+};
+
+const _: () = {
+    use crate::wrap::WrapType;
+    assert!(is_type_header(<Local as WrapType>::WRAP_HEADER));
+    assert!(is_type_header(<Secret as WrapType>::WRAP_HEADER));
+    assert!(is_concat(
+        &[<Local as WrapType>::WRAP_HEADER],
+        "local-wrap."
+    ));
+    assert!(is_concat(
+        &[<Secret as WrapType>::WRAP_HEADER],
+        "secret-wrap."
+    ));
+};
+
+const _: () = {
+    use crate::pbkw::PwType;
+    assert!(is_type_header(<Local as PwType>::WRAP_HEADER));
+    assert!(is_type_header(<Secret as PwType>::WRAP_HEADER));
+    assert!(is_concat(&[<Local as PwType>::WRAP_HEADER], "local-pw."));
+    assert!(is_concat(&[<Secret as PwType>::WRAP_HEADER], "secret-pw."));
+};
@@ -3,12 +3,15 @@ use std::{fmt, marker::PhantomData, str::FromStr};
 use generic_array::{typenum::U33, GenericArray};
 
 use rusty_paseto::core::PasetoError;
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
 use rusty_paseto::core::V4;
+use subtle::{Choice, ConstantTimeEq};
 
-use crate::{write_b64, Key, KeyType, Version};
+use crate::{write_b64, Key, KeyType, Local, Public, Secret, Version};
 
 /// Unique ID for a key
 ///
@@ -93,6 +96,12 @@ impl<V: Version, K: KeyType<V>> Clone for KeyId<V, K> {
 }
 impl<V: Version, K: KeyType<V>> Copy for KeyId<V, K> {}
 
+impl<V: Version, K: KeyType<V>> KeyId<V, K> {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.id
+    }
+}
+
 impl<V: Version, K: KeyType<V>> Key<V, K>
 where
     KeyId<V, K>: From<Self>,
@@ -113,65 +122,373 @@ where
     }
 }
 
+impl<V: Version, K: KeyType<V>> KeyId<V, K>
+where
+    Self: From<Key<V, K>>,
+{
+    /// Check in constant time whether this is the ID of the given key.
+    ///
+    /// ```
+    /// use rusty_paserk::{KeyId, Key, Local, V4};
+    ///
+    /// let local_key = Key::<V4, Local>::new_os_random();
+    /// let kid: KeyId<V4, Local> = "k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB".parse().unwrap();
+    ///
+    /// assert!(bool::from(local_key.to_id().is_id_of(&local_key)));
+    /// assert!(!bool::from(kid.is_id_of(&local_key)));
+    /// ```
+    pub fn is_id_of(&self, key: &Key<V, K>) -> Choice {
+        self.id.ct_eq(&key.to_id().id)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "arbitrary")]
+impl<'a, V: Version, K: KeyType<V>> arbitrary::Arbitrary<'a> for KeyId<V, K> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(KeyId {
+            id: crate::arbitrary_bytes(u)?,
+            key: PhantomData,
+        })
+    }
+}
+
 #[cfg(feature = "v3")]
 impl<K: KeyType<V3>> From<Key<V3, K>> for KeyId<V3, K> {
     fn from(key: Key<V3, K>) -> Self {
         use base64ct::{Base64UrlUnpadded, Encoding};
-        use sha2::digest::Digest;
 
         // V3 Public keys are 49 bytes, V3 private keys are 48 bytes, symmetric keys are 32 bytes.
         // allocate enough space for 49 bytes base64 encoded which is ~66
         let mut output = [0; 49 * 4 / 3 + 3];
         let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
 
-        let mut derive_d = sha2::Sha384::new();
-        derive_d.update(V3::KEY_HEADER);
-        derive_d.update(K::ID);
-        derive_d.update(V3::KEY_HEADER);
-        derive_d.update(K::HEADER);
-        derive_d.update(p);
-        let d = derive_d.finalize();
-        let id = *GenericArray::from_slice(&d[..33]);
-
         KeyId {
-            id,
+            id: v3_id(&[V3::KEY_HEADER, K::ID, V3::KEY_HEADER, K::HEADER, p]),
             key: PhantomData,
         }
     }
 }
 
+#[cfg(feature = "v3")]
+impl<K: KeyType<V3> + 'static> KeyId<V3, K> {
+    /// Compute the IDs of many keys, reusing the hasher state and buffers between keys.
+    ///
+    /// ```
+    /// use rusty_paserk::{KeyId, Key, Local, V3};
+    ///
+    /// let keys: Vec<_> = (0..4).map(|_| Key::<V3, Local>::new_os_random()).collect();
+    /// let ids: Vec<_> = KeyId::compute_many(&keys).collect();
+    /// assert_eq!(ids[2], keys[2].to_id());
+    /// ```
+    pub fn compute_many<'a>(
+        keys: impl IntoIterator<Item = &'a Key<V3, K>> + 'a,
+    ) -> impl Iterator<Item = Self> + 'a {
+        use base64ct::{Base64UrlUnpadded, Encoding};
+        use sha2::digest::Digest;
+
+        let prefix = sha2::Sha384::new()
+            .chain_update(V3::KEY_HEADER)
+            .chain_update(K::ID)
+            .chain_update(V3::KEY_HEADER)
+            .chain_update(K::HEADER);
+        let mut output = [0; 49 * 4 / 3 + 3];
+
+        keys.into_iter().map(move |key| {
+            let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
+            let d = prefix.clone().chain_update(p).finalize();
+            KeyId {
+                id: *GenericArray::from_slice(&d[..33]),
+                key: PhantomData,
+            }
+        })
+    }
+}
+
+#[cfg(feature = "v3")]
+pub(crate) fn v3_id(parts: &[&str]) -> GenericArray<u8, U33> {
+    use sha2::digest::Digest;
+
+    let mut derive_d = sha2::Sha384::new();
+    for part in parts {
+        derive_d.update(part);
+    }
+    let d = derive_d.finalize();
+    *GenericArray::from_slice(&d[..33])
+}
+
+#[cfg(feature = "v4")]
+macro_rules! impl_blake2_id {
+    ($v:ident) => {
+        impl<K: KeyType<$v>> From<Key<$v, K>> for KeyId<$v, K> {
+            fn from(key: Key<$v, K>) -> Self {
+                use base64ct::{Base64UrlUnpadded, Encoding};
+
+                // $v Public keys are 64 bytes, symmetric keys are 32 bytes.
+                // allocate enough space for 64 bytes base64 encoded
+                let mut output = [0; 64 * 4 / 3 + 3];
+                let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
+
+                KeyId {
+                    id: v4_id(&[$v::KEY_HEADER, K::ID, $v::KEY_HEADER, K::HEADER, p]),
+                    key: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "v4")]
+impl_blake2_id!(V4);
+#[cfg(feature = "v2")]
+impl_blake2_id!(V2);
+
 #[cfg(feature = "v4")]
-impl<K: KeyType<V4>> From<Key<V4, K>> for KeyId<V4, K> {
-    fn from(key: Key<V4, K>) -> Self {
+impl<K: KeyType<V4> + 'static> KeyId<V4, K> {
+    /// Compute the IDs of many keys, reusing the hasher state and buffers between keys.
+    ///
+    /// ```
+    /// use rusty_paserk::{KeyId, Key, Local, V4};
+    ///
+    /// let keys: Vec<_> = (0..4).map(|_| Key::<V4, Local>::new_os_random()).collect();
+    /// let ids: Vec<_> = KeyId::compute_many(&keys).collect();
+    /// assert_eq!(ids[2], keys[2].to_id());
+    /// ```
+    pub fn compute_many<'a>(
+        keys: impl IntoIterator<Item = &'a Key<V4, K>> + 'a,
+    ) -> impl Iterator<Item = Self> + 'a {
         use base64ct::{Base64UrlUnpadded, Encoding};
         use blake2::digest::Digest;
 
-        // V4 Public keys are 64 bytes, symmetric keys are 32 bytes.
-        // allocate enough space for 64 bytes base64 encoded
+        let prefix = blake2::Blake2b::<U33>::new()
+            .chain_update(V4::KEY_HEADER)
+            .chain_update(K::ID)
+            .chain_update(V4::KEY_HEADER)
+            .chain_update(K::HEADER);
         let mut output = [0; 64 * 4 / 3 + 3];
-        let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
 
-        let mut derive_d = blake2::Blake2b::<U33>::new();
-        derive_d.update(V4::KEY_HEADER);
-        derive_d.update(K::ID);
-        derive_d.update(V4::KEY_HEADER);
-        derive_d.update(K::HEADER);
-        derive_d.update(p);
-        let id = derive_d.finalize();
+        keys.into_iter().map(move |key| {
+            let p = Base64UrlUnpadded::encode(key.as_ref(), &mut output).unwrap();
+            KeyId {
+                id: prefix.clone().chain_update(p).finalize(),
+                key: PhantomData,
+            }
+        })
+    }
+}
 
-        KeyId {
-            id,
-            key: PhantomData,
+#[cfg(feature = "v4")]
+pub(crate) fn v4_id(parts: &[&str]) -> GenericArray<u8, U33> {
+    use blake2::digest::Digest;
+
+    let mut derive_d = blake2::Blake2b::<U33>::new();
+    for part in parts {
+        derive_d.update(part);
+    }
+    derive_d.finalize()
+}
+
+/// A [`KeyId`] of any supported version and key type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum AnyKeyId {
+    /// `k2.lid.`
+    #[cfg(feature = "v2")]
+    V2Local(KeyId<V2, Local>),
+    /// `k2.pid.`
+    #[cfg(feature = "v2")]
+    V2Public(KeyId<V2, Public>),
+    /// `k2.sid.`
+    #[cfg(feature = "v2")]
+    V2Secret(KeyId<V2, Secret>),
+    /// `k3.lid.`
+    #[cfg(feature = "v3")]
+    V3Local(KeyId<V3, Local>),
+    /// `k3.pid.`
+    #[cfg(feature = "v3")]
+    V3Public(KeyId<V3, Public>),
+    /// `k3.sid.`
+    #[cfg(feature = "v3")]
+    V3Secret(KeyId<V3, Secret>),
+    /// `k4.lid.`
+    #[cfg(feature = "v4")]
+    V4Local(KeyId<V4, Local>),
+    /// `k4.pid.`
+    #[cfg(feature = "v4")]
+    V4Public(KeyId<V4, Public>),
+    /// `k4.sid.`
+    #[cfg(feature = "v4")]
+    V4Secret(KeyId<V4, Secret>),
+}
+
+impl fmt::Display for AnyKeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "v2")]
+            AnyKeyId::V2Local(id) => id.fmt(f),
+            #[cfg(feature = "v2")]
+            AnyKeyId::V2Public(id) => id.fmt(f),
+            #[cfg(feature = "v2")]
+            AnyKeyId::V2Secret(id) => id.fmt(f),
+            #[cfg(feature = "v3")]
+            AnyKeyId::V3Local(id) => id.fmt(f),
+            #[cfg(feature = "v3")]
+            AnyKeyId::V3Public(id) => id.fmt(f),
+            #[cfg(feature = "v3")]
+            AnyKeyId::V3Secret(id) => id.fmt(f),
+            #[cfg(feature = "v4")]
+            AnyKeyId::V4Local(id) => id.fmt(f),
+            #[cfg(feature = "v4")]
+            AnyKeyId::V4Public(id) => id.fmt(f),
+            #[cfg(feature = "v4")]
+            AnyKeyId::V4Secret(id) => id.fmt(f),
         }
     }
 }
 
+#[cfg(feature = "v2")]
+impl From<KeyId<V2, Local>> for AnyKeyId {
+    fn from(id: KeyId<V2, Local>) -> Self {
+        AnyKeyId::V2Local(id)
+    }
+}
+#[cfg(feature = "v2")]
+impl From<KeyId<V2, Public>> for AnyKeyId {
+    fn from(id: KeyId<V2, Public>) -> Self {
+        AnyKeyId::V2Public(id)
+    }
+}
+#[cfg(feature = "v2")]
+impl From<KeyId<V2, Secret>> for AnyKeyId {
+    fn from(id: KeyId<V2, Secret>) -> Self {
+        AnyKeyId::V2Secret(id)
+    }
+}
+#[cfg(feature = "v3")]
+impl From<KeyId<V3, Local>> for AnyKeyId {
+    fn from(id: KeyId<V3, Local>) -> Self {
+        AnyKeyId::V3Local(id)
+    }
+}
+#[cfg(feature = "v3")]
+impl From<KeyId<V3, Public>> for AnyKeyId {
+    fn from(id: KeyId<V3, Public>) -> Self {
+        AnyKeyId::V3Public(id)
+    }
+}
+#[cfg(feature = "v3")]
+impl From<KeyId<V3, Secret>> for AnyKeyId {
+    fn from(id: KeyId<V3, Secret>) -> Self {
+        AnyKeyId::V3Secret(id)
+    }
+}
+#[cfg(feature = "v4")]
+impl From<KeyId<V4, Local>> for AnyKeyId {
+    fn from(id: KeyId<V4, Local>) -> Self {
+        AnyKeyId::V4Local(id)
+    }
+}
+#[cfg(feature = "v4")]
+impl From<KeyId<V4, Public>> for AnyKeyId {
+    fn from(id: KeyId<V4, Public>) -> Self {
+        AnyKeyId::V4Public(id)
+    }
+}
+#[cfg(feature = "v4")]
+impl From<KeyId<V4, Secret>> for AnyKeyId {
+    fn from(id: KeyId<V4, Secret>) -> Self {
+        AnyKeyId::V4Secret(id)
+    }
+}
+
+/// Compute the ID of a `local`, `public` or `secret` PASERK string, of any supported version.
+///
+/// The key data is validated but never decoded into a [`Key`]. If this fails, use
+/// [`PaserkVersion::of_paserk`](crate::PaserkVersion::of_paserk) to check whether the string
+/// is for a version this build does not support.
+///
+/// ```
+/// use rusty_paserk::{id_of_paserk, AnyKeyId, Key, Local, PlaintextKey, V4};
+///
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let paserk = PlaintextKey(local_key).to_string();
+///
+/// assert_eq!(id_of_paserk(&paserk).unwrap(), AnyKeyId::V4Local(local_key.to_id()));
+/// ```
+pub fn id_of_paserk(s: &str) -> Result<AnyKeyId, PasetoError> {
+    #[cfg(feature = "v2")]
+    {
+        if let Some(id) = id_of::<V2, Local>(s, v4_id)? {
+            return Ok(AnyKeyId::V2Local(id));
+        }
+        if let Some(id) = id_of::<V2, Public>(s, v4_id)? {
+            return Ok(AnyKeyId::V2Public(id));
+        }
+        if let Some(id) = id_of::<V2, Secret>(s, v4_id)? {
+            return Ok(AnyKeyId::V2Secret(id));
+        }
+    }
+    #[cfg(feature = "v3")]
+    {
+        if let Some(id) = id_of::<V3, Local>(s, v3_id)? {
+            return Ok(AnyKeyId::V3Local(id));
+        }
+        if let Some(id) = id_of::<V3, Public>(s, v3_id)? {
+            return Ok(AnyKeyId::V3Public(id));
+        }
+        if let Some(id) = id_of::<V3, Secret>(s, v3_id)? {
+            return Ok(AnyKeyId::V3Secret(id));
+        }
+    }
+    #[cfg(feature = "v4")]
+    {
+        if let Some(id) = id_of::<V4, Local>(s, v4_id)? {
+            return Ok(AnyKeyId::V4Local(id));
+        }
+        if let Some(id) = id_of::<V4, Public>(s, v4_id)? {
+            return Ok(AnyKeyId::V4Public(id));
+        }
+        if let Some(id) = id_of::<V4, Secret>(s, v4_id)? {
+            return Ok(AnyKeyId::V4Secret(id));
+        }
+    }
+    Err(PasetoError::WrongHeader)
+}
+
+/// Returns `None` if the headers do not match
+fn id_of<V: Version, K: KeyType<V>>(
+    s: &str,
+    hash: fn(&[&str]) -> GenericArray<u8, U33>,
+) -> Result<Option<KeyId<V, K>>, PasetoError> {
+    let Some(data) = s
+        .strip_prefix(V::KEY_HEADER)
+        .and_then(|s| s.strip_prefix(K::HEADER))
+    else {
+        return Ok(None);
+    };
+
+    // only checks that the data is the correct length and valid base64
+    let _: GenericArray<u8, K::KeyLen> = crate::read_b64(data)?;
+
+    Ok(Some(KeyId {
+        id: hash(&[V::KEY_HEADER, K::ID, s]),
+        key: PhantomData,
+    }))
+}
+
 impl<V, K> super::SafeForFooter for KeyId<V, K>
 where
     V: Version,
     K: KeyType<V>,
 {
 }
+impl<V: Version, K: KeyType<V>> crate::FromPaserkBytes for KeyId<V, K> {}
+
+impl<V: Version, K: KeyType<V>> crate::PaserkLen for KeyId<V, K> {
+    fn paserk_len(&self) -> usize {
+        V::KEY_HEADER.len() + K::ID.len() + crate::encoding::encoded_len(33)
+    }
+}
 
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 #[cfg(feature = "serde")]
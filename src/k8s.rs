@@ -0,0 +1,153 @@
+//! Storing keys in Kubernetes Secrets and ConfigMaps.
+//!
+//! These helpers build and read the `data` maps and annotations of the objects. They don't
+//! talk to the API server, so they work with any client. With kube-rs, the maps go in
+//! `Secret::data` (wrapping each value in a `ByteString`), `ConfigMap::data` and
+//! `ObjectMeta::annotations`.
+//!
+//! A [`KeyStoreFile`] goes in a Secret under [`KEYSTORE_DATA_KEY`]. Every local and secret
+//! key is still wrapped, so reading the Secret is not enough to get the keys. Public keys go in
+//! a ConfigMap under [`PUBLIC_KEYS_DATA_KEY`], one PASERK per line.
+//!
+//! The annotations record the key IDs in the object and the rotation state, so tooling can see
+//! which keys are in use without unlocking anything:
+//!
+//! | Annotation | Value |
+//! |---|---|
+//! | [`KEY_IDS_ANNOTATION`] | The IDs of the keys in the object, comma separated |
+//! | [`CURRENT_KEY_ANNOTATION`] | The ID of the key used for new tokens |
+//! | [`PREVIOUS_KEYS_ANNOTATION`] | The previous keys still accepted, as `<id>=<expiry>` pairs, comma separated. The expiry is in seconds since the Unix epoch, or empty if the key never expires |
+//!
+//! ```
+//! use std::time::Duration;
+//! use rusty_paserk::{k8s, Key, KeyStoreFile, Local, RotationManager, Secret, V4};
+//!
+//! let first = Key::<V4, Local>::new_os_random();
+//! let mut keys = RotationManager::new(first, Duration::from_secs(3600));
+//! let second = keys.rotate(Key::<V4, Local>::new_os_random());
+//!
+//! // sealed to a key that only the pods can unseal with
+//! let unsealing_key = Key::<V4, Secret>::new_os_random();
+//! let file = KeyStoreFile::seal(keys.key_ring(), &unsealing_key.public_key());
+//!
+//! let data = k8s::secret_data(&file);
+//! let mut annotations = k8s::keystore_annotations(&file).unwrap();
+//! annotations.extend(k8s::rotation_annotations(&keys));
+//! assert_eq!(annotations[k8s::CURRENT_KEY_ANNOTATION], second.to_string());
+//!
+//! // in the pod
+//! let file: KeyStoreFile<V4> = k8s::keystore_from_secret_data(&data).unwrap();
+//! let ring = file.unseal(&unsealing_key).unwrap();
+//! assert!(ring.get_local(&first.to_id()).is_some());
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{
+    internal::{KeyRingType, PieVersion, PieWrapType, PwVersion, PwWrapType, SealedVersion},
+    Clock, Key, KeyId, KeyStoreFile, Local, Public, PublicKeyRing, RotationManager, Secret,
+    Version,
+};
+
+/// The data key of the [`KeyStoreFile`] in a Secret
+pub const KEYSTORE_DATA_KEY: &str = "keystore";
+
+/// The data key of the public keys in a ConfigMap
+pub const PUBLIC_KEYS_DATA_KEY: &str = "public-keys";
+
+/// The annotation listing the IDs of the keys in the object
+pub const KEY_IDS_ANNOTATION: &str = "rusty-paserk/key-ids";
+
+/// The annotation with the ID of the current key
+pub const CURRENT_KEY_ANNOTATION: &str = "rusty-paserk/current-key-id";
+
+/// The annotation listing the previous keys that are still accepted, and when they expire
+pub const PREVIOUS_KEYS_ANNOTATION: &str = "rusty-paserk/previous-key-ids";
+
+/// The `data` of a Secret holding `file`
+pub fn secret_data<V: PieVersion>(file: &KeyStoreFile<V>) -> BTreeMap<String, Vec<u8>> {
+    BTreeMap::from([(KEYSTORE_DATA_KEY.to_owned(), file.to_string().into_bytes())])
+}
+
+/// Read the [`KeyStoreFile`] from the `data` of a Secret made with [`secret_data`].
+///
+/// Returns [`PasetoError::InvalidKey`] if the Secret has no key store.
+pub fn keystore_from_secret_data<V: PieVersion>(
+    data: &BTreeMap<String, Vec<u8>>,
+) -> Result<KeyStoreFile<V>, PasetoError> {
+    let file = data.get(KEYSTORE_DATA_KEY).ok_or(PasetoError::InvalidKey)?;
+    std::str::from_utf8(file)
+        .map_err(|_| PasetoError::InvalidKey)?
+        .parse()
+}
+
+/// The annotations of a Secret holding `file`, listing the IDs of its keys
+pub fn keystore_annotations<V>(
+    file: &KeyStoreFile<V>,
+) -> Result<BTreeMap<String, String>, PasetoError>
+where
+    V: PieVersion + PwVersion + SealedVersion,
+    Local: KeyRingType<V> + PieWrapType<V> + PwWrapType<V>,
+    Public: KeyRingType<V>,
+    Secret: KeyRingType<V> + PieWrapType<V>,
+{
+    let ids: Vec<String> = file.inventory()?.into_iter().map(|e| e.id).collect();
+    Ok(BTreeMap::from([(
+        KEY_IDS_ANNOTATION.to_owned(),
+        ids.join(","),
+    )]))
+}
+
+/// The `data` of a ConfigMap holding the public keys in `ring`
+pub fn config_map_data<V: Version>(ring: &PublicKeyRing<V>) -> BTreeMap<String, String> {
+    BTreeMap::from([(PUBLIC_KEYS_DATA_KEY.to_owned(), ring.to_string())])
+}
+
+/// Read the public keys from the `data` of a ConfigMap made with [`config_map_data`].
+///
+/// Returns [`PasetoError::InvalidKey`] if the ConfigMap has no public keys.
+pub fn public_keys_from_config_map<V: Version>(
+    data: &BTreeMap<String, String>,
+) -> Result<PublicKeyRing<V>, PasetoError>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    data.get(PUBLIC_KEYS_DATA_KEY)
+        .ok_or(PasetoError::InvalidKey)?
+        .parse()
+}
+
+/// The annotations of a ConfigMap holding `ring`, listing the IDs of its keys
+pub fn public_keys_annotations<V: Version>(ring: &PublicKeyRing<V>) -> BTreeMap<String, String> {
+    let ids: Vec<String> = ring.iter().map(|(id, _)| id.to_string()).collect();
+    BTreeMap::from([(KEY_IDS_ANNOTATION.to_owned(), ids.join(","))])
+}
+
+/// The annotations recording the current and previous keys of `manager`
+pub fn rotation_annotations<V: Version, K: KeyRingType<V>, C: Clock>(
+    manager: &RotationManager<V, K, C>,
+) -> BTreeMap<String, String> {
+    let previous: Vec<String> = manager
+        .previous()
+        .map(|(id, expires)| match expires {
+            Some(expires) => format!("{id}={}", unix_secs(expires)),
+            None => format!("{id}="),
+        })
+        .collect();
+    BTreeMap::from([
+        (
+            CURRENT_KEY_ANNOTATION.to_owned(),
+            manager.current_id().to_string(),
+        ),
+        (PREVIOUS_KEYS_ANNOTATION.to_owned(), previous.join(",")),
+    ])
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
@@ -2,6 +2,8 @@ use std::fmt;
 
 use generic_array::{ArrayLength, GenericArray};
 
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
@@ -43,6 +45,18 @@ impl Version for V4 {
     const KEY_HEADER: &'static str = "k4.";
 }
 
+/// V2 uses the same algorithms and key sizes as V4
+#[cfg(feature = "v2")]
+impl Version for V2 {
+    type Local = generic_array::typenum::U32;
+    /// Compressed edwards y point
+    type Public = generic_array::typenum::U32;
+    /// Ed25519 scalar key, concatenated with the public key bytes
+    type Secret = generic_array::typenum::U64;
+    const TOKEN_HEADER: &'static str = "v2.";
+    const KEY_HEADER: &'static str = "k2.";
+}
+
 /// Public verifying/encrypting keys
 #[derive(Debug)]
 pub struct Public;
@@ -113,9 +127,52 @@ impl<V: Version, K: KeyType<V>> fmt::Debug for Key<V, K> {
     }
 }
 
+impl<V: Version, K: KeyType<V>> Key<V, K> {
+    /// Check in constant time whether this key has the same bytes as a key of another version.
+    ///
+    /// This is for migration tooling that needs to confirm that, for example, a V3 and V4 local key
+    /// share their key material. Keys of different lengths never match.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    /// let copy = Key::<V4, Local>::from_bytes(key.as_ref().try_into().unwrap());
+    /// assert!(bool::from(key.same_material(&copy)));
+    /// assert!(!bool::from(key.same_material(&Key::<V4, Local>::new_os_random())));
+    ///
+    /// #[cfg(feature = "v2")]
+    /// {
+    ///     use rusty_paserk::V2;
+    ///
+    ///     let legacy = Key::<V2, Local>::from_bytes(key.as_ref().try_into().unwrap());
+    ///     assert!(bool::from(key.same_material(&legacy)));
+    ///     assert!(bool::from(legacy.same_material(&key)));
+    /// }
+    /// ```
+    pub fn same_material<W: Version>(&self, other: &Key<W, K>) -> subtle::Choice
+    where
+        K: KeyType<W>,
+    {
+        use subtle::ConstantTimeEq;
+        self.key.as_slice().ct_eq(other.key.as_slice())
+    }
+}
+
 mod convert;
 
 #[cfg(feature = "arbitrary")]
 mod arbitrary;
 
+#[cfg(feature = "openpgp")]
+mod openpgp;
+
+mod subkey;
+mod uri;
+
+#[cfg(feature = "v4")]
+mod tls;
+#[cfg(feature = "v4")]
+pub use tls::TLS_EXPORTER_LABEL;
+
 pub mod plaintext;
@@ -45,3 +45,30 @@ impl<'a> Arbitrary<'a> for super::Key<super::V4, super::Secret> {
         })
     }
 }
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "v3")]
+impl<'a> Arbitrary<'a> for super::Key<super::V3, super::Public> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(super::Key::<super::V3, super::Secret>::arbitrary(u)?.public_key())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "v4")]
+impl<'a> Arbitrary<'a> for super::Key<super::V4, super::Public> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(super::Key::<super::V4, super::Secret>::arbitrary(u)?.public_key())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+impl<'a, V: super::Version, K: super::KeyType<V>> Arbitrary<'a>
+    for super::plaintext::PlaintextKey<V, K>
+where
+    super::Key<V, K>: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
@@ -2,6 +2,8 @@ use generic_array::GenericArray;
 use rand::{rngs::OsRng, CryptoRng, RngCore};
 use rusty_paseto::core::PasetoError;
 
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
@@ -48,6 +50,21 @@ impl Key<V3, Secret> {
             key: *GenericArray::from_slice(pk),
         }
     }
+
+    /// Check that `public_key` is the public key for this secret key
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Secret, V3};
+    ///
+    /// let secret_key = Key::<V3, Secret>::new_os_random();
+    /// let other_key = Key::<V3, Secret>::new_os_random();
+    ///
+    /// assert!(secret_key.validate_keypair(&secret_key.public_key()).is_ok());
+    /// assert!(secret_key.validate_keypair(&other_key.public_key()).is_err());
+    /// ```
+    pub fn validate_keypair(&self, public_key: &Key<V3, Public>) -> Result<(), PasetoError> {
+        validate_keypair(&self.public_key(), public_key)
+    }
 }
 
 #[cfg(feature = "v3")]
@@ -95,12 +112,12 @@ impl Key<V4, Secret> {
     /// Decode an Ed25519 Secret Keypair
     ///
     /// ```
-    /// use rusty_paserk::Key;
+    /// use rusty_paserk::{Key, Secret, V4};
     ///
     /// let private_key = "407796f4bc4b8184e9fe0c54b336822d34823092ad873d87ba14c3efb9db8c1db7715bd661458d928654d3e832f53ff5c9480542e0e3d4c9b032c768c7ce6023";
     /// let private_key = hex::decode(&private_key).unwrap();
     ///
-    /// let _key = Key::from_keypair_bytes(&private_key).unwrap();
+    /// let _key = Key::<V4, Secret>::from_keypair_bytes(&private_key).unwrap();
     /// ```
     pub fn from_keypair_bytes(key: &[u8]) -> Result<Self, PasetoError> {
         use ed25519_dalek::SigningKey;
@@ -115,13 +132,13 @@ impl Key<V4, Secret> {
     /// Create a new secret key from the byte array
     ///
     /// ```
-    /// use rusty_paserk::Key;
+    /// use rusty_paserk::{Key, Secret, V4};
     ///
     /// let private_key = "407796f4bc4b8184e9fe0c54b336822d34823092ad873d87ba14c3efb9db8c1d";
     /// let private_key = hex::decode(&private_key).unwrap();
     /// let private_key: [u8; 32] = private_key.try_into().unwrap();
     ///
-    /// let _key = Key::from_secret_key(private_key);
+    /// let _key = Key::<V4, Secret>::from_secret_key(private_key);
     /// ```
     pub fn from_secret_key(key: [u8; 32]) -> Self {
         Self {
@@ -137,6 +154,57 @@ impl Key<V4, Secret> {
         let (_sk, pk): (GenericArray<u8, generic_array::typenum::U32>, _) = self.key.split();
         Key { key: pk }
     }
+
+    /// Check that `public_key` is the public key for this secret key.
+    ///
+    /// The public key stored in the second half of the secret key is also checked against the
+    /// one derived from the seed, so a secret key assembled from two different keypairs fails.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, PlaintextKey, Secret, V4};
+    ///
+    /// let secret_key = Key::<V4, Secret>::new_os_random();
+    /// let other_key = Key::<V4, Secret>::new_os_random();
+    ///
+    /// assert!(secret_key.validate_keypair(&secret_key.public_key()).is_ok());
+    /// assert!(secret_key.validate_keypair(&other_key.public_key()).is_err());
+    ///
+    /// // the seed of k4.secret-2 with the public key of k4.secret-3
+    /// let mismatched = "k4.secret.cHFyc3R1dnd4eXp7fH1-f4CBgoOEhYaHiImKi4yNjo9g_jdXGl1ufTCxUVTOSp-5LHDIcISPTM3xYmWICX9z9w";
+    /// let PlaintextKey(mismatched) = mismatched.parse::<PlaintextKey<V4, Secret>>().unwrap();
+    /// assert!(mismatched.validate_keypair(&mismatched.public_key()).is_err());
+    /// ```
+    pub fn validate_keypair(&self, public_key: &Key<V4, Public>) -> Result<(), PasetoError> {
+        let derived = Key {
+            key: ed25519_public_key(&self.key),
+        };
+        validate_keypair(&derived, &self.public_key())?;
+        validate_keypair(&derived, public_key)
+    }
+}
+
+/// The public key for the seed in the first half of an Ed25519 keypair
+#[cfg(feature = "v4")]
+fn ed25519_public_key(
+    keypair: &GenericArray<u8, generic_array::typenum::U64>,
+) -> GenericArray<u8, generic_array::typenum::U32> {
+    let seed = keypair[..32].try_into().expect("seed should be 32 bytes");
+    ed25519_dalek::SigningKey::from_bytes(seed)
+        .verifying_key()
+        .to_bytes()
+        .into()
+}
+
+fn validate_keypair<V: Version>(
+    expected: &Key<V, Public>,
+    public_key: &Key<V, Public>,
+) -> Result<(), PasetoError> {
+    use subtle::ConstantTimeEq;
+    if expected.key.ct_eq(&public_key.key).into() {
+        Ok(())
+    } else {
+        Err(PasetoError::InvalidKey)
+    }
 }
 
 #[cfg(feature = "v4")]
@@ -144,12 +212,12 @@ impl Key<V4, Public> {
     /// Decode a PEM encoded SEC1 Ed25519 Secret Key
     ///
     /// ```
-    /// use rusty_paserk::Key;
+    /// use rusty_paserk::{Key, Public, V4};
     ///
     /// let public_key = "b7715bd661458d928654d3e832f53ff5c9480542e0e3d4c9b032c768c7ce6023";
     /// let public_key = hex::decode(&public_key).unwrap();
     ///
-    /// let _key = Key::from_public_key(&public_key);
+    /// let _key = Key::<V4, Public>::from_public_key(&public_key);
     /// ```
     pub fn from_public_key(key: &[u8]) -> Result<Self, PasetoError> {
         let key = key.try_into().map_err(|_| PasetoError::InvalidKey)?;
@@ -202,6 +270,41 @@ impl<V: Version> Key<V, Local> {
         rng.fill_bytes(&mut key);
         Self { key }
     }
+
+    /// Generate a random local key using OS random, mixed with additional entropy
+    /// from another source, such as a hardware token or dice rolls.
+    ///
+    /// The key is at least as unpredictable as the OS random source,
+    /// even if the additional entropy is known to an attacker.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, V4};
+    ///
+    /// let dice_rolls = b"3 1 4 1 5 6 2 6 5 3 5 6 2 6 4 3 3 2 3 6 4 6 2 6 4 3 3 1 3 2 5 2";
+    /// let key = Key::<V4, Local>::new_with_additional_entropy(dice_rolls);
+    /// ```
+    pub fn new_with_additional_entropy(entropy: &[u8]) -> Self {
+        Self::new_random(&mut mixed_rng(entropy))
+    }
+}
+
+/// An rng seeded from both OS random and the caller's entropy
+fn mixed_rng(entropy: &[u8]) -> rand::rngs::StdRng {
+    use rand::SeedableRng;
+    use sha2::Digest;
+
+    let mut os = [0; 32];
+    OsRng.fill_bytes(&mut os);
+
+    let seed = sha2::Sha512::new()
+        .chain_update("paserk-additional-entropy.")
+        .chain_update(os)
+        .chain_update(entropy)
+        .finalize();
+
+    let mut rng_seed = [0; 32];
+    rng_seed.copy_from_slice(&seed[..32]);
+    rand::rngs::StdRng::from_seed(rng_seed)
 }
 
 #[cfg(feature = "v4")]
@@ -211,6 +314,14 @@ impl Key<V4, Secret> {
         Self::new_random(&mut OsRng)
     }
 
+    /// Generate a random V4 secret key using OS random, mixed with additional entropy.
+    ///
+    /// The key is at least as unpredictable as the OS random source,
+    /// even if the additional entropy is known to an attacker.
+    pub fn new_with_additional_entropy(entropy: &[u8]) -> Self {
+        Self::new_random(&mut mixed_rng(entropy))
+    }
+
     /// Generate a random V4 secret key using the provided random source
     pub fn new_random(rng: &mut (impl RngCore + CryptoRng)) -> Self {
         let mut key = [0; 32];
@@ -230,6 +341,14 @@ impl Key<V3, Secret> {
         Self::new_random(&mut OsRng)
     }
 
+    /// Generate a random V3 secret key using OS random, mixed with additional entropy.
+    ///
+    /// The key is at least as unpredictable as the OS random source,
+    /// even if the additional entropy is known to an attacker.
+    pub fn new_with_additional_entropy(entropy: &[u8]) -> Self {
+        Self::new_random(&mut mixed_rng(entropy))
+    }
+
     /// Generate a random V3 secret key using the provided random source
     pub fn new_random(rng: &mut (impl RngCore + CryptoRng)) -> Self {
         Self {
@@ -291,3 +410,148 @@ impl From<Key<V3, Secret>> for rusty_paseto::core::Key<48> {
         key.into()
     }
 }
+
+#[cfg(feature = "v4")]
+impl From<&Key<V4, Secret>> for Key<V4, Public> {
+    fn from(key: &Key<V4, Secret>) -> Self {
+        key.public_key()
+    }
+}
+
+/// ```
+/// use rusty_paserk::{Key, Public, Secret, V4};
+///
+/// fn verifier(key: impl Into<Key<V4, Public>>) -> Key<V4, Public> {
+///     key.into()
+/// }
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// assert_eq!(verifier(secret_key), verifier(secret_key.public_key()));
+/// ```
+#[cfg(feature = "v4")]
+impl From<Key<V4, Secret>> for Key<V4, Public> {
+    fn from(key: Key<V4, Secret>) -> Self {
+        key.public_key()
+    }
+}
+
+#[cfg(feature = "v3")]
+impl From<&Key<V3, Secret>> for Key<V3, Public> {
+    fn from(key: &Key<V3, Secret>) -> Self {
+        key.public_key()
+    }
+}
+
+#[cfg(feature = "v3")]
+impl From<Key<V3, Secret>> for Key<V3, Public> {
+    fn from(key: Key<V3, Secret>) -> Self {
+        key.public_key()
+    }
+}
+
+#[cfg(feature = "v2")]
+impl Key<V2, Secret> {
+    /// Decode an Ed25519 Secret Keypair
+    ///
+    /// V2 and V4 secret keys have the same encoding, but a key must only ever be used with one
+    /// version. Import the key material separately for each version instead of converting keys.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, PlaintextKey, Secret, V2};
+    ///
+    /// let private_key = "407796f4bc4b8184e9fe0c54b336822d34823092ad873d87ba14c3efb9db8c1db7715bd661458d928654d3e832f53ff5c9480542e0e3d4c9b032c768c7ce6023";
+    /// let private_key = hex::decode(&private_key).unwrap();
+    ///
+    /// let key = Key::<V2, Secret>::from_keypair_bytes(&private_key).unwrap();
+    /// assert!(PlaintextKey(key).to_string().starts_with("k2.secret."));
+    /// ```
+    pub fn from_keypair_bytes(key: &[u8]) -> Result<Self, PasetoError> {
+        let Key { key } = Key::<V4, Secret>::from_keypair_bytes(key)?;
+        Ok(Self { key })
+    }
+
+    /// Create a new secret key from the byte array
+    pub fn from_secret_key(key: [u8; 32]) -> Self {
+        let Key { key } = Key::<V4, Secret>::from_secret_key(key);
+        Self { key }
+    }
+
+    /// Generate a random V2 secret key using OS random
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, PlaintextKey, Secret, V2};
+    ///
+    /// let secret_key = Key::<V2, Secret>::new_os_random();
+    /// let public_key = secret_key.public_key();
+    /// assert!(PlaintextKey(public_key).to_string().starts_with("k2.public."));
+    /// assert!(public_key.to_id().to_string().starts_with("k2.pid."));
+    ///
+    /// let local_key = Key::<V2, Local>::new_os_random();
+    /// let sealed = local_key.seal(&public_key);
+    /// assert!(sealed.to_string().starts_with("k2.seal."));
+    /// assert_eq!(sealed.unseal(&secret_key).unwrap(), local_key);
+    /// ```
+    pub fn new_os_random() -> Self {
+        Self::new_random(&mut OsRng)
+    }
+
+    /// Generate a random V2 secret key using the provided random source
+    pub fn new_random(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let Key { key } = Key::<V4, Secret>::new_random(rng);
+        Self { key }
+    }
+
+    /// Get the corresponding V2 public key for this V2 secret key
+    pub fn public_key(&self) -> Key<V2, Public> {
+        use generic_array::sequence::Split;
+        let (_sk, pk): (GenericArray<u8, generic_array::typenum::U32>, _) = self.key.split();
+        Key { key: pk }
+    }
+
+    /// Check that `public_key` is the public key for this secret key.
+    ///
+    /// The public key stored in the second half of the secret key is also checked against the
+    /// one derived from the seed.
+    pub fn validate_keypair(&self, public_key: &Key<V2, Public>) -> Result<(), PasetoError> {
+        let derived = Key {
+            key: ed25519_public_key(&self.key),
+        };
+        validate_keypair(&derived, &self.public_key())?;
+        validate_keypair(&derived, public_key)
+    }
+}
+
+#[cfg(feature = "v2")]
+impl Key<V2, Public> {
+    /// Decode an Ed25519 public key
+    pub fn from_public_key(key: &[u8]) -> Result<Self, PasetoError> {
+        let Key { key } = Key::<V4, Public>::from_public_key(key)?;
+        Ok(Self { key })
+    }
+}
+
+#[cfg(feature = "v2")]
+impl Key<V2, Local> {
+    /// Create a V2 local key from raw bytes
+    pub fn from_bytes(key: [u8; 32]) -> Self {
+        Self { key: key.into() }
+    }
+    /// Get the raw bytes from this key
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.key.into()
+    }
+}
+
+#[cfg(feature = "v2")]
+impl From<&Key<V2, Secret>> for Key<V2, Public> {
+    fn from(key: &Key<V2, Secret>) -> Self {
+        key.public_key()
+    }
+}
+
+#[cfg(feature = "v2")]
+impl From<Key<V2, Secret>> for Key<V2, Public> {
+    fn from(key: Key<V2, Secret>) -> Self {
+        key.public_key()
+    }
+}
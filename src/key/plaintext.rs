@@ -15,6 +15,14 @@ impl<V: Version, K: KeyType<V>> fmt::Display for PlaintextKey<V, K> {
     }
 }
 
+impl<V: Version, K: KeyType<V>> crate::FromPaserkBytes for PlaintextKey<V, K> {}
+
+impl<V: Version, K: KeyType<V>> crate::PaserkLen for PlaintextKey<V, K> {
+    fn paserk_len(&self) -> usize {
+        V::KEY_HEADER.len() + K::HEADER.len() + crate::encoding::encoded_len(self.0.key.len())
+    }
+}
+
 impl<V: Version, K: KeyType<V>> FromStr for PlaintextKey<V, K> {
     type Err = PasetoError;
 
@@ -0,0 +1,38 @@
+use digest::Mac;
+use generic_array::{typenum::Unsigned, GenericArray};
+
+use crate::{internal::PieVersion, Key, Local};
+
+impl<V: PieVersion> Key<V, Local> {
+    /// Derive an independent local key for a single purpose, such as one service or one of
+    /// an encryption and authentication key pair.
+    ///
+    /// This uses the same MAC as PIE key wrapping for the version, HMAC-SHA384 for V3 and
+    /// keyed BLAKE2b for V4. The purpose is length prefixed, so different purposes can never
+    /// produce the same subkey, and subkeys never collide with the keys PASETO derives internally.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    ///
+    /// let enc = key.derive_subkey("encryption");
+    /// let auth = key.derive_subkey("authentication");
+    /// assert_ne!(enc, auth);
+    /// assert_eq!(enc, key.derive_subkey("encryption"));
+    /// ```
+    pub fn derive_subkey(&self, purpose: &str) -> Key<V, Local> {
+        let out = <V::TagMac as Mac>::new_from_slice(&self.key)
+            .expect("key should be valid mac key")
+            .chain_update(V::KEY_HEADER)
+            .chain_update(b"local-subkey.")
+            .chain_update((purpose.len() as u64).to_le_bytes())
+            .chain_update(purpose)
+            .finalize()
+            .into_bytes();
+
+        Key {
+            key: GenericArray::clone_from_slice(&out[..V::Local::USIZE]),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Channel-bound local keys derived from TLS exporter keying material.
+//!
+//! <https://www.rfc-editor.org/rfc/rfc5705>
+//! <https://www.rfc-editor.org/rfc/rfc8446#section-7.5>
+
+use digest::Mac;
+use generic_array::typenum::U32;
+use rusty_paseto::core::{PasetoError, V4};
+
+use crate::{Key, Local};
+
+/// The recommended exporter label when deriving keys with [`Key::from_tls_exporter`]
+pub const TLS_EXPORTER_LABEL: &str = "EXPORTER-paserk-k4.local";
+
+impl Key<V4, Local> {
+    /// Derive a local key from TLS exporter keying material.
+    ///
+    /// `exporter_secret` is the output of your TLS library's keying material exporter
+    /// (eg `export_keying_material`) and must be between 32 and 64 bytes long.
+    /// `label` must be the same label that was passed to the exporter, which should usually be
+    /// [`TLS_EXPORTER_LABEL`](crate::TLS_EXPORTER_LABEL).
+    ///
+    /// Both ends of the same TLS session will derive the same key, so tokens encrypted with it
+    /// cannot be replayed over any other connection.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, V4, TLS_EXPORTER_LABEL};
+    ///
+    /// // let ekm = conn.export_keying_material([0; 32], TLS_EXPORTER_LABEL.as_bytes(), None)?;
+    /// let ekm = [0x42; 32];
+    ///
+    /// let client_key = Key::<V4, Local>::from_tls_exporter(&ekm, TLS_EXPORTER_LABEL).unwrap();
+    /// let server_key = Key::<V4, Local>::from_tls_exporter(&ekm, TLS_EXPORTER_LABEL).unwrap();
+    /// assert_eq!(client_key, server_key);
+    /// ```
+    pub fn from_tls_exporter(exporter_secret: &[u8], label: &str) -> Result<Self, PasetoError> {
+        if exporter_secret.len() < 32 {
+            return Err(PasetoError::InvalidKey);
+        }
+        let key = blake2::Blake2bMac::<U32>::new_from_slice(exporter_secret)
+            .map_err(|_| PasetoError::InvalidKey)?
+            .chain_update("paserk-tls-exporter.")
+            .chain_update(label)
+            .finalize()
+            .into_bytes();
+        Ok(Key { key })
+    }
+}
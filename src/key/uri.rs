@@ -0,0 +1,50 @@
+use rusty_paseto::core::PasetoError;
+
+use crate::{Key, KeyId, PlaintextKey, Public, Version};
+
+const SCHEME: &str = "paserk:";
+
+impl<V: Version> Key<V, Public>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// A `paserk:` URI with this public key and its ID, for checking keys out of band,
+    /// such as with a QR code scanned between two operators' devices.
+    ///
+    /// Every character is URI safe, so the URI can be encoded without escaping.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Public, Secret, V4};
+    ///
+    /// let public_key = Key::<V4, Secret>::new_os_random().public_key();
+    ///
+    /// let uri = public_key.to_uri();
+    /// // => "paserk:k4.public.Xg3mDpB6n5hYDgXnHLG1P5ZGHWU0mGGhDlCA4QgMvxQ?pid=k4.pid.yMgldRRLHBLkhmcp8NG8yZrtyldbYoAjQWPv_Ma1rzRu"
+    ///
+    /// let public_key2 = Key::<V4, Public>::from_uri(&uri).unwrap();
+    /// assert_eq!(public_key, public_key2);
+    /// ```
+    pub fn to_uri(&self) -> String {
+        format!(
+            "{SCHEME}{}?pid={}",
+            PlaintextKey(self.clone()),
+            self.to_id()
+        )
+    }
+
+    /// Parse a URI made by [`to_uri`](Self::to_uri).
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the ID in the URI is not the ID of the key.
+    pub fn from_uri(uri: &str) -> Result<Self, PasetoError> {
+        let rest = uri.strip_prefix(SCHEME).ok_or(PasetoError::WrongHeader)?;
+        let (key, pid) = rest.split_once("?pid=").ok_or(PasetoError::IncorrectSize)?;
+
+        let PlaintextKey(key) = key.parse()?;
+        let pid: KeyId<V, Public> = pid.parse()?;
+
+        if !bool::from(pid.is_id_of(&key)) {
+            return Err(PasetoError::InvalidKey);
+        }
+        Ok(key)
+    }
+}
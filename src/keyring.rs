@@ -0,0 +1,540 @@
+//! A collection of keys indexed by their IDs.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use rusty_paseto::core::PasetoError;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use crate::{Key, KeyId, KeyType, Local, PlaintextKey, Public, Secret, Version};
+
+/// Local, public and secret keys of one version, indexed by their [`KeyId`].
+///
+/// This is the lookup table for the `kid` in a token footer. Lookups with [`get`](Self::get)
+/// take the same time whichever key matches.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyRing, Local, Secret, V4};
+///
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// let lid = ring.insert(local_key);
+/// let pid = ring.insert(secret_key.public_key());
+/// ring.insert(secret_key);
+///
+/// // later, with the kid from a token footer
+/// assert_eq!(ring.get_local(&lid), Some(&local_key));
+/// assert_eq!(ring.get_public(&pid), Some(&secret_key.public_key()));
+/// assert_eq!(ring.len(), 3);
+///
+/// let other = Key::<V4, Local>::new_os_random();
+/// assert_eq!(ring.get_local(&other.to_id()), None);
+/// ```
+///
+/// Keys are deliberately not serializable on their own. With the `serde` feature, use
+/// [`serde::plaintext::key_ring`](crate::serde::plaintext::key_ring) to store a key ring as a
+/// list of [`PlaintextKey`] strings.
+///
+/// Keys are always listed in the same order: local, then public, then secret keys, each
+/// ordered by the bytes of their IDs. Everything built from a key ring, such as its
+/// serialization, a [`KeyStoreFile`](crate::KeyStoreFile) or a
+/// [`SignedKeySet`](crate::SignedKeySet), follows that order, so the same keys give the same
+/// output whatever order they were inserted in, and published sets can be diffed in review.
+pub struct KeyRing<V: Version> {
+    local: BTreeMap<KeyId<V, Local>, Key<V, Local>>,
+    public: BTreeMap<KeyId<V, Public>, Key<V, Public>>,
+    secret: BTreeMap<KeyId<V, Secret>, Key<V, Secret>>,
+}
+
+/// Key types that can be stored in a [`KeyRing`]
+pub trait KeyRingType<V: Version>: KeyType<V> + Sized + 'static {
+    #[doc(hidden)]
+    fn id_of(key: &Key<V, Self>) -> KeyId<V, Self>;
+    #[doc(hidden)]
+    fn keys(ring: &KeyRing<V>) -> &BTreeMap<KeyId<V, Self>, Key<V, Self>>;
+    #[doc(hidden)]
+    fn keys_mut(ring: &mut KeyRing<V>) -> &mut BTreeMap<KeyId<V, Self>, Key<V, Self>>;
+}
+
+macro_rules! impl_key_ring_type {
+    ($k:ident, $field:ident) => {
+        impl<V: Version> KeyRingType<V> for $k
+        where
+            KeyId<V, $k>: From<Key<V, $k>>,
+        {
+            fn id_of(key: &Key<V, Self>) -> KeyId<V, Self> {
+                key.to_id()
+            }
+            fn keys(ring: &KeyRing<V>) -> &BTreeMap<KeyId<V, Self>, Key<V, Self>> {
+                &ring.$field
+            }
+            fn keys_mut(ring: &mut KeyRing<V>) -> &mut BTreeMap<KeyId<V, Self>, Key<V, Self>> {
+                &mut ring.$field
+            }
+        }
+    };
+}
+
+impl_key_ring_type!(Local, local);
+impl_key_ring_type!(Public, public);
+impl_key_ring_type!(Secret, secret);
+
+impl<V: Version> KeyRing<V> {
+    /// An empty key ring
+    pub fn new() -> Self {
+        Self {
+            local: BTreeMap::new(),
+            public: BTreeMap::new(),
+            secret: BTreeMap::new(),
+        }
+    }
+
+    /// Add a key, returning its ID
+    pub fn insert<K: KeyRingType<V>>(&mut self, key: Key<V, K>) -> KeyId<V, K> {
+        let id = K::id_of(&key);
+        K::keys_mut(self).insert(id, key);
+        id
+    }
+
+    /// Find the key with this ID.
+    ///
+    /// The ID is compared in constant time against every key of the same type, so the time
+    /// taken depends on the number of keys, but not on which one matched. This matters when
+    /// IDs are derived from local keys that must stay secret.
+    pub fn get<K: KeyRingType<V>>(&self, id: &KeyId<V, K>) -> Option<&Key<V, K>> {
+        find(K::keys(self), id)
+    }
+
+    /// Find the local key with this ID
+    pub fn get_local(&self, id: &KeyId<V, Local>) -> Option<&Key<V, Local>>
+    where
+        Local: KeyRingType<V>,
+    {
+        self.get(id)
+    }
+
+    /// Find the public key with this ID
+    pub fn get_public(&self, id: &KeyId<V, Public>) -> Option<&Key<V, Public>>
+    where
+        Public: KeyRingType<V>,
+    {
+        self.get(id)
+    }
+
+    /// Find the secret key with this ID
+    pub fn get_secret(&self, id: &KeyId<V, Secret>) -> Option<&Key<V, Secret>>
+    where
+        Secret: KeyRingType<V>,
+    {
+        self.get(id)
+    }
+
+    /// Remove the key with this ID
+    pub fn remove<K: KeyRingType<V>>(&mut self, id: &KeyId<V, K>) -> Option<Key<V, K>> {
+        K::keys_mut(self).remove(id)
+    }
+
+    /// All the keys of one type, ordered by the bytes of their IDs
+    pub fn iter<K: KeyRingType<V>>(&self) -> impl Iterator<Item = (&KeyId<V, K>, &Key<V, K>)> {
+        K::keys(self).iter()
+    }
+
+    /// The number of keys of all types
+    pub fn len(&self) -> usize {
+        self.local.len() + self.public.len() + self.secret.len()
+    }
+
+    /// Whether there are no keys
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The IDs of the keys that are only in `other` (added) or only in `self` (removed), for
+    /// reviewing a change to a key ring before it is distributed.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, KeyRing, Local, Secret, V4};
+    ///
+    /// let old_key = Key::<V4, Local>::new_os_random();
+    /// let new_key = Key::<V4, Local>::new_os_random();
+    ///
+    /// let mut before = KeyRing::<V4>::new();
+    /// before.insert(old_key);
+    /// let mut after = KeyRing::<V4>::new();
+    /// after.insert(new_key);
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.added_local, vec![new_key.to_id()]);
+    /// assert_eq!(diff.removed_local, vec![old_key.to_id()]);
+    /// assert_eq!(diff.to_string(), format!("+ {}\n- {}\n", new_key.to_id(), old_key.to_id()));
+    ///
+    /// assert_eq!(before.merge(&after), 1);
+    /// assert!(before.diff(&after).added_local.is_empty());
+    /// ```
+    pub fn diff(&self, other: &Self) -> KeyRingDiff<V> {
+        let (added_local, removed_local) = changes(&self.local, &other.local);
+        let (added_public, removed_public) = changes(&self.public, &other.public);
+        let (added_secret, removed_secret) = changes(&self.secret, &other.secret);
+        KeyRingDiff {
+            added_local,
+            removed_local,
+            added_public,
+            removed_public,
+            added_secret,
+            removed_secret,
+        }
+    }
+
+    /// Add every key in `other` that is not already in this ring, returning how many were added.
+    ///
+    /// IDs are derived from the keys, so two rings can't hold different keys under the same ID
+    /// and merging never has to resolve a conflict. Keys only in `self` are kept.
+    pub fn merge(&mut self, other: &Self) -> usize {
+        let before = self.len();
+        for (id, key) in &other.local {
+            self.local.entry(*id).or_insert_with(|| key.clone());
+        }
+        for (id, key) in &other.public {
+            self.public.entry(*id).or_insert_with(|| key.clone());
+        }
+        for (id, key) in &other.secret {
+            self.secret.entry(*id).or_insert_with(|| key.clone());
+        }
+        self.len() - before
+    }
+
+    /// Only the public keys, in a ring that can be serialized and sent to services that only
+    /// verify tokens or seal keys, without any risk of leaking local or secret keys.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, KeyRing, Local, Secret, V4};
+    ///
+    /// let secret_key = Key::<V4, Secret>::new_os_random();
+    ///
+    /// let mut ring = KeyRing::<V4>::new();
+    /// ring.insert(Key::<V4, Local>::new_os_random());
+    /// let pid = ring.insert(secret_key.public_key());
+    /// ring.insert(secret_key);
+    ///
+    /// let public = ring.public_view();
+    /// assert_eq!(public.len(), 1);
+    /// assert_eq!(public.get(&pid), Some(&secret_key.public_key()));
+    /// ```
+    pub fn public_view(&self) -> PublicKeyRing<V> {
+        PublicKeyRing {
+            public: self.public.clone(),
+        }
+    }
+
+    /// Add a key from its [`PlaintextKey`] string, of any type.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn insert_plaintext(&mut self, s: &str) -> Result<(), PasetoError>
+    where
+        Local: KeyRingType<V>,
+        Public: KeyRingType<V>,
+        Secret: KeyRingType<V>,
+    {
+        let rest = s
+            .strip_prefix(V::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        if rest.starts_with(<Local as KeyType<V>>::HEADER) {
+            self.insert(s.parse::<PlaintextKey<V, Local>>()?.0);
+        } else if rest.starts_with(<Public as KeyType<V>>::HEADER) {
+            self.insert(s.parse::<PlaintextKey<V, Public>>()?.0);
+        } else if rest.starts_with(<Secret as KeyType<V>>::HEADER) {
+            self.insert(s.parse::<PlaintextKey<V, Secret>>()?.0);
+        } else {
+            return Err(PasetoError::WrongHeader);
+        }
+        Ok(())
+    }
+}
+
+impl<V: Version> Default for KeyRing<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Version> Clone for KeyRing<V> {
+    fn clone(&self) -> Self {
+        Self {
+            local: self.local.clone(),
+            public: self.public.clone(),
+            secret: self.secret.clone(),
+        }
+    }
+}
+
+impl<V: Version> fmt::Debug for KeyRing<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.local.keys())
+            .entries(self.public.keys())
+            .entries(self.secret.keys())
+            .finish()
+    }
+}
+
+/// The public keys of a [`KeyRing`], returned by [`KeyRing::public_view`].
+///
+/// This can't hold local or secret keys, so unlike [`KeyRing`], it is serializable with the
+/// `serde` feature, as a list of [`PlaintextKey`] strings in the same order as
+/// [`iter`](Self::iter), so the output only depends on which keys are in the ring.
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use rusty_paserk::{Key, KeyRing, PublicKeyRing, Secret, V4};
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// ring.insert(secret_key.public_key());
+/// ring.insert(secret_key);
+///
+/// let public = ring.public_view();
+/// let json = serde_json::to_string(&public).unwrap();
+/// // => ["k4.public...."]
+/// assert!(!json.contains("k4.secret."));
+///
+/// let public2: PublicKeyRing<V4> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(public2.len(), 1);
+///
+/// // or as plain text, one key per line
+/// let public3: PublicKeyRing<V4> = public.to_string().parse().unwrap();
+/// assert_eq!(public3.len(), 1);
+/// # }
+/// ```
+pub struct PublicKeyRing<V: Version> {
+    public: BTreeMap<KeyId<V, Public>, Key<V, Public>>,
+}
+
+impl<V: Version> PublicKeyRing<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// An empty key ring
+    pub fn new() -> Self {
+        Self {
+            public: BTreeMap::new(),
+        }
+    }
+
+    /// Add a key, returning its ID
+    pub fn insert(&mut self, key: Key<V, Public>) -> KeyId<V, Public> {
+        let id = key.to_id();
+        self.public.insert(id, key);
+        id
+    }
+}
+
+impl<V: Version> PublicKeyRing<V> {
+    /// Find the key with this ID, in constant time like [`KeyRing::get`]
+    pub fn get(&self, id: &KeyId<V, Public>) -> Option<&Key<V, Public>> {
+        find(&self.public, id)
+    }
+
+    /// Remove the key with this ID
+    pub fn remove(&mut self, id: &KeyId<V, Public>) -> Option<Key<V, Public>> {
+        self.public.remove(id)
+    }
+
+    /// All the keys, ordered by the bytes of their IDs
+    pub fn iter(&self) -> impl Iterator<Item = (&KeyId<V, Public>, &Key<V, Public>)> {
+        self.public.iter()
+    }
+
+    /// The number of keys
+    pub fn len(&self) -> usize {
+        self.public.len()
+    }
+
+    /// Whether there are no keys
+    pub fn is_empty(&self) -> bool {
+        self.public.is_empty()
+    }
+}
+
+impl<V: Version> Default for PublicKeyRing<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Version> Clone for PublicKeyRing<V> {
+    fn clone(&self) -> Self {
+        Self {
+            public: self.public.clone(),
+        }
+    }
+}
+
+/// One `public` PASERK per line, in the same order as [`PublicKeyRing::iter`]
+impl<V: Version> fmt::Display for PublicKeyRing<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in self.public.values() {
+            writeln!(f, "{}", PlaintextKey(key.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+/// One `public` PASERK per line, ignoring blank lines
+impl<V: Version> FromStr for PublicKeyRing<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ring = Self::new();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            ring.insert(line.parse::<PlaintextKey<V, Public>>()?.0);
+        }
+        Ok(ring)
+    }
+}
+
+impl<V: Version> fmt::Debug for PublicKeyRing<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.public.keys()).finish()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: Version> serde::Serialize for PublicKeyRing<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for key in self.public.values() {
+            seq.serialize_element(&PlaintextKey(key.clone()))?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: Version> serde::Deserialize<'de> for PublicKeyRing<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let keys = Vec::<PlaintextKey<V, Public>>::deserialize(deserializer)?;
+        let mut ring = Self::new();
+        for key in keys {
+            ring.insert(key.0);
+        }
+        Ok(ring)
+    }
+}
+
+/// The differences between two [`KeyRing`]s, returned by [`KeyRing::diff`]
+///
+/// It displays as one `+ <id>` line per added key and one `- <id>` line per removed key.
+/// With the `serde` feature, it serializes to a struct of ID lists, one per field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRingDiff<V: Version> {
+    /// Local keys only in the other ring
+    pub added_local: Vec<KeyId<V, Local>>,
+    /// Local keys only in this ring
+    pub removed_local: Vec<KeyId<V, Local>>,
+    /// Public keys only in the other ring
+    pub added_public: Vec<KeyId<V, Public>>,
+    /// Public keys only in this ring
+    pub removed_public: Vec<KeyId<V, Public>>,
+    /// Secret keys only in the other ring
+    pub added_secret: Vec<KeyId<V, Secret>>,
+    /// Secret keys only in this ring
+    pub removed_secret: Vec<KeyId<V, Secret>>,
+}
+
+impl<V: Version> KeyRingDiff<V> {
+    /// Whether the two rings have the same keys
+    pub fn is_empty(&self) -> bool {
+        self.added_local.is_empty()
+            && self.removed_local.is_empty()
+            && self.added_public.is_empty()
+            && self.removed_public.is_empty()
+            && self.added_secret.is_empty()
+            && self.removed_secret.is_empty()
+    }
+}
+
+impl<V: Version> fmt::Display for KeyRingDiff<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn lines<I: fmt::Display>(
+            f: &mut fmt::Formatter<'_>,
+            sign: char,
+            ids: &[I],
+        ) -> fmt::Result {
+            ids.iter().try_for_each(|id| writeln!(f, "{sign} {id}"))
+        }
+        lines(f, '+', &self.added_local)?;
+        lines(f, '-', &self.removed_local)?;
+        lines(f, '+', &self.added_public)?;
+        lines(f, '-', &self.removed_public)?;
+        lines(f, '+', &self.added_secret)?;
+        lines(f, '-', &self.removed_secret)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: Version> serde::Serialize for KeyRingDiff<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("KeyRingDiff", 6)?;
+        s.serialize_field("added_local", &self.added_local)?;
+        s.serialize_field("removed_local", &self.removed_local)?;
+        s.serialize_field("added_public", &self.added_public)?;
+        s.serialize_field("removed_public", &self.removed_public)?;
+        s.serialize_field("added_secret", &self.added_secret)?;
+        s.serialize_field("removed_secret", &self.removed_secret)?;
+        s.end()
+    }
+}
+
+/// Compare `id` with every ID in `keys` in constant time.
+///
+/// Only whether a key was found, and its position, affect the time taken.
+fn find<'a, V: Version, K: KeyType<V>>(
+    keys: &'a BTreeMap<KeyId<V, K>, Key<V, K>>,
+    id: &KeyId<V, K>,
+) -> Option<&'a Key<V, K>> {
+    let mut found = Choice::from(0);
+    let mut index = 0u64;
+    for (i, kid) in (0u64..).zip(keys.keys()) {
+        let matches = kid.as_bytes().ct_eq(id.as_bytes());
+        index.conditional_assign(&i, matches);
+        found |= matches;
+    }
+    if found.into() {
+        keys.values().nth(index as usize)
+    } else {
+        None
+    }
+}
+
+/// The IDs only in `theirs`, and the IDs only in `ours`
+fn changes<I: Ord + Copy, T>(ours: &BTreeMap<I, T>, theirs: &BTreeMap<I, T>) -> (Vec<I>, Vec<I>) {
+    let added = theirs.keys().filter(|id| !ours.contains_key(id));
+    let removed = ours.keys().filter(|id| !theirs.contains_key(id));
+    (added.copied().collect(), removed.copied().collect())
+}
@@ -0,0 +1,258 @@
+//! Public keys published as a signed PASETO token.
+
+use std::{fmt, str::FromStr};
+
+use base64::Engine;
+use rusty_paseto::core::{
+    Footer, Paseto, PasetoAsymmetricPrivateKey, PasetoAsymmetricPublicKey, PasetoError, Payload,
+};
+
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use crate::{internal::KeyRingType, Key, KeyId, KeyRing, PlaintextKey, Public, Secret, Version};
+
+/// The public keys of a [`KeyRing`], signed by a distribution key, so that verifiers can fetch
+/// them over an untrusted channel.
+///
+/// This is not a standard PASERK type. It is encoded as a `public` PASETO token. The payload is
+/// a JSON array of `public` PASERK strings, without whitespace and ordered as in [`KeyRing`],
+/// and the footer is the ID of the distribution key. The keys are only returned once the
+/// signature has been checked against a distribution key that the verifier already trusts.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyRing, Secret, SignedKeySet, V4};
+///
+/// let distribution_key = Key::<V4, Secret>::new_os_random();
+/// let signing_key = Key::<V4, Secret>::new_os_random();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// let pid = ring.insert(signing_key.public_key());
+/// // only public keys are published
+/// ring.insert(signing_key);
+///
+/// let set = SignedKeySet::sign(&ring, &distribution_key).to_string();
+/// // => "v4.public.WyJrNC5wdWJsaWMu..."
+///
+/// // on the verifier, which only trusts the distribution key
+/// let set: SignedKeySet<V4> = set.parse().unwrap();
+/// assert_eq!(*set.signer(), distribution_key.public_key().to_id());
+///
+/// let keys = set.verify(&distribution_key.public_key()).unwrap();
+/// assert_eq!(keys.len(), 1);
+/// assert!(keys.get_public(&pid).is_some());
+///
+/// let attacker = Key::<V4, Secret>::new_os_random();
+/// assert!(set.verify(&attacker.public_key()).is_err());
+///
+/// // V4 signatures are deterministic, so the same keys always give the same set
+/// let mut reordered = KeyRing::<V4>::new();
+/// reordered.insert(signing_key);
+/// reordered.insert(signing_key.public_key());
+/// assert_eq!(SignedKeySet::sign(&reordered, &distribution_key).to_string(), set.to_string());
+/// ```
+pub struct SignedKeySet<V: SignedSetVersion> {
+    token: String,
+    signer: KeyId<V, Public>,
+}
+
+impl<V: SignedSetVersion> SignedKeySet<V>
+where
+    Public: KeyRingType<V>,
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Sign the public keys in `ring` with `distribution_key`. Local and secret keys are not
+    /// included.
+    pub fn sign(ring: &KeyRing<V>, distribution_key: &Key<V, Secret>) -> Self {
+        let mut payload = String::from("[");
+        for (i, (_, key)) in ring.iter::<Public>().enumerate() {
+            if i > 0 {
+                payload.push(',');
+            }
+            crate::push_json_str(&mut payload, &PlaintextKey(key.clone()).to_string());
+        }
+        payload.push(']');
+
+        let signer = V::public_key(distribution_key).to_id();
+        let token = V::sign(distribution_key, &payload, &signer.to_string());
+        Self { token, signer }
+    }
+
+    /// The ID of the distribution key that claims to have signed this set. This is not
+    /// verified, and is only for choosing which trusted key to [`verify`](Self::verify) with.
+    pub fn signer(&self) -> &KeyId<V, Public> {
+        &self.signer
+    }
+
+    /// Check the signature with `distribution_key`, and return the public keys in the set.
+    pub fn verify(&self, distribution_key: &Key<V, Public>) -> Result<KeyRing<V>, PasetoError> {
+        let footer = self.signer.to_string();
+        let payload = V::verify(distribution_key, &self.token, &footer)?;
+
+        let list = payload
+            .strip_prefix('[')
+            .and_then(|p| p.strip_suffix(']'))
+            .ok_or(PasetoError::InvalidKey)?;
+
+        let mut ring = KeyRing::new();
+        for item in list.split(',').filter(|item| !item.is_empty()) {
+            let key = item
+                .strip_prefix('"')
+                .and_then(|item| item.strip_suffix('"'))
+                .ok_or(PasetoError::InvalidKey)?;
+            ring.insert(key.parse::<PlaintextKey<V, Public>>()?.0);
+        }
+        Ok(ring)
+    }
+}
+
+impl<V: SignedSetVersion> crate::FromPaserkBytes for SignedKeySet<V> {}
+
+impl<V: SignedSetVersion> FromStr for SignedKeySet<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix(V::TOKEN_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let rest = rest
+            .strip_prefix("public.")
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let (_payload, footer) = rest.split_once('.').ok_or(PasetoError::IncorrectSize)?;
+        let footer = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(footer)
+            .map_err(|_| PasetoError::PayloadBase64Decode {
+                source: base64::DecodeError::InvalidLength(footer.len()),
+            })?;
+        let footer = std::str::from_utf8(&footer).map_err(|_| PasetoError::InvalidKey)?;
+
+        Ok(Self {
+            token: s.to_owned(),
+            signer: footer.parse()?,
+        })
+    }
+}
+
+impl<V: SignedSetVersion> fmt::Display for SignedKeySet<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+impl<V: SignedSetVersion> fmt::Debug for SignedKeySet<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+impl<V: SignedSetVersion> Clone for SignedKeySet<V> {
+    fn clone(&self) -> Self {
+        Self {
+            token: self.token.clone(),
+            signer: self.signer,
+        }
+    }
+}
+
+/// Version info for signing key sets
+pub trait SignedSetVersion: Version + Sized {
+    #[doc(hidden)]
+    fn public_key(key: &Key<Self, Secret>) -> Key<Self, Public>;
+
+    #[doc(hidden)]
+    fn sign(key: &Key<Self, Secret>, payload: &str, footer: &str) -> String;
+
+    #[doc(hidden)]
+    fn verify(key: &Key<Self, Public>, token: &str, footer: &str) -> Result<String, PasetoError>;
+}
+
+#[cfg(feature = "v3")]
+impl SignedSetVersion for V3 {
+    fn public_key(key: &Key<Self, Secret>) -> Key<Self, Public> {
+        key.into()
+    }
+
+    fn sign(key: &Key<Self, Secret>, payload: &str, footer: &str) -> String {
+        let key = rusty_paseto::core::Key::from(*key);
+        Paseto::<V3, rusty_paseto::core::Public>::builder()
+            .set_payload(Payload::from(payload))
+            .set_footer(Footer::from(footer))
+            .try_sign(&PasetoAsymmetricPrivateKey::from(&key))
+            .expect("secret key should be a valid signing key")
+    }
+
+    fn verify(key: &Key<Self, Public>, token: &str, footer: &str) -> Result<String, PasetoError> {
+        let key = rusty_paseto::core::Key::from(*key);
+        Paseto::<V3, rusty_paseto::core::Public>::try_verify(
+            token,
+            &PasetoAsymmetricPublicKey::try_from(&key)?,
+            Footer::from(footer),
+            None,
+        )
+    }
+}
+
+#[cfg(feature = "v4")]
+impl SignedSetVersion for V4 {
+    fn public_key(key: &Key<Self, Secret>) -> Key<Self, Public> {
+        key.into()
+    }
+
+    fn sign(key: &Key<Self, Secret>, payload: &str, footer: &str) -> String {
+        let key = rusty_paseto::core::Key::from(*key);
+        Paseto::<V4, rusty_paseto::core::Public>::builder()
+            .set_payload(Payload::from(payload))
+            .set_footer(Footer::from(footer))
+            .try_sign(&PasetoAsymmetricPrivateKey::from(&key))
+            .expect("secret key should be a valid signing key")
+    }
+
+    fn verify(key: &Key<Self, Public>, token: &str, footer: &str) -> Result<String, PasetoError> {
+        let key = rusty_paseto::core::Key::from(*key);
+        Paseto::<V4, rusty_paseto::core::Public>::try_verify(
+            token,
+            &PasetoAsymmetricPublicKey::from(&key),
+            Footer::from(footer),
+            None,
+        )
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: SignedSetVersion> serde::Serialize for SignedKeySet<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: SignedSetVersion> serde::Deserialize<'de> for SignedKeySet<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: SignedSetVersion> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = SignedKeySet<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a \"{}public.\" token", V::TOKEN_HEADER)
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
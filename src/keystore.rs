@@ -0,0 +1,1166 @@
+//! Persisting a set of keys to a single file, with every local and secret key wrapped.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use digest::{Mac, Output};
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::PasetoError;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    internal::{
+        KeyRingType, PieVersion, PieWrapType, PwType, PwVersion, PwWrapType, SealedVersion,
+        WrapType,
+    },
+    read_b64, write_b64, Algorithm, Clock, Key, KeyEntry, KeyId, KeyKind, KeyRing, KeyType, Local,
+    PaserkVersion, PieWrappedKey, PlaintextKey, Public, PwWrappedKey, SealedKey, Secret,
+    SystemClock,
+};
+
+const BEGIN: &str = "-----BEGIN PASERK KEYSTORE-----";
+const END: &str = "-----END PASERK KEYSTORE-----";
+const VERSION: &str = "Version: 1";
+
+/// A [`KeyRing`] stored as a single text file.
+///
+/// A fresh local key, the file key, [pie wraps](PieWrappedKey) every local and secret key in the
+/// ring. The file key itself is either [password wrapped](PwWrappedKey) or [sealed](SealedKey)
+/// to a public key, so no key is ever written in plaintext, apart from public keys.
+///
+/// The file starts with a format version, then the wrapped file key, then one
+/// `<key id>:<wrapped key>:<created>:<expires>` entry per line. Times are in seconds since the
+/// Unix epoch, and the expiry is empty for keys that never expire. The whole file is
+/// authenticated with a MAC keyed by the file key, so entries cannot be added, removed, reordered
+/// or changed without unlocking failing.
+///
+/// Keys are recorded as created when the ring is locked, with no expiry. Record their actual
+/// lifetime with [`set_metadata_with_password`](Self::set_metadata_with_password) or
+/// [`set_metadata_with_key`](Self::set_metadata_with_key).
+///
+/// ```
+/// use rusty_paserk::{Argon2State, Key, KeyRing, KeyStoreFile, Local, Secret, V4};
+///
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// let lid = ring.insert(local_key);
+/// ring.insert(secret_key);
+///
+/// # let settings = Argon2State::new(8 * 1024, 1, 1).unwrap();
+/// let file = KeyStoreFile::lock_with_password(&ring, b"hunter2", settings).to_string();
+/// // => "-----BEGIN PASERK KEYSTORE-----
+/// // Version: 1
+/// // k4.local-pw.XkGGaTsKM1kERN5yi2KvVQ...
+/// // k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB:k4.local-wrap.pie.RcAvOxHI0H...:1700000000:
+/// // k4.sid.6LGrWvRy82nbVTBu2bKcsuqWDJhSz1UrHEvR0sNRJCJv:k4.secret-wrap.pie.cTTnZwzB...:1700000000:
+/// // -----END PASERK KEYSTORE-----
+/// // 0a9CGr5IcuiVjOHjvoMXz4zNJ5mAx0ZZHJ_bnJCbVbE
+/// // "
+///
+/// let file: KeyStoreFile<V4> = file.parse().unwrap();
+/// let ring = file.unlock_with_password(b"hunter2").unwrap();
+/// assert_eq!(ring.get_local(&lid), Some(&local_key));
+///
+/// assert!(file.unlock_with_password(b"hunter3").is_err());
+///
+/// // dropping an entry is detected
+/// let tampered: String = file
+///     .to_string()
+///     .lines()
+///     .filter(|line| !line.starts_with("k4.sid."))
+///     .map(|line| format!("{line}\n"))
+///     .collect();
+/// let tampered: KeyStoreFile<V4> = tampered.parse().unwrap();
+/// assert!(tampered.unlock_with_password(b"hunter2").is_err());
+/// ```
+pub struct KeyStoreFile<V: PieVersion> {
+    lock: String,
+    entries: Vec<String>,
+    tag: Output<V::TagMac>,
+}
+
+impl<V> KeyStoreFile<V>
+where
+    V: PieVersion + PwVersion + SealedVersion,
+    Local: KeyRingType<V> + PieWrapType<V> + PwWrapType<V>,
+    Public: KeyRingType<V>,
+    Secret: KeyRingType<V> + PieWrapType<V>,
+{
+    /// Store `ring`, protected by `password`
+    pub fn lock_with_password(ring: &KeyRing<V>, password: &[u8], settings: V::KdfState) -> Self {
+        let file_key = Key::<V, Local>::new_os_random();
+        let lock = file_key
+            .pw_wrap_with_settings(password, settings)
+            .to_string();
+        Self::lock(ring, &file_key, lock, &mut OsRng, &BTreeMap::new())
+    }
+
+    /// Store `ring`, sealed to `sealing_key`
+    pub fn seal(ring: &KeyRing<V>, sealing_key: &Key<V, Public>) -> Self {
+        Self::seal_with_rng(ring, sealing_key, &mut OsRng)
+    }
+
+    /// Store `ring`, sealed to `sealing_key`, using the provided random source.
+    pub fn seal_with_rng(
+        ring: &KeyRing<V>,
+        sealing_key: &Key<V, Public>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let file_key = Key::<V, Local>::new_random(rng);
+        let lock = file_key.seal_with_rng(sealing_key, rng).to_string();
+        Self::lock(ring, &file_key, lock, rng, &BTreeMap::new())
+    }
+
+    /// Build the entries for `ring`, taking the creation and expiry times of each key from
+    /// `metadata`, or now and never for keys that are not in it
+    fn lock(
+        ring: &KeyRing<V>,
+        file_key: &Key<V, Local>,
+        lock: String,
+        rng: &mut (impl RngCore + CryptoRng),
+        metadata: &BTreeMap<String, (SystemTime, Option<SystemTime>)>,
+    ) -> Self {
+        let now = SystemTime::now();
+        let entry = |id: String, key: &dyn fmt::Display| {
+            let (created, expires) = metadata.get(&id).copied().unwrap_or((now, None));
+            format_entry(&id, key, created, expires)
+        };
+
+        let mut entries = Vec::with_capacity(ring.len());
+        for (id, key) in ring.iter::<Local>() {
+            entries.push(entry(id.to_string(), &key.wrap_pie_with_rng(file_key, rng)));
+        }
+        for (id, key) in ring.iter::<Public>() {
+            entries.push(entry(id.to_string(), &PlaintextKey(key.clone())));
+        }
+        for (id, key) in ring.iter::<Secret>() {
+            entries.push(entry(id.to_string(), &key.wrap_pie_with_rng(file_key, rng)));
+        }
+
+        let tag = tag(file_key, &lock, &entries);
+        Self { lock, entries, tag }
+    }
+
+    /// Recover the keys with the password used in
+    /// [`lock_with_password`](Self::lock_with_password).
+    pub fn unlock_with_password(&self, password: &[u8]) -> Result<KeyRing<V>, PasetoError> {
+        let file_key = self
+            .lock
+            .parse::<PwWrappedKey<V, Local>>()?
+            .unwrap_key(password)?;
+        self.unlock(&file_key)
+    }
+
+    /// Recover the keys with the secret key matching the public key used in
+    /// [`seal`](Self::seal).
+    pub fn unseal(&self, unsealing_key: &Key<V, Secret>) -> Result<KeyRing<V>, PasetoError> {
+        let file_key = self.lock.parse::<SealedKey<V>>()?.unseal(unsealing_key)?;
+        self.unlock(&file_key)
+    }
+
+    fn unlock(&self, file_key: &Key<V, Local>) -> Result<KeyRing<V>, PasetoError> {
+        let tag = tag(file_key, &self.lock, &self.entries);
+        if self.tag.ct_ne(&tag).into() {
+            return Err(PasetoError::InvalidSignature);
+        }
+
+        let mut ring = KeyRing::new();
+        for entry in &self.entries {
+            unlock_entry(&mut ring, entry, Some(file_key))?;
+        }
+        Ok(ring)
+    }
+
+    /// Check every entry without unlocking the file, reporting each one that is corrupt.
+    ///
+    /// Entries are parsed and the IDs of public keys are recomputed. Wrapped keys can only be
+    /// checked against their IDs once the file is unlocked, with
+    /// [`verify_integrity_with_password`](Self::verify_integrity_with_password) or
+    /// [`verify_integrity_with_key`](Self::verify_integrity_with_key).
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, KeyRing, KeyStoreFile, Local, PlaintextKey, Secret, V4};
+    /// use rusty_paseto::core::PasetoError;
+    ///
+    /// let secret_key = Key::<V4, Secret>::new_os_random();
+    /// let mut ring = KeyRing::<V4>::new();
+    /// ring.insert(Key::<V4, Local>::new_os_random());
+    /// ring.insert(secret_key.public_key());
+    ///
+    /// let file = KeyStoreFile::seal(&ring, &secret_key.public_key());
+    /// assert!(file.verify_integrity().is_empty());
+    /// assert!(file.verify_integrity_with_key(&secret_key).unwrap().is_empty());
+    ///
+    /// // swap the public key for another one, keeping its ID
+    /// let other = Key::<V4, Secret>::new_os_random().public_key();
+    /// let corrupt: KeyStoreFile<V4> = file
+    ///     .to_string()
+    ///     .replace(
+    ///         &PlaintextKey(secret_key.public_key()).to_string(),
+    ///         &PlaintextKey(other).to_string(),
+    ///     )
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let issues = corrupt.verify_integrity();
+    /// assert_eq!(issues.len(), 1);
+    /// assert_eq!(issues[0].entry, Some(1));
+    /// assert!(matches!(issues[0].error, PasetoError::InvalidKey));
+    ///
+    /// // the file MAC no longer matches either
+    /// let issues = corrupt.verify_integrity_with_key(&secret_key).unwrap();
+    /// assert_eq!(issues.len(), 2);
+    /// assert_eq!(issues[0].entry, None);
+    /// ```
+    pub fn verify_integrity(&self) -> Vec<IntegrityIssue> {
+        let mut issues = Vec::new();
+        let lock = if self.lock_algorithm() == <V as PwVersion>::ALGORITHM {
+            self.lock.parse::<PwWrappedKey<V, Local>>().err()
+        } else {
+            self.lock.parse::<SealedKey<V>>().err()
+        };
+        if let Some(error) = lock {
+            issues.push(IntegrityIssue { entry: None, error });
+        }
+        self.verify_entries(None, &mut issues);
+        issues
+    }
+
+    /// Check the file and every entry with the password used in
+    /// [`lock_with_password`](Self::lock_with_password), reporting each problem found.
+    ///
+    /// Unlike [`unlock_with_password`](Self::unlock_with_password), this does not stop at the
+    /// first corrupt entry. Returns an error only if the file key can't be recovered.
+    pub fn verify_integrity_with_password(
+        &self,
+        password: &[u8],
+    ) -> Result<Vec<IntegrityIssue>, PasetoError> {
+        let file_key = self
+            .lock
+            .parse::<PwWrappedKey<V, Local>>()?
+            .unwrap_key(password)?;
+        Ok(self.verify_unlocked(&file_key))
+    }
+
+    /// Check the file and every entry with the secret key matching the public key used in
+    /// [`seal`](Self::seal), reporting each problem found.
+    ///
+    /// Unlike [`unseal`](Self::unseal), this does not stop at the first corrupt entry. Returns an
+    /// error only if the file key can't be recovered.
+    pub fn verify_integrity_with_key(
+        &self,
+        unsealing_key: &Key<V, Secret>,
+    ) -> Result<Vec<IntegrityIssue>, PasetoError> {
+        let file_key = self.lock.parse::<SealedKey<V>>()?.unseal(unsealing_key)?;
+        Ok(self.verify_unlocked(&file_key))
+    }
+
+    fn verify_unlocked(&self, file_key: &Key<V, Local>) -> Vec<IntegrityIssue> {
+        let mut issues = Vec::new();
+        let tag = tag(file_key, &self.lock, &self.entries);
+        if self.tag.ct_ne(&tag).into() {
+            issues.push(IntegrityIssue {
+                entry: None,
+                error: PasetoError::InvalidSignature,
+            });
+        }
+        self.verify_entries(Some(file_key), &mut issues);
+        issues
+    }
+
+    fn verify_entries(&self, file_key: Option<&Key<V, Local>>, issues: &mut Vec<IntegrityIssue>) {
+        let mut ring = KeyRing::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            if let Err(error) = unlock_entry(&mut ring, entry, file_key) {
+                issues.push(IntegrityIssue {
+                    entry: Some(i),
+                    error,
+                });
+            }
+        }
+    }
+
+    /// List the keys in the file without unlocking it, for auditing what keys exist and how
+    /// old they are.
+    ///
+    /// Returns [`PasetoError::WrongHeader`] if an entry is not a key this file could contain.
+    /// The times are read from the file as is, and are only authenticated once it is unlocked.
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use rusty_paserk::{Algorithm, Key, KeyKind, KeyRing, KeyStoreFile, Local, Secret, V4};
+    ///
+    /// let secret_key = Key::<V4, Secret>::new_os_random();
+    /// let mut ring = KeyRing::<V4>::new();
+    /// let lid = ring.insert(Key::<V4, Local>::new_os_random());
+    /// ring.insert(secret_key.public_key());
+    ///
+    /// let file = KeyStoreFile::seal(&ring, &secret_key.public_key());
+    /// assert_eq!(file.lock_algorithm(), Algorithm::SealX25519);
+    ///
+    /// let inventory = file.inventory().unwrap();
+    /// assert_eq!(inventory.len(), 2);
+    /// assert_eq!(inventory[0].id, lid.to_string());
+    /// assert_eq!(inventory[0].kind, KeyKind::Local);
+    /// assert_eq!(inventory[0].wrap, Some(Algorithm::WrapPieXChaCha20));
+    /// assert_eq!(inventory[1].kind, KeyKind::Public);
+    /// assert_eq!(inventory[1].wrap, None);
+    ///
+    /// // locked keys are created now, and never expire
+    /// let age = SystemTime::now().duration_since(inventory[0].created).unwrap();
+    /// assert!(age < Duration::from_secs(60));
+    /// assert_eq!(inventory[0].expires, None);
+    /// ```
+    pub fn inventory(&self) -> Result<Vec<InventoryEntry>, PasetoError> {
+        let version = PaserkVersion::of_paserk(V::KEY_HEADER)?;
+        self.entries
+            .iter()
+            .map(|entry| {
+                let EntryParts {
+                    id,
+                    key,
+                    created,
+                    expires,
+                } = split_entry(entry)?;
+                let rest = key
+                    .strip_prefix(V::KEY_HEADER)
+                    .ok_or(PasetoError::WrongHeader)?;
+                let (kind, wrap) = if rest.starts_with(<Local as WrapType>::WRAP_HEADER) {
+                    (KeyKind::Local, Some(<V as PieVersion>::ALGORITHM))
+                } else if rest.starts_with(<Public as KeyType<V>>::HEADER) {
+                    (KeyKind::Public, None)
+                } else if rest.starts_with(<Secret as WrapType>::WRAP_HEADER) {
+                    (KeyKind::Secret, Some(<V as PieVersion>::ALGORITHM))
+                } else {
+                    return Err(PasetoError::WrongHeader);
+                };
+                Ok(InventoryEntry {
+                    id: id.to_owned(),
+                    version,
+                    kind,
+                    wrap,
+                    created,
+                    expires,
+                })
+            })
+            .collect()
+    }
+
+    /// How the file key is protected: with a password or sealed to a public key
+    pub fn lock_algorithm(&self) -> Algorithm {
+        let rest = &self.lock[V::KEY_HEADER.len()..];
+        if rest.starts_with(<Local as PwType>::WRAP_HEADER) {
+            <V as PwVersion>::ALGORITHM
+        } else {
+            <V as SealedVersion>::ALGORITHM
+        }
+    }
+
+    /// The entries that [`prune_with_password`](Self::prune_with_password) or
+    /// [`prune_with_key`](Self::prune_with_key) would remove with the same `policy`, without
+    /// changing the file.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rusty_paserk::{FixedClock, Key, KeyEntry, KeyRing, KeyStoreFile, Local, PrunePolicy, Secret, V4};
+    ///
+    /// let old_key = Key::<V4, Local>::new_os_random();
+    /// let new_key = Key::<V4, Local>::new_os_random();
+    /// let secret_key = Key::<V4, Secret>::new_os_random();
+    ///
+    /// let mut ring = KeyRing::<V4>::new();
+    /// let old = ring.insert(old_key);
+    /// let new = ring.insert(new_key);
+    /// let mut file = KeyStoreFile::seal(&ring, &secret_key.public_key());
+    ///
+    /// let day = Duration::from_secs(86400);
+    /// let entry = KeyEntry::new(old, (), "session").expires_after(day);
+    /// file.set_metadata_with_key(&secret_key, &entry).unwrap();
+    ///
+    /// // a week later, report what would be removed a day after expiring
+    /// let clock = FixedClock(entry.created + 7 * day);
+    /// let report = file.prune_dry_run(PrunePolicy::expired_with_clock(day, clock)).unwrap();
+    /// assert_eq!(report.len(), 1);
+    /// assert_eq!(report[0].id, old.to_string());
+    /// assert_eq!(file.inventory().unwrap().len(), 2);
+    ///
+    /// // then remove it
+    /// let removed = file
+    ///     .prune_with_key(&secret_key, PrunePolicy::expired_with_clock(day, clock))
+    ///     .unwrap();
+    /// assert_eq!(removed, report);
+    ///
+    /// let ring = file.unseal(&secret_key).unwrap();
+    /// assert_eq!(ring.len(), 1);
+    /// assert_eq!(ring.get_local(&new), Some(&new_key));
+    ///
+    /// // any other policy
+    /// let new = new.to_string();
+    /// let report = file.prune_dry_run(PrunePolicy::custom(|entry| entry.id == new)).unwrap();
+    /// assert_eq!(report.len(), 1);
+    /// ```
+    pub fn prune_dry_run<C: Clock>(
+        &self,
+        mut policy: PrunePolicy<'_, C>,
+    ) -> Result<Vec<InventoryEntry>, PasetoError> {
+        let mut inventory = self.inventory()?;
+        inventory.retain(|entry| policy.matches(entry));
+        Ok(inventory)
+    }
+
+    /// Remove the entries matching `policy`, with the password used in
+    /// [`lock_with_password`](Self::lock_with_password), returning the removed entries.
+    ///
+    /// The removed keys are not unwrapped. Save the file before pruning if they should be
+    /// archived. Returns an error, without changing the file, if the file key can't be
+    /// recovered or the file has been tampered with.
+    pub fn prune_with_password<C: Clock>(
+        &mut self,
+        password: &[u8],
+        policy: PrunePolicy<'_, C>,
+    ) -> Result<Vec<InventoryEntry>, PasetoError> {
+        let file_key = self
+            .lock
+            .parse::<PwWrappedKey<V, Local>>()?
+            .unwrap_key(password)?;
+        self.prune_unlocked(&file_key, policy)
+    }
+
+    /// Remove the entries matching `policy`, with the secret key matching the public key used
+    /// in [`seal`](Self::seal), returning the removed entries.
+    ///
+    /// As with [`prune_with_password`](Self::prune_with_password), the removed keys are not
+    /// unwrapped, and the file is unchanged on error.
+    pub fn prune_with_key<C: Clock>(
+        &mut self,
+        unsealing_key: &Key<V, Secret>,
+        policy: PrunePolicy<'_, C>,
+    ) -> Result<Vec<InventoryEntry>, PasetoError> {
+        let file_key = self.lock.parse::<SealedKey<V>>()?.unseal(unsealing_key)?;
+        self.prune_unlocked(&file_key, policy)
+    }
+
+    fn prune_unlocked<C: Clock>(
+        &mut self,
+        file_key: &Key<V, Local>,
+        mut policy: PrunePolicy<'_, C>,
+    ) -> Result<Vec<InventoryEntry>, PasetoError> {
+        let inventory = self.inventory()?;
+        self.modify(file_key, |entries| {
+            let mut removed = Vec::new();
+            let mut kept = Vec::with_capacity(entries.len());
+            for (entry, item) in entries.drain(..).zip(inventory) {
+                if policy.matches(&item) {
+                    removed.push(item);
+                } else {
+                    kept.push(entry);
+                }
+            }
+            *entries = kept;
+            Ok(removed)
+        })
+    }
+
+    /// Record the creation and expiry times of `entry` for the key with the same ID, with the
+    /// password used in [`lock_with_password`](Self::lock_with_password).
+    ///
+    /// Returns whether the key is in the file. The file is unchanged on error.
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use rusty_paserk::{Argon2State, FixedClock, Key, KeyEntry, KeyRing, KeyStoreFile, Local, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    /// let mut ring = KeyRing::<V4>::new();
+    /// let lid = ring.insert(key);
+    ///
+    /// # let settings = Argon2State::new(8 * 1024, 1, 1).unwrap();
+    /// let mut file = KeyStoreFile::lock_with_password(&ring, b"hunter2", settings);
+    ///
+    /// let created = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let entry = KeyEntry::new_with_clock(lid, (), "session", &FixedClock(created))
+    ///     .expires_after(Duration::from_secs(86400));
+    /// assert!(file.set_metadata_with_password(b"hunter2", &entry).unwrap());
+    ///
+    /// let inventory = file.inventory().unwrap();
+    /// assert_eq!(inventory[0].created, created);
+    /// assert_eq!(inventory[0].expires, entry.expires);
+    /// assert!(file.unlock_with_password(b"hunter2").is_ok());
+    /// ```
+    pub fn set_metadata_with_password<K: KeyType<V>, T>(
+        &mut self,
+        password: &[u8],
+        entry: &KeyEntry<V, K, T>,
+    ) -> Result<bool, PasetoError> {
+        let file_key = self
+            .lock
+            .parse::<PwWrappedKey<V, Local>>()?
+            .unwrap_key(password)?;
+        self.set_metadata_unlocked(&file_key, entry)
+    }
+
+    /// Record the creation and expiry times of `entry` for the key with the same ID, with the
+    /// secret key matching the public key used in [`seal`](Self::seal).
+    ///
+    /// See [`set_metadata_with_password`](Self::set_metadata_with_password).
+    pub fn set_metadata_with_key<K: KeyType<V>, T>(
+        &mut self,
+        unsealing_key: &Key<V, Secret>,
+        entry: &KeyEntry<V, K, T>,
+    ) -> Result<bool, PasetoError> {
+        let file_key = self.lock.parse::<SealedKey<V>>()?.unseal(unsealing_key)?;
+        self.set_metadata_unlocked(&file_key, entry)
+    }
+
+    fn set_metadata_unlocked<K: KeyType<V>, T>(
+        &mut self,
+        file_key: &Key<V, Local>,
+        entry: &KeyEntry<V, K, T>,
+    ) -> Result<bool, PasetoError> {
+        let id = entry.id.to_string();
+        self.modify(file_key, |entries| {
+            for line in entries.iter_mut() {
+                let parts = split_entry(line)?;
+                if parts.id == id {
+                    *line = format_entry(parts.id, &parts.key, entry.created, entry.expires);
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        })
+    }
+
+    /// Change the entries with `f`, then authenticate them again.
+    ///
+    /// The file is checked first, as a file modified without the file key must never be
+    /// re-tagged. The file is unchanged if `f` fails.
+    fn modify<T>(
+        &mut self,
+        file_key: &Key<V, Local>,
+        f: impl FnOnce(&mut Vec<String>) -> Result<T, PasetoError>,
+    ) -> Result<T, PasetoError> {
+        if self
+            .tag
+            .ct_ne(&tag(file_key, &self.lock, &self.entries))
+            .into()
+        {
+            return Err(PasetoError::InvalidSignature);
+        }
+
+        let mut entries = self.entries.clone();
+        let out = f(&mut entries)?;
+        self.tag = tag(file_key, &self.lock, &entries);
+        self.entries = entries;
+        Ok(out)
+    }
+
+    /// A copy of the key store under a fresh file key protected by `to`, unlocked with the
+    /// password used in [`lock_with_password`](Self::lock_with_password).
+    ///
+    /// Use this to move the keys to another environment without them ever leaving the library
+    /// in plaintext. Every local and secret key is wrapped again under the new file key, and
+    /// keeps its creation and expiry times.
+    ///
+    /// ```
+    /// use rusty_paserk::{Argon2State, Key, KeyRing, KeyStoreFile, KeyStoreLock, Local, Secret, V4};
+    ///
+    /// let local_key = Key::<V4, Local>::new_os_random();
+    /// let mut ring = KeyRing::<V4>::new();
+    /// let lid = ring.insert(local_key);
+    ///
+    /// # let settings = Argon2State::new(8 * 1024, 1, 1).unwrap();
+    /// let file = KeyStoreFile::lock_with_password(&ring, b"hunter2", settings);
+    ///
+    /// // the other environment only has its own secret key
+    /// let target = Key::<V4, Secret>::new_os_random();
+    /// let exported = file
+    ///     .reencrypt_with_password(b"hunter2", KeyStoreLock::Seal(&target.public_key()))
+    ///     .unwrap();
+    ///
+    /// let ring = exported.unseal(&target).unwrap();
+    /// assert_eq!(ring.get_local(&lid), Some(&local_key));
+    /// assert!(exported.unlock_with_password(b"hunter2").is_err());
+    ///
+    /// // and back again, under a new password
+    /// let file = exported
+    ///     .reencrypt_with_key(&target, KeyStoreLock::Password(b"correct horse", settings))
+    ///     .unwrap();
+    /// assert!(file.unlock_with_password(b"correct horse").is_ok());
+    /// ```
+    pub fn reencrypt_with_password(
+        &self,
+        password: &[u8],
+        to: KeyStoreLock<'_, V>,
+    ) -> Result<Self, PasetoError> {
+        let file_key = self
+            .lock
+            .parse::<PwWrappedKey<V, Local>>()?
+            .unwrap_key(password)?;
+        self.reencrypt(&file_key, to)
+    }
+
+    /// A copy of the key store under a fresh file key protected by `to`, unlocked with the
+    /// secret key matching the public key used in [`seal`](Self::seal).
+    ///
+    /// See [`reencrypt_with_password`](Self::reencrypt_with_password).
+    pub fn reencrypt_with_key(
+        &self,
+        unsealing_key: &Key<V, Secret>,
+        to: KeyStoreLock<'_, V>,
+    ) -> Result<Self, PasetoError> {
+        let file_key = self.lock.parse::<SealedKey<V>>()?.unseal(unsealing_key)?;
+        self.reencrypt(&file_key, to)
+    }
+
+    fn reencrypt(
+        &self,
+        file_key: &Key<V, Local>,
+        to: KeyStoreLock<'_, V>,
+    ) -> Result<Self, PasetoError> {
+        let ring = self.unlock(file_key)?;
+        let metadata = self
+            .inventory()?
+            .into_iter()
+            .map(|entry| (entry.id, (entry.created, entry.expires)))
+            .collect();
+
+        let new_key = Key::<V, Local>::new_os_random();
+        let lock = match to {
+            KeyStoreLock::Password(password, settings) => new_key
+                .pw_wrap_with_settings(password, settings)
+                .to_string(),
+            KeyStoreLock::Seal(sealing_key) => new_key.seal(sealing_key).to_string(),
+        };
+        Ok(Self::lock(&ring, &new_key, lock, &mut OsRng, &metadata))
+    }
+
+    /// Read a key store from `path`. Nothing is decrypted until it is unlocked.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Write the key store to `path`.
+    ///
+    /// The file is written next to `path` and then renamed over it, so a crash while saving
+    /// leaves either the old or the new key store, never a partial one.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(".{}.tmp", std::process::id()));
+        let tmp = path.with_file_name(name);
+
+        let written = (|| {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(self.to_string().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&tmp, path)
+        })();
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written
+    }
+
+    /// Rewrap every key in the key store at `path` with `rewrap`, and replace the file only if
+    /// all of them succeed.
+    ///
+    /// The new key store is staged in memory, then [saved](Self::save) by renaming it over the
+    /// old one. If `rewrap` fails, or the process crashes part way through, the file still
+    /// holds the old key store, never a mix of entries under the old and new file keys.
+    /// Returns the new key store.
+    ///
+    /// ```
+    /// use rusty_paserk::{Argon2State, Key, KeyRing, KeyStoreFile, KeyStoreLock, Local, V4};
+    ///
+    /// let mut ring = KeyRing::<V4>::new();
+    /// let lid = ring.insert(Key::<V4, Local>::new_os_random());
+    ///
+    /// # let settings = Argon2State::new(8 * 1024, 1, 1).unwrap();
+    /// let path = std::env::temp_dir().join(format!("rewrap-{}.txt", std::process::id()));
+    /// KeyStoreFile::lock_with_password(&ring, b"hunter2", settings)
+    ///     .save(&path)
+    ///     .unwrap();
+    ///
+    /// // a wrong password changes nothing
+    /// let result = KeyStoreFile::<V4>::rewrap_all_transactional(&path, |file| {
+    ///     file.reencrypt_with_password(b"hunter3", KeyStoreLock::Password(b"new", settings))
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(KeyStoreFile::<V4>::load(&path).unwrap().unlock_with_password(b"hunter2").is_ok());
+    ///
+    /// KeyStoreFile::<V4>::rewrap_all_transactional(&path, |file| {
+    ///     file.reencrypt_with_password(b"hunter2", KeyStoreLock::Password(b"new", settings))
+    /// })
+    /// .unwrap();
+    ///
+    /// let ring = KeyStoreFile::<V4>::load(&path).unwrap().unlock_with_password(b"new").unwrap();
+    /// assert!(ring.get_local(&lid).is_some());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn rewrap_all_transactional(
+        path: impl AsRef<Path>,
+        rewrap: impl FnOnce(&Self) -> Result<Self, PasetoError>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let staged = rewrap(&Self::load(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        staged.save(path)?;
+        Ok(staged)
+    }
+}
+
+/// How a [`KeyStoreFile`] is protected, for
+/// [`reencrypt_with_password`](KeyStoreFile::reencrypt_with_password) and
+/// [`reencrypt_with_key`](KeyStoreFile::reencrypt_with_key)
+pub enum KeyStoreLock<'a, V: PwVersion> {
+    /// Protected by a password, as in [`KeyStoreFile::lock_with_password`]
+    Password(&'a [u8], V::KdfState),
+    /// Sealed to a public key, as in [`KeyStoreFile::seal`]
+    Seal(&'a Key<V, Public>),
+}
+
+/// Which entries [`KeyStoreFile::prune_with_password`], [`KeyStoreFile::prune_with_key`] and
+/// [`KeyStoreFile::prune_dry_run`] remove
+pub enum PrunePolicy<'a, C: Clock = SystemClock> {
+    /// Entries that expired at least `horizon` ago, at the current time of `clock`. Entries that
+    /// never expire are kept.
+    Expired {
+        /// How long to keep entries after they expire, such as to verify tokens issued just
+        /// before
+        horizon: Duration,
+        /// The clock to check expiry against
+        clock: C,
+    },
+    /// Entries for which the function returns `true`, such as the IDs returned by
+    /// [`RotationManager::expired`](crate::RotationManager::expired)
+    Custom(Box<dyn FnMut(&InventoryEntry) -> bool + 'a>),
+}
+
+impl<'a> PrunePolicy<'a> {
+    /// Entries that expired at least `horizon` ago
+    pub fn expired(horizon: Duration) -> Self {
+        Self::expired_with_clock(horizon, SystemClock)
+    }
+
+    /// Entries for which `f` returns `true`
+    pub fn custom(f: impl FnMut(&InventoryEntry) -> bool + 'a) -> Self {
+        Self::Custom(Box::new(f))
+    }
+}
+
+impl<C: Clock> PrunePolicy<'_, C> {
+    /// Entries that expired at least `horizon` ago, at the current time of `clock`
+    pub fn expired_with_clock(horizon: Duration, clock: C) -> Self {
+        Self::Expired { horizon, clock }
+    }
+
+    fn matches(&mut self, entry: &InventoryEntry) -> bool {
+        match self {
+            Self::Expired { horizon, clock } => entry
+                .expires
+                .and_then(|expires| expires.checked_add(*horizon))
+                .is_some_and(|horizon| clock.now() >= horizon),
+            Self::Custom(f) => f(entry),
+        }
+    }
+}
+
+/// One key in a [`KeyStoreFile`], as listed by [`KeyStoreFile::inventory`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryEntry {
+    /// The ID of the key, as written in the file
+    pub id: String,
+    /// The version of the key
+    pub version: PaserkVersion,
+    /// The type of the key
+    pub kind: KeyKind,
+    /// How the key is wrapped, or `None` for public keys, which are stored in plaintext
+    pub wrap: Option<Algorithm>,
+    /// When the key was created, to the second
+    pub created: SystemTime,
+    /// When the key should stop being used, if ever
+    pub expires: Option<SystemTime>,
+}
+
+/// A problem found by [`KeyStoreFile::verify_integrity`]
+#[derive(Debug)]
+pub struct IntegrityIssue {
+    /// The index of the corrupt entry, or `None` if the problem is with the file as a whole,
+    /// such as a wrong MAC or an unreadable file key
+    pub entry: Option<usize>,
+    /// What is wrong. [`PasetoError::InvalidKey`] means the key does not match its ID.
+    pub error: PasetoError,
+}
+
+/// Parse one `<key id>:<wrapped key>:<created>:<expires>` entry and add it to the ring.
+///
+/// Without the file key, wrapped keys are only parsed, and not checked against their IDs.
+fn unlock_entry<V>(
+    ring: &mut KeyRing<V>,
+    entry: &str,
+    file_key: Option<&Key<V, Local>>,
+) -> Result<(), PasetoError>
+where
+    V: PieVersion,
+    Local: KeyRingType<V> + PieWrapType<V>,
+    Public: KeyRingType<V>,
+    Secret: KeyRingType<V> + PieWrapType<V>,
+{
+    let EntryParts { id, key, .. } = split_entry(entry)?;
+    let rest = key
+        .strip_prefix(V::KEY_HEADER)
+        .ok_or(PasetoError::WrongHeader)?;
+
+    if rest.starts_with(<Local as WrapType>::WRAP_HEADER) {
+        let key = key.parse::<PieWrappedKey<V, Local>>()?;
+        match file_key {
+            Some(file_key) => insert(ring, id, key.unwrap_key(file_key)?),
+            None => id.parse::<KeyId<V, Local>>().map(drop),
+        }
+    } else if rest.starts_with(<Public as KeyType<V>>::HEADER) {
+        insert(ring, id, key.parse::<PlaintextKey<V, Public>>()?.0)
+    } else if rest.starts_with(<Secret as WrapType>::WRAP_HEADER) {
+        let key = key.parse::<PieWrappedKey<V, Secret>>()?;
+        match file_key {
+            Some(file_key) => insert(ring, id, key.unwrap_key(file_key)?),
+            None => id.parse::<KeyId<V, Secret>>().map(drop),
+        }
+    } else {
+        Err(PasetoError::WrongHeader)
+    }
+}
+
+/// Add `key` to the ring, checking that it matches the ID in the file
+fn insert<V: crate::Version, K: KeyRingType<V>>(
+    ring: &mut KeyRing<V>,
+    id: &str,
+    key: Key<V, K>,
+) -> Result<(), PasetoError> {
+    let id: KeyId<V, K> = id.parse()?;
+    if ring.insert(key) != id {
+        return Err(PasetoError::InvalidKey);
+    }
+    Ok(())
+}
+
+/// One entry of a [`KeyStoreFile`], split into its fields
+struct EntryParts<'a> {
+    id: &'a str,
+    key: &'a str,
+    created: SystemTime,
+    expires: Option<SystemTime>,
+}
+
+fn split_entry(entry: &str) -> Result<EntryParts<'_>, PasetoError> {
+    let mut fields = entry.split(':');
+    let (Some(id), Some(key), Some(created), Some(expires), None) = (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    ) else {
+        return Err(PasetoError::IncorrectSize);
+    };
+    Ok(EntryParts {
+        id,
+        key,
+        created: read_time(created)?,
+        expires: match expires {
+            "" => None,
+            expires => Some(read_time(expires)?),
+        },
+    })
+}
+
+fn format_entry(
+    id: &str,
+    key: &dyn fmt::Display,
+    created: SystemTime,
+    expires: Option<SystemTime>,
+) -> String {
+    let expires = expires.map_or(String::new(), |t| unix_secs(t).to_string());
+    format!("{id}:{key}:{}:{expires}", unix_secs(created))
+}
+
+fn read_time(secs: &str) -> Result<SystemTime, PasetoError> {
+    secs.parse()
+        .ok()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .ok_or(PasetoError::IncorrectSize)
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn tag<V: PieVersion>(
+    file_key: &Key<V, Local>,
+    lock: &str,
+    entries: &[String],
+) -> Output<V::TagMac> {
+    let mut mac = <V::TagMac as Mac>::new_from_slice(&file_key.key)
+        .expect("key should be valid mac key")
+        .chain_update(V::KEY_HEADER)
+        .chain_update(b"keystore.")
+        .chain_update(VERSION)
+        .chain_update(b"\n")
+        .chain_update(lock)
+        .chain_update(b"\n");
+    for entry in entries {
+        mac.update(entry.as_bytes());
+        mac.update(b"\n");
+    }
+    mac.finalize().into_bytes()
+}
+
+impl<V: PieVersion> FromStr for KeyStoreFile<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim);
+        if lines.next() != Some(BEGIN) {
+            return Err(PasetoError::WrongHeader);
+        }
+        if lines.next() != Some(VERSION) {
+            return Err(PasetoError::WrongHeader);
+        }
+        let lock = lines.next().ok_or(PasetoError::IncorrectSize)?;
+        if !lock.starts_with(V::KEY_HEADER) {
+            return Err(PasetoError::WrongHeader);
+        }
+
+        let mut entries = Vec::new();
+        loop {
+            match lines.next() {
+                Some(END) => break,
+                Some(entry) => entries.push(entry.to_owned()),
+                None => return Err(PasetoError::IncorrectSize),
+            }
+        }
+
+        let tag = lines.next().ok_or(PasetoError::IncorrectSize)?;
+        let tag = read_b64(tag)?;
+        if lines.any(|line| !line.is_empty()) {
+            return Err(PasetoError::IncorrectSize);
+        }
+
+        Ok(Self {
+            lock: lock.to_owned(),
+            entries,
+            tag,
+        })
+    }
+}
+
+impl<V: PieVersion> fmt::Display for KeyStoreFile<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{BEGIN}")?;
+        writeln!(f, "{VERSION}")?;
+        writeln!(f, "{}", self.lock)?;
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        writeln!(f, "{END}")?;
+        write_b64(&self.tag, f)?;
+        writeln!(f)
+    }
+}
+
+/// A [`KeyRing`] loaded from a [`KeyStoreFile`], that can be reloaded when the file changes, so
+/// that keys rotated by configuration management take effect without a restart.
+///
+/// Readers get a snapshot of the keys from [`keys`](Self::keys). A reload unlocks the whole new
+/// file before swapping it in, so readers see either the old keys or the new keys, never a mix.
+/// If the new file can't be read or unlocked, the old keys are kept.
+///
+/// Call [`reload_if_changed`](Self::reload_if_changed) on a timer, or from a file watcher. With
+/// the `notify` feature, [`watch`](Self::watch) does this from a background thread.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyRing, KeyStoreFile, Local, ReloadingKeyRing, Secret, V4};
+///
+/// let path = std::env::temp_dir().join(format!("keystore-{}.txt", std::process::id()));
+/// let unsealing_key = Key::<V4, Secret>::new_os_random();
+/// let public_key = unsealing_key.public_key();
+///
+/// let mut ring = KeyRing::<V4>::new();
+/// let old = ring.insert(Key::<V4, Local>::new_os_random());
+/// KeyStoreFile::seal(&ring, &public_key).save(&path).unwrap();
+///
+/// let keys = ReloadingKeyRing::new(&path, move |file| file.unseal(&unsealing_key)).unwrap();
+/// let snapshot = keys.keys();
+/// assert!(!keys.reload_if_changed().unwrap());
+///
+/// // rotate the key
+/// ring.remove(&old);
+/// let new = ring.insert(Key::<V4, Local>::new_os_random());
+/// KeyStoreFile::seal(&ring, &public_key).save(&path).unwrap();
+///
+/// assert!(keys.reload_if_changed().unwrap());
+/// assert!(keys.keys().get_local(&new).is_some());
+/// assert!(keys.keys().get_local(&old).is_none());
+/// // readers that took a snapshot before the reload are unaffected
+/// assert!(snapshot.get_local(&old).is_some());
+///
+/// // a corrupt file is rejected, and the current keys are kept
+/// std::fs::write(&path, "not a key store").unwrap();
+/// assert!(keys.reload_if_changed().is_err());
+/// assert!(keys.keys().get_local(&new).is_some());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct ReloadingKeyRing<V: PieVersion, F> {
+    path: PathBuf,
+    unlock: F,
+    current: RwLock<(String, Arc<KeyRing<V>>)>,
+    reload: Mutex<()>,
+}
+
+impl<V, F> ReloadingKeyRing<V, F>
+where
+    V: PieVersion + PwVersion + SealedVersion,
+    Local: KeyRingType<V> + PieWrapType<V> + PwWrapType<V>,
+    Public: KeyRingType<V>,
+    Secret: KeyRingType<V> + PieWrapType<V>,
+    F: Fn(&KeyStoreFile<V>) -> Result<KeyRing<V>, PasetoError>,
+{
+    /// Load the key store at `path`, using `unlock` to recover its keys, such as
+    /// `|file| file.unseal(&unsealing_key)`.
+    pub fn new(path: impl Into<PathBuf>, unlock: F) -> io::Result<Self> {
+        let path = path.into();
+        let text = fs::read_to_string(&path)?;
+        let ring = unlock_text(&text, &unlock)?;
+        Ok(Self {
+            path,
+            unlock,
+            current: RwLock::new((text, Arc::new(ring))),
+            reload: Mutex::new(()),
+        })
+    }
+
+    /// The keys from the last successful load
+    pub fn keys(&self) -> Arc<KeyRing<V>> {
+        self.lock_read().1.clone()
+    }
+
+    /// Reload the key store if the file has changed since the last successful load, returning
+    /// whether it was reloaded.
+    ///
+    /// If the file can't be read or unlocked, the current keys are kept and the error is
+    /// returned. Concurrent reloads run one at a time, so keys read from an older version of the
+    /// file never replace keys from a newer one.
+    pub fn reload_if_changed(&self) -> io::Result<bool> {
+        let _reload = self.reload.lock().unwrap_or_else(PoisonError::into_inner);
+        let text = fs::read_to_string(&self.path)?;
+        if text == self.lock_read().0 {
+            return Ok(false);
+        }
+        let ring = unlock_text(&text, &self.unlock)?;
+
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        if text == current.0 {
+            return Ok(false);
+        }
+        *current = (text, Arc::new(ring));
+        Ok(true)
+    }
+
+    fn lock_read(&self) -> std::sync::RwLockReadGuard<'_, (String, Arc<KeyRing<V>>)> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "notify")))]
+#[cfg(feature = "notify")]
+impl<V, F> ReloadingKeyRing<V, F>
+where
+    V: PieVersion + PwVersion + SealedVersion,
+    Local: KeyRingType<V> + PieWrapType<V> + PwWrapType<V>,
+    Public: KeyRingType<V>,
+    Secret: KeyRingType<V> + PieWrapType<V>,
+    F: Fn(&KeyStoreFile<V>) -> Result<KeyRing<V>, PasetoError>,
+    Self: Send + Sync + 'static,
+{
+    /// Reload the key store from a background thread whenever the file changes, checking it
+    /// every `interval`.
+    ///
+    /// Errors from [`reload_if_changed`](Self::reload_if_changed) are passed to `on_error`, and
+    /// the current keys are kept. The thread stops once the returned [`ReloadWatcher`] is
+    /// dropped, or once nothing else holds the key ring.
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::{Duration, Instant}};
+    /// use rusty_paserk::{Key, KeyRing, KeyStoreFile, Local, ReloadingKeyRing, Secret, V4};
+    ///
+    /// let path = std::env::temp_dir().join(format!("watched-{}.txt", std::process::id()));
+    /// let unsealing_key = Key::<V4, Secret>::new_os_random();
+    /// let public_key = unsealing_key.public_key();
+    ///
+    /// let mut ring = KeyRing::<V4>::new();
+    /// ring.insert(Key::<V4, Local>::new_os_random());
+    /// KeyStoreFile::seal(&ring, &public_key).save(&path).unwrap();
+    ///
+    /// let keys = ReloadingKeyRing::new(&path, move |file| file.unseal(&unsealing_key)).unwrap();
+    /// let keys = Arc::new(keys);
+    /// let _watcher = keys.watch(Duration::from_millis(10), |err| eprintln!("reload failed: {err}"));
+    ///
+    /// // configuration management pushes a new key
+    /// let new = ring.insert(Key::<V4, Local>::new_os_random());
+    /// KeyStoreFile::seal(&ring, &public_key).save(&path).unwrap();
+    ///
+    /// let start = Instant::now();
+    /// while keys.keys().get_local(&new).is_none() {
+    ///     assert!(start.elapsed() < Duration::from_secs(10));
+    ///     std::thread::sleep(Duration::from_millis(10));
+    /// }
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn watch(
+        self: &Arc<Self>,
+        interval: Duration,
+        on_error: impl Fn(io::Error) + Send + 'static,
+    ) -> ReloadWatcher {
+        let keys = Arc::downgrade(self);
+        let (stop, stopped) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+                stopped.recv_timeout(interval)
+            {
+                let Some(keys) = keys.upgrade() else { break };
+                if let Err(err) = keys.reload_if_changed() {
+                    on_error(err);
+                }
+            }
+        });
+        ReloadWatcher { _stop: stop }
+    }
+}
+
+/// The background thread started by [`ReloadingKeyRing::watch`], which stops when this is
+/// dropped
+#[cfg_attr(docsrs, doc(cfg(feature = "notify")))]
+#[cfg(feature = "notify")]
+#[derive(Debug)]
+pub struct ReloadWatcher {
+    _stop: std::sync::mpsc::Sender<()>,
+}
+
+fn unlock_text<V: PieVersion>(
+    text: &str,
+    unlock: impl Fn(&KeyStoreFile<V>) -> Result<KeyRing<V>, PasetoError>,
+) -> io::Result<KeyRing<V>> {
+    text.parse()
+        .and_then(|file| unlock(&file))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
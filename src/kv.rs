@@ -0,0 +1,174 @@
+//! Distributing public keys through a key-value store, such as etcd or Consul.
+
+use std::{
+    future::Future,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{Key, KeyId, Public, PublicKeyRing, Version};
+
+/// A key-value store that versions each value, such as etcd or Consul KV.
+///
+/// Implementations make the requests to the store. The revision is the store's own version
+/// number for the key, such as the `mod_revision` in etcd or the `ModifyIndex` in Consul.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub trait KvStore {
+    /// The current value at `key`, or `None` if it is not set
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<KvValue>, PasetoError>> + Send;
+
+    /// Set the value at `key`, returning its new revision
+    fn put(&self, key: &str, value: &str) -> impl Future<Output = Result<u64, PasetoError>> + Send;
+
+    /// Wait until the value at `key` has a revision after `revision`, then return it, or `None`
+    /// if it was deleted.
+    ///
+    /// This is a watch in etcd, or a blocking query in Consul.
+    fn watch(
+        &self,
+        key: &str,
+        revision: u64,
+    ) -> impl Future<Output = Result<Option<KvValue>, PasetoError>> + Send;
+}
+
+/// A value read from a [`KvStore`]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvValue {
+    /// The stored text
+    pub value: String,
+    /// The revision of the value
+    pub revision: u64,
+}
+
+/// A [`PublicKeyRing`] kept in a [`KvStore`], and refreshed when it changes.
+///
+/// Only public keys can be stored, so the store never holds anything secret. The value is one
+/// `public` PASERK per line, ordered by ID.
+///
+/// ```
+/// use std::{future::Future, pin::pin, sync::{Arc, Mutex}, task::{Context, Poll, Waker}};
+/// use rusty_paserk::{Key, KvKeyRing, KvStore, KvValue, PasetoError, PublicKeyRing, Secret, V4};
+///
+/// /// An in-memory store, for illustration only
+/// #[derive(Default, Clone)]
+/// struct MemoryStore(Arc<Mutex<Option<KvValue>>>);
+///
+/// impl KvStore for MemoryStore {
+///     async fn get(&self, _key: &str) -> Result<Option<KvValue>, PasetoError> {
+///         Ok(self.0.lock().unwrap().clone())
+///     }
+///
+///     async fn put(&self, _key: &str, value: &str) -> Result<u64, PasetoError> {
+///         let mut current = self.0.lock().unwrap();
+///         let revision = current.as_ref().map_or(1, |v| v.revision + 1);
+///         *current = Some(KvValue { value: value.to_owned(), revision });
+///         Ok(revision)
+///     }
+///
+///     async fn watch(&self, key: &str, _revision: u64) -> Result<Option<KvValue>, PasetoError> {
+///         // a real store would wait for a newer revision
+///         self.get(key).await
+///     }
+/// }
+///
+/// # fn block_on<F: Future>(f: F) -> F::Output {
+/// #     let mut f = pin!(f);
+/// #     loop {
+/// #         if let Poll::Ready(out) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+/// #             return out;
+/// #         }
+/// #     }
+/// # }
+/// # block_on(async {
+/// let store = MemoryStore::default();
+///
+/// // the service that owns the signing keys publishes their public keys
+/// let publisher = KvKeyRing::<V4, _>::load(store.clone(), "/paserk/public").await.unwrap();
+/// let mut ring = PublicKeyRing::new();
+/// let pid = ring.insert(Key::<V4, Secret>::new_os_random().public_key());
+/// publisher.publish(&ring).await.unwrap();
+///
+/// // every other service loads them, then keeps watching for changes
+/// let keys = KvKeyRing::<V4, _>::load(store, "/paserk/public").await.unwrap();
+/// assert!(keys.keys().get(&pid).is_some());
+///
+/// ring.insert(Key::<V4, Secret>::new_os_random().public_key());
+/// publisher.publish(&ring).await.unwrap();
+///
+/// assert!(keys.refresh().await.unwrap());
+/// assert_eq!(keys.keys().len(), 2);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct KvKeyRing<V: Version, S> {
+    store: S,
+    key: String,
+    current: RwLock<(u64, Arc<PublicKeyRing<V>>)>,
+}
+
+impl<V: Version, S: KvStore> KvKeyRing<V, S>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Read the keys at `key` in `store`. The ring is empty if `key` is not set.
+    pub async fn load(store: S, key: impl Into<String>) -> Result<Self, PasetoError> {
+        let key = key.into();
+        let current = match store.get(&key).await? {
+            Some(value) => (value.revision, Arc::new(value.value.parse()?)),
+            None => (0, Arc::new(PublicKeyRing::new())),
+        };
+        Ok(Self {
+            store,
+            key,
+            current: RwLock::new(current),
+        })
+    }
+
+    /// The keys as of the last load, publish or refresh
+    pub fn keys(&self) -> Arc<PublicKeyRing<V>> {
+        self.lock_read().1.clone()
+    }
+
+    /// The revision of the keys in the store, or 0 if it was not set
+    pub fn revision(&self) -> u64 {
+        self.lock_read().0
+    }
+
+    /// Replace the keys in the store with `ring`
+    pub async fn publish(&self, ring: &PublicKeyRing<V>) -> Result<(), PasetoError> {
+        let revision = self.store.put(&self.key, &ring.to_string()).await?;
+        self.update(revision, ring.clone());
+        Ok(())
+    }
+
+    /// Wait for the keys to change in the store, then update them.
+    ///
+    /// Returns whether the keys were updated. If the new value can't be parsed, the previous
+    /// keys are kept and the error is returned. Call this in a loop in a background task to
+    /// follow the store.
+    pub async fn refresh(&self) -> Result<bool, PasetoError> {
+        let revision = self.revision();
+        let (revision, ring) = match self.store.watch(&self.key, revision).await? {
+            Some(value) if value.revision > revision => (value.revision, value.value.parse()?),
+            Some(_) => return Ok(false),
+            None => (0, PublicKeyRing::new()),
+        };
+        Ok(self.update(revision, ring))
+    }
+
+    /// Replace the keys unless a newer revision was already seen
+    fn update(&self, revision: u64, ring: PublicKeyRing<V>) -> bool {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        if revision != 0 && revision <= current.0 {
+            return false;
+        }
+        *current = (revision, Arc::new(ring));
+        true
+    }
+
+    fn lock_read(&self) -> std::sync::RwLockReadGuard<'_, (u64, Arc<PublicKeyRing<V>>)> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
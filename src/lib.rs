@@ -67,6 +67,14 @@
 //! * [`V3`] - NIST based modern cryptography (hmac, sha2, aes, p384)
 //! * [`V4`] - Sodium based modern cryptography (blake2b, chacha, ed25519)
 //!
+//! Deployments that must only use NIST approved algorithms can enable the `v3-only` feature
+//! with `default-features = false`. This only allows [`V3`], and enabling `v4` alongside it
+//! will fail to compile.
+//!
+//! `v3-only` does not make the crate FIPS 140 compliant. The primitives come from the
+//! RustCrypto crates, which are not validated modules, and non-standard extensions such as
+//! custom [`WrapProtocol`]s are still available.
+//!
 //! ### IDs: `lid`/`pid`/`sid`
 //!
 //! The [`KeyId`] type represents key ids. Building a KeyID is as simple as
@@ -159,11 +167,16 @@
 //!
 //! See the [`PwWrappedKey`] type for more info.
 
-use std::ops::DerefMut;
+#[cfg(all(feature = "v3-only", feature = "v4"))]
+compile_error!(
+    "the `v3-only` feature only supports V3. Disable default features and do not enable `v4`"
+);
+
+use std::str::FromStr;
+
+#[cfg(feature = "v2")]
+pub use rusty_paseto::core::V2;
 
-use base64ct::Encoding;
-use cipher::Unsigned;
-use generic_array::sequence::GenericSequence;
 #[cfg(feature = "v3")]
 pub use rusty_paseto::core::V3;
 
@@ -172,11 +185,56 @@ pub use rusty_paseto::core::V4;
 
 pub use rusty_paseto::core::PasetoError;
 
-pub use id::KeyId;
+#[cfg(feature = "aes-kw")]
+pub use aeskw::AesKwWrappedKey;
+pub use algorithm::Algorithm;
+pub use alphabet::{Base64Mode, Base64Warning};
+pub use any::{AnyPaserk, Paserk};
+#[cfg(feature = "auth-seal")]
+pub use authseal::AuthSealedKey;
+#[cfg(feature = "async")]
+pub use backend::{CachedKeyStore, KeyStoreBackend};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use commit::KeyCommitment;
+pub use entry::KeyEntry;
+#[cfg(feature = "envelope")]
+pub use envelope::Envelope;
+pub use erased::{ErasedKey, KeyKind};
+pub use error::ErrorCode;
+pub use file::EncryptedFile;
+pub use handle::KeyHandle;
+pub use id::{id_of_paserk, AnyKeyId, KeyId};
+#[cfg(feature = "v4")]
+pub use key::TLS_EXPORTER_LABEL;
 pub use key::{plaintext::PlaintextKey, Key, KeyType, Local, Public, Secret, Version};
-pub use pbkw::PwWrappedKey;
-pub use pke::SealedKey;
-pub use wrap::PieWrappedKey;
+pub use keyring::{KeyRing, KeyRingDiff, PublicKeyRing};
+pub use keyset::SignedKeySet;
+#[cfg(feature = "notify")]
+pub use keystore::ReloadWatcher;
+pub use keystore::{
+    IntegrityIssue, InventoryEntry, KeyStoreFile, KeyStoreLock, PrunePolicy, ReloadingKeyRing,
+};
+#[cfg(feature = "async")]
+pub use kv::{KvKeyRing, KvStore, KvValue};
+pub use lines::{read_paserk_lines, LineError, PaserkLines};
+pub use negotiate::{negotiate, PaserkVersion, VersionError, VersionPolicy};
+pub use onion::OnionSealedKey;
+pub use pbkw::{PwWrapOptions, PwWrappedKey};
+#[cfg(feature = "pkcs11")]
+pub use pke::HardwareUnsealer;
+pub use pke::{
+    CompactSealedKey, EscrowPolicy, MultiSealedKey, ResealEvent, SealedKey, SealedKeyEnvelope,
+    TimestampedSealedKey,
+};
+#[cfg(feature = "threshold")]
+pub use pke::{KeyShare, PartialUnseal};
+pub use profile::ParseProfile;
+pub use quarantine::Quarantined;
+pub use rotation::{DualKey, KeyUsed, RotationManager};
+pub use scan::{scan_paserk, PaserkScan};
+pub use wrap::{CustomWrappedKey, PieWrappedKey, WrapProtocol};
+#[cfg(feature = "async")]
+pub use wrap::{RemoteWrappedKey, RemoteWrapper};
 
 #[cfg(feature = "v3")]
 pub use pbkw::Pbkdf2State;
@@ -184,69 +242,357 @@ pub use pbkw::Pbkdf2State;
 #[cfg(feature = "v4")]
 pub use pbkw::Argon2State;
 
+#[cfg(feature = "aes-kw")]
+mod aeskw;
+mod algorithm;
+mod alphabet;
+mod any;
+#[cfg(feature = "auth-seal")]
+mod authseal;
+#[cfg(feature = "async")]
+mod backend;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "testing", feature = "async"))))]
+#[cfg(all(feature = "testing", feature = "async"))]
+pub mod chaos;
+mod clock;
+mod commit;
+pub mod ct;
+pub mod encoding;
+mod entry;
+#[cfg(feature = "envelope")]
+mod envelope;
+mod erased;
+mod error;
+pub mod exchange;
+mod file;
+pub mod fixtures;
+mod handle;
+pub mod headers;
 mod id;
+#[cfg_attr(docsrs, doc(cfg(feature = "k8s")))]
+#[cfg(feature = "k8s")]
+pub mod k8s;
 mod key;
+mod keyring;
+mod keyset;
+mod keystore;
+#[cfg(feature = "async")]
+mod kv;
+mod lines;
+mod macros;
+mod negotiate;
+mod onion;
 mod pbkw;
 mod pke;
+mod profile;
+mod quarantine;
+mod rotation;
+mod scan;
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg_attr(docsrs, doc(cfg(feature = "transparency")))]
+#[cfg(feature = "transparency")]
+pub mod transparency;
 mod wrap;
 
 /// Internally used traits for encryption version configuration
 pub mod internal {
+    #[cfg(feature = "aes-kw")]
+    pub use crate::aeskw::AesKwWrapType;
+    pub use crate::keyring::KeyRingType;
+    pub use crate::keyset::SignedSetVersion;
+    pub use crate::onion::OnionVersion;
     pub use crate::pbkw::{PwType, PwVersion, PwWrapType};
     pub use crate::pke::SealedVersion;
     pub use crate::wrap::{PieVersion, PieWrapType, WrapType};
+    pub use generic_array;
 }
 
-fn write_b64<W: std::fmt::Write>(b: &[u8], w: &mut W) -> std::fmt::Result {
-    let mut buffer = [0; 64];
-    for chunk in b.chunks(48) {
-        let s = base64ct::Base64UrlUnpadded::encode(chunk, &mut buffer).unwrap();
-        w.write_str(s)?;
-    }
-    Ok(())
+#[doc(hidden)]
+pub mod __private {
+    pub use generic_array::{typenum::Unsigned, GenericArray};
+    #[cfg(feature = "serde")]
+    pub use serde;
+    pub use subtle::ConstantTimeEq;
 }
 
-fn read_b64<L: GenericSequence<u8> + DerefMut<Target = [u8]> + Default>(
-    s: &str,
-) -> Result<L, PasetoError> {
-    let expected_len = (s.len() + 3) / 4 * 3;
-    if expected_len < <L::Length as Unsigned>::USIZE {
-        return Err(PasetoError::PayloadBase64Decode {
-            source: base64::DecodeError::InvalidLength(s.len()),
-        });
-    }
+use encoding::{read_b64, write_b64};
 
+#[cfg(feature = "arbitrary")]
+fn arbitrary_bytes<L: std::ops::DerefMut<Target = [u8]> + Default>(
+    u: &mut arbitrary::Unstructured<'_>,
+) -> arbitrary::Result<L> {
     let mut total = L::default();
+    u.fill_buffer(&mut total)?;
+    Ok(total)
+}
+
+/// Whether the key serialization is safe to be added to a PASETO footer.
+///
+/// ```
+/// use rusty_paserk::{footer_json, Key, Local, SafeForFooter, Secret, V4};
+///
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+///
+/// let kid = local_key.to_id();
+/// let sealed = local_key.seal(&secret_key.public_key());
+///
+/// let footer = footer_json(&[("kid", &kid), ("wpk", &sealed)]);
+/// assert_eq!(footer, format!(r#"{{"kid":"{}","wpk":"{}"}}"#, kid, sealed));
+/// assert_eq!(kid.footer_fragment(), kid.to_string());
+/// ```
+pub trait SafeForFooter: std::fmt::Display {
+    /// Write this value into a footer
+    fn write_footer_fragment(&self, w: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(w, "{self}")
+    }
 
-    let len = base64ct::Base64UrlUnpadded::decode(s, &mut total)
-        .map_err(|_| PasetoError::PayloadBase64Decode {
-            source: base64::DecodeError::InvalidLength(s.len()),
-        })?
-        .len();
+    /// This value, as it should appear in a footer
+    fn footer_fragment(&self) -> String {
+        let mut s = String::new();
+        self.write_footer_fragment(&mut s)
+            .expect("writing to a string should not fail");
+        s
+    }
+}
 
-    if len != <L::Length as Unsigned>::USIZE {
-        return Err(PasetoError::PayloadBase64Decode {
-            source: base64::DecodeError::InvalidLength(s.len()),
-        });
+/// Compose a JSON object footer from PASERK values that are safe to put in footers.
+///
+/// The values never need escaping. The field names are escaped as JSON strings.
+pub fn footer_json(fields: &[(&str, &dyn SafeForFooter)]) -> String {
+    let mut s = String::from("{");
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            s.push(',');
+        }
+        push_json_str(&mut s, name);
+        s.push_str(":\"");
+        value
+            .write_footer_fragment(&mut s)
+            .expect("writing to a string should not fail");
+        s.push('"');
     }
+    s.push('}');
+    s
+}
 
-    Ok(total)
+/// Push `value` as an escaped JSON string
+fn push_json_str(s: &mut String, value: &str) {
+    use std::fmt::Write;
+
+    s.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            c if c.is_control() => write!(s, "\\u{:04x}", c as u32).unwrap(),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
 }
 
-/// Whether the key serialization is safe to be added to a PASETO footer.
-pub trait SafeForFooter {}
+/// PASERK types whose serialized length is known before formatting.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, PaserkLen, Secret, V4};
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let kid = secret_key.public_key().to_id();
+/// assert_eq!(kid.paserk_len(), kid.to_string().len());
+///
+/// let sealed = Key::<V4, Local>::new_os_random().seal(&secret_key.public_key());
+/// let s = sealed.to_string_exact();
+/// assert_eq!(s.len(), s.capacity());
+/// assert_eq!(s, sealed.to_string());
+/// ```
+pub trait PaserkLen: std::fmt::Display {
+    /// The exact length of the `Display` output
+    fn paserk_len(&self) -> usize;
+
+    /// Format into a `String` that is allocated once, with exactly the right capacity
+    fn to_string_exact(&self) -> String {
+        use std::fmt::Write;
 
-#[cfg(any(test, fuzzing))]
+        let mut s = String::with_capacity(self.paserk_len());
+        write!(s, "{self}").expect("writing to a string should not fail");
+        s
+    }
+}
+
+/// The longest input accepted by [`FromPaserkBytes`], unless a different limit is given.
+///
+/// This is well above the length of every PASERK type in this crate, and bounds the memory and
+/// CPU spent on untrusted input such as footers.
+pub const DEFAULT_MAX_PASERK_LEN: usize = 2048;
+
+/// Parse a PASERK value directly from bytes, such as a footer received over the network.
+///
+/// ```
+/// use rusty_paserk::{FromPaserkBytes, Key, KeyId, Local, V4};
+///
+/// let local_key = Key::<V4, Local>::new_os_random();
+/// let footer = local_key.to_id().to_string().into_bytes();
+///
+/// let kid = KeyId::<V4, Local>::from_paserk_bytes(&footer).unwrap();
+/// assert_eq!(kid, local_key.to_id());
+/// ```
+pub trait FromPaserkBytes: FromStr<Err = PasetoError> {
+    /// Parse the value from its PASERK serialization.
+    ///
+    /// Inputs longer than [`DEFAULT_MAX_PASERK_LEN`] are rejected before parsing.
+    fn from_paserk_bytes(bytes: &[u8]) -> Result<Self, PasetoError> {
+        Self::from_paserk_bytes_max(bytes, DEFAULT_MAX_PASERK_LEN)
+    }
+
+    /// Parse the value from its PASERK serialization, rejecting inputs longer than `max_len`
+    /// before doing any other work.
+    ///
+    /// ```
+    /// use rusty_paserk::{FromPaserkBytes, Key, KeyId, Local, V4};
+    ///
+    /// let kid = Key::<V4, Local>::new_os_random().to_id().to_string();
+    /// assert!(KeyId::<V4, Local>::from_paserk_bytes_max(kid.as_bytes(), 64).is_ok());
+    /// assert!(KeyId::<V4, Local>::from_paserk_bytes_max(kid.as_bytes(), 32).is_err());
+    /// ```
+    fn from_paserk_bytes_max(bytes: &[u8], max_len: usize) -> Result<Self, PasetoError> {
+        if bytes.len() > max_len {
+            return Err(PasetoError::IncorrectSize);
+        }
+        std::str::from_utf8(bytes)
+            .map_err(|source| PasetoError::Utf8Error { source })?
+            .parse()
+    }
+
+    /// Parse the value from the front of `bytes`, returning the data that follows it.
+    ///
+    /// The value ends at the first byte that is not base64url or `.`, so the trailing data
+    /// must start with a separator such as `;` or `,`.
+    ///
+    /// ```
+    /// use rusty_paserk::{FromPaserkBytes, Key, KeyId, Local, V4};
+    ///
+    /// let local_key = Key::<V4, Local>::new_os_random();
+    /// let footer = format!("{};tenant=acme", local_key.to_id());
+    ///
+    /// let (kid, rest) = KeyId::<V4, Local>::from_paserk_prefix(footer.as_bytes()).unwrap();
+    /// assert_eq!(kid, local_key.to_id());
+    /// assert_eq!(rest, b";tenant=acme");
+    /// ```
+    fn from_paserk_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), PasetoError> {
+        // don't scan past the longest value that could be accepted
+        let end = bytes
+            .iter()
+            .take(DEFAULT_MAX_PASERK_LEN + 1)
+            .position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.')))
+            .unwrap_or(bytes.len());
+        let (value, rest) = bytes.split_at(end);
+        Ok((Self::from_paserk_bytes(value)?, rest))
+    }
+
+    /// Parse the value, with the base64 strictness chosen by `mode`.
+    ///
+    /// Returns a [`Base64Warning`] if the value was only accepted because `mode` is lenient.
+    ///
+    /// ```
+    /// use rusty_paserk::{Base64Mode, FromPaserkBytes, KeyId, Local, V4};
+    ///
+    /// let kid = "k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB";
+    /// let (_, warning) = KeyId::<V4, Local>::from_paserk_with(kid, Base64Mode::Strict).unwrap();
+    /// assert!(warning.is_none());
+    ///
+    /// let kid = "k4.lid.XxPub51WIAEmbVTmrs+lFoFodxTSKk8RuYEJk3gl+DYB";
+    /// assert!(KeyId::<V4, Local>::from_paserk_with(kid, Base64Mode::Strict).is_err());
+    ///
+    /// # #[cfg(feature = "lenient-base64")] {
+    /// let (_, warning) = KeyId::<V4, Local>::from_paserk_with(kid, Base64Mode::Lenient).unwrap();
+    /// assert!(warning.is_some());
+    /// # }
+    /// ```
+    fn from_paserk_with(
+        s: &str,
+        mode: Base64Mode,
+    ) -> Result<(Self, Option<Base64Warning>), PasetoError> {
+        if s.len() > DEFAULT_MAX_PASERK_LEN {
+            return Err(PasetoError::IncorrectSize);
+        }
+        let (s, warning) = mode.normalize(s);
+        Ok((s.parse()?, warning))
+    }
+
+    /// Parse the value with all of the options chosen by `profile`.
+    ///
+    /// Returns a [`Base64Warning`] if the value was only accepted because `profile` is lenient.
+    ///
+    /// ```
+    /// use rusty_paserk::{FromPaserkBytes, KeyId, Local, ParseProfile, V4};
+    ///
+    /// let kid = b" k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB\n";
+    /// assert!(KeyId::<V4, Local>::from_paserk_profile(kid, ParseProfile::Strict).is_err());
+    ///
+    /// let (kid, _) = KeyId::<V4, Local>::from_paserk_profile(kid, ParseProfile::Compat).unwrap();
+    /// assert_eq!(kid.to_string(), "k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB");
+    /// ```
+    fn from_paserk_profile(
+        bytes: &[u8],
+        profile: ParseProfile,
+    ) -> Result<(Self, Option<Base64Warning>), PasetoError> {
+        if bytes.len() > profile.max_len() {
+            return Err(PasetoError::IncorrectSize);
+        }
+        let s = std::str::from_utf8(bytes).map_err(|source| PasetoError::Utf8Error { source })?;
+        let s = if profile.trims_whitespace() {
+            s.trim()
+        } else {
+            s
+        };
+        let (s, warning) = profile.base64_mode().normalize(s);
+        Ok((s.parse()?, warning))
+    }
+}
+
+/// Deterministic randomness for tests and fuzzing.
+///
+/// Enable the `testing` feature to use these in your own tests. They must never be used
+/// outside of tests, as they make the ephemeral keys used by `seal` and `wrap` predictable.
+///
+/// ```
+/// use rusty_paserk::{fuzzing::FakeRng, Key, Local, Secret, V4};
+///
+/// let key = Key::<V4, Local>::from_bytes([1; 32]);
+/// let secret_key = Key::<V4, Secret>::from_secret_key([2; 32]);
+///
+/// let sealed1 = key.seal_with_rng(&secret_key.public_key(), &mut FakeRng::new([3; 32]));
+/// let sealed2 = key.seal_with_rng(&secret_key.public_key(), &mut FakeRng::new([3; 32]));
+/// assert_eq!(sealed1.to_string(), sealed2.to_string());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+#[cfg(any(test, fuzzing, feature = "testing"))]
 pub mod fuzzing {
     use rand::{CryptoRng, RngCore};
 
     #[derive(Clone, Debug)]
-    /// a consistent rng store
+    /// a consistent rng store. It returns the bytes in order, and panics once they run out.
+    ///
+    /// # Panics
+    ///
+    /// Every method panics with "not enough entropy" if more bytes are requested than remain.
     pub struct FakeRng<const N: usize> {
+        /// The bytes to return
         pub bytes: [u8; N],
+        /// How many bytes have been returned so far
         pub start: usize,
     }
 
+    impl<const N: usize> FakeRng<N> {
+        /// Create a new rng that will return exactly these bytes
+        pub fn new(bytes: [u8; N]) -> Self {
+            Self { bytes, start: 0 }
+        }
+    }
+
     #[cfg(feature = "arbitrary")]
     impl<'a, const N: usize> arbitrary::Arbitrary<'a> for FakeRng<N>
     where
@@ -262,13 +608,16 @@ pub mod fuzzing {
 
     impl<const N: usize> RngCore for FakeRng<N> {
         fn next_u32(&mut self) -> u32 {
-            unimplemented!()
+            rand_core::impls::next_u32_via_fill(self)
         }
 
         fn next_u64(&mut self) -> u64 {
-            unimplemented!()
+            rand_core::impls::next_u64_via_fill(self)
         }
 
+        /// # Panics
+        ///
+        /// Panics with "not enough entropy" if `dest` is longer than the remaining bytes
         fn fill_bytes(&mut self, dest: &mut [u8]) {
             let remaining = N - self.start;
             let requested = dest.len();
@@ -288,12 +637,14 @@ pub mod fuzzing {
     // not really
     impl<const N: usize> CryptoRng for FakeRng<N> {}
 
+    /// Seal round-trip harnesses
     pub mod seal {
         #[cfg(feature = "v3")]
         pub use crate::pke::fuzz_tests::V3SealInput;
         #[cfg(feature = "v4")]
         pub use crate::pke::fuzz_tests::V4SealInput;
     }
+    /// Wrap round-trip harnesses
     pub mod wrap {
         pub use crate::wrap::fuzz_tests::FuzzInput;
     }
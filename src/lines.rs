@@ -0,0 +1,170 @@
+//! Reading files with one PASERK value per line.
+
+use std::{
+    fmt,
+    io::{self, BufRead, Read},
+    marker::PhantomData,
+};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{FromPaserkBytes, DEFAULT_MAX_PASERK_LEN};
+
+/// Iterate over the PASERK values in a reader, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped. Surrounding whitespace is ignored.
+/// At most [`DEFAULT_MAX_PASERK_LEN`] bytes of each line are buffered, and longer lines are
+/// reported as errors.
+///
+/// ```
+/// use rusty_paserk::{read_paserk_lines, Key, KeyId, Local, V4};
+///
+/// let key1 = Key::<V4, Local>::new_os_random();
+/// let key2 = Key::<V4, Local>::new_os_random();
+/// let file = format!("# trusted keys\n{}\n\n{}\nnot a key\n", key1.to_id(), key2.to_id());
+///
+/// let mut ids = read_paserk_lines::<KeyId<V4, Local>, _>(file.as_bytes());
+/// assert_eq!(ids.next().unwrap().unwrap(), key1.to_id());
+/// assert_eq!(ids.next().unwrap().unwrap(), key2.to_id());
+///
+/// let err = ids.next().unwrap().unwrap_err();
+/// assert_eq!(err.line(), 5);
+///
+/// assert!(ids.next().is_none());
+///
+/// let file = format!("{}\n{}\n", "A".repeat(1 << 20), key1.to_id());
+/// let mut ids = read_paserk_lines::<KeyId<V4, Local>, _>(file.as_bytes());
+/// assert_eq!(ids.next().unwrap().unwrap_err().line(), 1);
+/// assert_eq!(ids.next().unwrap().unwrap(), key1.to_id());
+/// ```
+pub fn read_paserk_lines<T: FromPaserkBytes, R: io::BufRead>(reader: R) -> PaserkLines<T, R> {
+    PaserkLines {
+        reader,
+        line: 0,
+        buf: Vec::new(),
+        _value: PhantomData,
+    }
+}
+
+/// Iterator returned by [`read_paserk_lines`]
+pub struct PaserkLines<T, R> {
+    reader: R,
+    line: usize,
+    buf: Vec<u8>,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T: FromPaserkBytes, R: io::BufRead> Iterator for PaserkLines<T, R> {
+    type Item = Result<T, LineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            self.line += 1;
+            // don't buffer more of a line than could be accepted
+            let limit = DEFAULT_MAX_PASERK_LEN as u64 + 1;
+            let read = (&mut self.reader)
+                .take(limit)
+                .read_until(b'\n', &mut self.buf);
+            let too_long = self.buf.len() as u64 == limit && !self.buf.ends_with(b"\n");
+            let read = match read {
+                Ok(0) => return None,
+                Ok(_) if too_long => skip_line(&mut self.reader),
+                Ok(_) => Ok(()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = read {
+                return Some(Err(LineError {
+                    line: self.line,
+                    kind: LineErrorKind::Io(err),
+                }));
+            }
+
+            let value = self.buf.trim_ascii();
+            if value.is_empty() || value.starts_with(b"#") {
+                continue;
+            }
+            if too_long {
+                return Some(Err(LineError {
+                    line: self.line,
+                    kind: LineErrorKind::Parse(PasetoError::IncorrectSize),
+                }));
+            }
+
+            return Some(T::from_paserk_bytes(value).map_err(|err| LineError {
+                line: self.line,
+                kind: LineErrorKind::Parse(err),
+            }));
+        }
+    }
+}
+
+/// Discard the rest of the current line
+fn skip_line(reader: &mut impl io::BufRead) -> io::Result<()> {
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if buf.is_empty() {
+            return Ok(());
+        }
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                reader.consume(i + 1);
+                return Ok(());
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+/// An error reading a PASERK value from a line-delimited file
+#[derive(Debug)]
+pub struct LineError {
+    line: usize,
+    kind: LineErrorKind,
+}
+
+#[derive(Debug)]
+enum LineErrorKind {
+    Io(io::Error),
+    Parse(PasetoError),
+}
+
+impl LineError {
+    /// The 1-based line number that failed
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The parse error, if this line was read successfully but was not a valid value
+    pub fn parse_error(&self) -> Option<&PasetoError> {
+        match &self.kind {
+            LineErrorKind::Parse(err) => Some(err),
+            LineErrorKind::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LineErrorKind::Io(_) => write!(f, "could not read line {}", self.line),
+            LineErrorKind::Parse(_) => write!(f, "invalid PASERK on line {}", self.line),
+        }
+    }
+}
+
+impl std::error::Error for LineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LineErrorKind::Io(err) => Some(err),
+            LineErrorKind::Parse(err) => Some(err),
+        }
+    }
+}
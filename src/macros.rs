@@ -0,0 +1,172 @@
+/// Define a fixed length PASERK type, for crates adding their own vendor-specific types.
+///
+/// The type is generic over the [`Version`](crate::Version) and is serialized as the version
+/// header, the type header, then the unpadded base64url bytes, like the types in this crate.
+/// It gets the same `Display`, `FromStr`, [`SafeForFooter`](crate::SafeForFooter),
+/// [`FromPaserkBytes`](crate::FromPaserkBytes), [`PaserkLen`](crate::PaserkLen) and, with the
+/// `serde` feature, serde impls. Equality is checked in constant time.
+///
+/// The type header must end with `.`, which is checked at compile time.
+///
+/// ```
+/// use rusty_paserk::{define_paserk_type, PaserkLen, V4};
+///
+/// define_paserk_type! {
+///     /// A vendor-specific device attestation tag
+///     pub struct DeviceTag, "acme-tag.", rusty_paserk::internal::generic_array::typenum::U16;
+/// }
+///
+/// let tag = DeviceTag::<V4>::new([7; 16].into());
+/// let s = tag.to_string();
+/// assert_eq!(s, "k4.acme-tag.BwcHBwcHBwcHBwcHBwcHBw");
+/// assert_eq!(tag.paserk_len(), s.len());
+///
+/// let tag2: DeviceTag<V4> = s.parse().unwrap();
+/// assert_eq!(tag, tag2);
+/// assert!("k4.lid.BwcHBwcHBwcHBwcHBwcHBw".parse::<DeviceTag<V4>>().is_err());
+/// ```
+#[macro_export]
+macro_rules! define_paserk_type {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident, $header:literal, $len:ty;) => {
+        $(#[$meta])*
+        $vis struct $name<V: $crate::Version> {
+            bytes: $crate::__private::GenericArray<u8, $len>,
+            version: ::core::marker::PhantomData<V>,
+        }
+
+        const _: () = ::core::assert!(
+            $crate::headers::is_type_header($header),
+            "type headers must end with '.'"
+        );
+
+        impl<V: $crate::Version> $name<V> {
+            /// The type header, without the version
+            pub const HEADER: &'static str = $header;
+
+            /// Wrap raw bytes
+            pub fn new(bytes: $crate::__private::GenericArray<u8, $len>) -> Self {
+                Self {
+                    bytes,
+                    version: ::core::marker::PhantomData,
+                }
+            }
+
+            /// The raw bytes
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.bytes
+            }
+        }
+
+        impl<V: $crate::Version> ::core::clone::Clone for $name<V> {
+            fn clone(&self) -> Self {
+                Self::new(self.bytes.clone())
+            }
+        }
+
+        impl<V: $crate::Version> ::core::cmp::PartialEq for $name<V> {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::__private::ConstantTimeEq::ct_eq(&self.bytes[..], &other.bytes[..]).into()
+            }
+        }
+
+        impl<V: $crate::Version> ::core::cmp::Eq for $name<V> {}
+
+        impl<V: $crate::Version> ::core::fmt::Debug for $name<V> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(self, f)
+            }
+        }
+
+        impl<V: $crate::Version> ::core::fmt::Display for $name<V> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(V::KEY_HEADER)?;
+                f.write_str($header)?;
+                $crate::encoding::write_b64(&self.bytes, f)
+            }
+        }
+
+        impl<V: $crate::Version> ::core::str::FromStr for $name<V> {
+            type Err = $crate::PasetoError;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                let s = s
+                    .strip_prefix(V::KEY_HEADER)
+                    .ok_or($crate::PasetoError::WrongHeader)?;
+                let s = s
+                    .strip_prefix($header)
+                    .ok_or($crate::PasetoError::WrongHeader)?;
+                Ok(Self::new($crate::encoding::read_b64(s)?))
+            }
+        }
+
+        impl<V: $crate::Version> $crate::SafeForFooter for $name<V> {}
+        impl<V: $crate::Version> $crate::FromPaserkBytes for $name<V> {}
+        impl<V: $crate::Version> $crate::PaserkLen for $name<V> {
+            fn paserk_len(&self) -> usize {
+                V::KEY_HEADER.len()
+                    + $header.len()
+                    + $crate::encoding::encoded_len(
+                        <$len as $crate::__private::Unsigned>::USIZE,
+                    )
+            }
+        }
+
+        $crate::__paserk_serde!($name, $header);
+    };
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __paserk_serde {
+    ($name:ident, $header:literal) => {
+        impl<V: $crate::Version> $crate::__private::serde::Serialize for $name<V> {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de, V: $crate::Version> $crate::__private::serde::Deserialize<'de> for $name<V> {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                struct FromStrVisitor<V>(::core::marker::PhantomData<V>);
+                impl<'de, V: $crate::Version> $crate::__private::serde::de::Visitor<'de>
+                    for FromStrVisitor<V>
+                {
+                    type Value = $name<V>;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        write!(
+                            formatter,
+                            "a \"{}{}\" serialized value",
+                            V::KEY_HEADER,
+                            $header
+                        )
+                    }
+                    fn visit_str<E>(self, v: &str) -> ::core::result::Result<Self::Value, E>
+                    where
+                        E: $crate::__private::serde::de::Error,
+                    {
+                        v.parse().map_err(E::custom)
+                    }
+                }
+                deserializer.deserialize_str(FromStrVisitor(::core::marker::PhantomData))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __paserk_serde {
+    ($name:ident, $header:literal) => {};
+}
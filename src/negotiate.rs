@@ -0,0 +1,163 @@
+//! Picking a PASERK version that both sides of an exchange support.
+
+use std::fmt;
+
+/// A PASERK version, for negotiating with peers at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum PaserkVersion {
+    /// `k2`: Sodium based legacy cryptography
+    #[cfg(feature = "v2")]
+    V2,
+    /// `k3`: NIST based modern cryptography
+    V3,
+    /// `k4`: Sodium based modern cryptography
+    V4,
+}
+
+impl PaserkVersion {
+    /// The versions enabled in this build of the crate, from weakest to strongest
+    pub const SUPPORTED: &'static [PaserkVersion] = &[
+        #[cfg(feature = "v2")]
+        PaserkVersion::V2,
+        #[cfg(feature = "v3")]
+        PaserkVersion::V3,
+        #[cfg(feature = "v4")]
+        PaserkVersion::V4,
+    ];
+
+    /// The PASERK header for this version
+    pub const fn header(self) -> &'static str {
+        match self {
+            #[cfg(feature = "v2")]
+            PaserkVersion::V2 => "k2.",
+            PaserkVersion::V3 => "k3.",
+            PaserkVersion::V4 => "k4.",
+        }
+    }
+}
+
+impl PaserkVersion {
+    /// Find the version of a PASERK string.
+    ///
+    /// Syntactically valid headers for versions that are not supported by this build,
+    /// such as `k1.` or a future `k5.`, are reported as [`VersionError::Unsupported`],
+    /// so callers can log more than a generic header failure.
+    ///
+    /// ```
+    /// use rusty_paserk::{PaserkVersion, VersionError};
+    ///
+    /// let version = PaserkVersion::of_paserk("k4.lid.XxPub51WIAEmbVTmrs-lFoFodxTSKk8RuYEJk3gl-DYB");
+    /// assert_eq!(version, Ok(PaserkVersion::V4));
+    ///
+    /// let version = PaserkVersion::of_paserk("k1.local.AAAA");
+    /// assert_eq!(version, Err(VersionError::Unsupported { version: 1 }));
+    ///
+    /// #[cfg(feature = "v2")]
+    /// assert_eq!(PaserkVersion::of_paserk("k2.local.AAAA"), Ok(PaserkVersion::V2));
+    ///
+    /// assert_eq!(PaserkVersion::of_paserk("v4.local.AAAA"), Err(VersionError::Malformed));
+    /// ```
+    pub fn of_paserk(s: &str) -> Result<Self, VersionError> {
+        let (header, _) = s.split_once('.').ok_or(VersionError::Malformed)?;
+        let digits = header.strip_prefix('k').ok_or(VersionError::Malformed)?;
+        let canonical = digits == "0" || !digits.starts_with('0');
+        if digits.is_empty() || !canonical || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(VersionError::Malformed);
+        }
+        let version = digits.parse().map_err(|_| VersionError::Malformed)?;
+
+        Self::SUPPORTED
+            .iter()
+            .copied()
+            .find(|v| v.header() == &s[..header.len() + 1])
+            .ok_or(VersionError::Unsupported { version })
+    }
+}
+
+/// The version of a PASERK string could not be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum VersionError {
+    /// The string has a valid version header, for a version this build does not support
+    Unsupported {
+        /// The version number from the header
+        version: u32,
+    },
+    /// The string does not start with a version header
+    Malformed,
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Unsupported { version } => {
+                write!(f, "PASERK version k{version} is not supported")
+            }
+            VersionError::Malformed => f.write_str("missing PASERK version header"),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+impl From<VersionError> for rusty_paseto::core::PasetoError {
+    fn from(_: VersionError) -> Self {
+        rusty_paseto::core::PasetoError::WrongHeader
+    }
+}
+
+impl fmt::Display for PaserkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.header().trim_end_matches('.'))
+    }
+}
+
+/// Rules for which versions may be negotiated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionPolicy {
+    /// Never negotiate a version older than this
+    pub minimum: PaserkVersion,
+}
+
+impl Default for VersionPolicy {
+    /// Allow every version enabled in this build
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "v2")]
+            minimum: PaserkVersion::V2,
+            #[cfg(not(feature = "v2"))]
+            minimum: PaserkVersion::V3,
+        }
+    }
+}
+
+impl VersionPolicy {
+    /// Pick the strongest version supported by both this build and the peer,
+    /// that is allowed by this policy.
+    ///
+    /// The result only depends on the set of versions, not the order the peer lists them in.
+    pub fn negotiate(&self, peer_supported: &[PaserkVersion]) -> Option<PaserkVersion> {
+        PaserkVersion::SUPPORTED
+            .iter()
+            .rev()
+            .copied()
+            .filter(|v| *v >= self.minimum)
+            .find(|v| peer_supported.contains(v))
+    }
+}
+
+/// Pick the strongest version supported by both this build and the peer.
+///
+/// ```
+/// use rusty_paserk::{negotiate, PaserkVersion, VersionPolicy};
+///
+/// let peer = [PaserkVersion::V4, PaserkVersion::V3];
+/// assert_eq!(negotiate(&peer), Some(PaserkVersion::V4));
+///
+/// let policy = VersionPolicy { minimum: PaserkVersion::V4 };
+/// assert_eq!(policy.negotiate(&[PaserkVersion::V3]), None);
+/// ```
+pub fn negotiate(peer_supported: &[PaserkVersion]) -> Option<PaserkVersion> {
+    VersionPolicy::default().negotiate(peer_supported)
+}
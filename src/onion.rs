@@ -0,0 +1,225 @@
+//! Onion sealing: a sealed key that is itself sealed to a relay.
+//!
+//! The relay can peel off its layer to learn which [`SealedKey`] to forward,
+//! but cannot unseal the local key inside it.
+
+use std::{fmt, str::FromStr};
+
+use base64::Engine;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::{Footer, Paseto, PasetoError, PasetoNonce, PasetoSymmetricKey, Payload};
+
+#[cfg(feature = "v3")]
+use rusty_paseto::core::V3;
+#[cfg(feature = "v4")]
+use rusty_paseto::core::V4;
+
+use crate::{internal::SealedVersion, Key, Local, Public, SealedKey, Secret};
+
+/// A [`SealedKey`] for a recipient, encrypted for delivery through a relay.
+///
+/// This is encoded as a `local` PASETO token. The payload is the `seal` for the recipient,
+/// and the footer is the `seal` of the one-time token key for the relay.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, OnionSealedKey, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+///
+/// let recipient = Key::<V4, Secret>::new_os_random();
+/// let relay = Key::<V4, Secret>::new_os_random();
+///
+/// let onion = key.seal_onion(&recipient.public_key(), &relay.public_key()).unwrap();
+/// let onion = onion.to_string();
+///
+/// // relay
+/// let onion: OnionSealedKey<V4> = onion.parse().unwrap();
+/// let sealed = onion.peel(&relay).unwrap();
+///
+/// // recipient
+/// let key2 = sealed.unseal(&recipient).unwrap();
+/// assert_eq!(key, key2);
+/// ```
+pub struct OnionSealedKey<V: OnionVersion> {
+    token: String,
+    relay_key: SealedKey<V>,
+}
+
+impl<V: OnionVersion> Key<V, Local> {
+    /// Seal this key to `recipient`, then seal the result to `relay`. See [`OnionSealedKey`]
+    pub fn seal_onion(
+        &self,
+        recipient: &Key<V, Public>,
+        relay: &Key<V, Public>,
+    ) -> Result<OnionSealedKey<V>, PasetoError> {
+        self.seal_onion_with_rng(recipient, relay, &mut OsRng)
+    }
+
+    /// Seal this key to `recipient`, then seal the result to `relay`,
+    /// using the provided random source.
+    pub fn seal_onion_with_rng(
+        &self,
+        recipient: &Key<V, Public>,
+        relay: &Key<V, Public>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<OnionSealedKey<V>, PasetoError> {
+        let inner = self.seal_with_rng(recipient, rng).to_string();
+
+        let token_key = Key::<V, Local>::new_random(rng);
+        let relay_key = token_key.seal_with_rng(relay, rng);
+
+        let mut nonce = [0; 32];
+        rng.fill_bytes(&mut nonce);
+
+        let token = V::encrypt(&token_key, &nonce, &inner, &relay_key.to_string())?;
+        Ok(OnionSealedKey { token, relay_key })
+    }
+}
+
+impl<V: OnionVersion> OnionSealedKey<V> {
+    /// Remove the relay's layer, returning the [`SealedKey`] to forward to the recipient.
+    pub fn peel(self, relay: &Key<V, Secret>) -> Result<SealedKey<V>, PasetoError> {
+        let Self { token, relay_key } = self;
+        let footer = relay_key.to_string();
+        let token_key = relay_key.unseal(relay)?;
+        V::decrypt(&token_key, &token, &footer)?.parse()
+    }
+}
+
+impl<V: OnionVersion> crate::FromPaserkBytes for OnionSealedKey<V> {}
+
+impl<V: OnionVersion> FromStr for OnionSealedKey<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix(V::TOKEN_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let rest = rest
+            .strip_prefix("local.")
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let (_payload, footer) = rest.split_once('.').ok_or(PasetoError::IncorrectSize)?;
+        let footer = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(footer)
+            .map_err(|_| PasetoError::PayloadBase64Decode {
+                source: base64::DecodeError::InvalidLength(footer.len()),
+            })?;
+        let footer = std::str::from_utf8(&footer).map_err(|_| PasetoError::InvalidKey)?;
+
+        Ok(Self {
+            token: s.to_owned(),
+            relay_key: footer.parse()?,
+        })
+    }
+}
+
+impl<V: OnionVersion> fmt::Display for OnionSealedKey<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token)
+    }
+}
+
+/// Version info for configuring onion sealing
+pub trait OnionVersion: SealedVersion {
+    #[doc(hidden)]
+    fn encrypt(
+        key: &Key<Self, Local>,
+        nonce: &[u8; 32],
+        payload: &str,
+        footer: &str,
+    ) -> Result<String, PasetoError>;
+
+    #[doc(hidden)]
+    fn decrypt(key: &Key<Self, Local>, token: &str, footer: &str) -> Result<String, PasetoError>;
+}
+
+#[cfg(feature = "v3")]
+impl OnionVersion for V3 {
+    fn encrypt(
+        key: &Key<Self, Local>,
+        nonce: &[u8; 32],
+        payload: &str,
+        footer: &str,
+    ) -> Result<String, PasetoError> {
+        let nonce = rusty_paseto::core::Key::from(nonce);
+        Paseto::<V3, rusty_paseto::core::Local>::builder()
+            .set_payload(Payload::from(payload))
+            .set_footer(Footer::from(footer))
+            .try_encrypt(&PasetoSymmetricKey::from(*key), &PasetoNonce::from(&nonce))
+    }
+
+    fn decrypt(key: &Key<Self, Local>, token: &str, footer: &str) -> Result<String, PasetoError> {
+        Paseto::<V3, rusty_paseto::core::Local>::try_decrypt(
+            token,
+            &PasetoSymmetricKey::from(*key),
+            Footer::from(footer),
+            None,
+        )
+    }
+}
+
+#[cfg(feature = "v4")]
+impl OnionVersion for V4 {
+    fn encrypt(
+        key: &Key<Self, Local>,
+        nonce: &[u8; 32],
+        payload: &str,
+        footer: &str,
+    ) -> Result<String, PasetoError> {
+        let nonce = rusty_paseto::core::Key::from(nonce);
+        Paseto::<V4, rusty_paseto::core::Local>::builder()
+            .set_payload(Payload::from(payload))
+            .set_footer(Footer::from(footer))
+            .try_encrypt(&PasetoSymmetricKey::from(*key), &PasetoNonce::from(&nonce))
+    }
+
+    fn decrypt(key: &Key<Self, Local>, token: &str, footer: &str) -> Result<String, PasetoError> {
+        Paseto::<V4, rusty_paseto::core::Local>::try_decrypt(
+            token,
+            &PasetoSymmetricKey::from(*key),
+            Footer::from(footer),
+            None,
+        )
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: OnionVersion> serde::Serialize for OnionSealedKey<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: OnionVersion> serde::Deserialize<'de> for OnionSealedKey<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: OnionVersion> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = OnionSealedKey<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a \"{}local.\" token with a sealed key footer",
+                    V::TOKEN_HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
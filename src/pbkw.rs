@@ -16,13 +16,15 @@ use rand::{CryptoRng, RngCore};
 
 use rand::rngs::OsRng;
 use rusty_paseto::core::PasetoError;
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
 use rusty_paseto::core::V4;
 use subtle::ConstantTimeEq;
 
-use crate::{read_b64, write_b64, Key, KeyType, Local, Secret, Version};
+use crate::{read_b64, write_b64, Algorithm, Key, KeyType, Local, Secret, Version};
 
 /// Password wrapped keys
 ///
@@ -106,10 +108,47 @@ impl<V: PwVersion, K: PwWrapType<V>> Key<V, K> {
         self.pw_wrap_with_settings(password, V::KdfState::default())
     }
 
+    /// Password wrapped keys
+    ///
+    /// * Use the options to configure how strong the derived key should be
+    /// * Use the OS RNG to determine a random salt
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the options are invalid for this version.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, PwWrapOptions, PwWrappedKey, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    /// let options = PwWrapOptions::new().memory(16 * 1024 * 1024).iterations(3);
+    ///
+    /// let wrapped = key.pw_wrap_with_options(b"hunter2", &options).unwrap().to_string();
+    ///
+    /// let wrapped: PwWrappedKey<V4, Local> = wrapped.parse().unwrap();
+    /// assert_eq!(wrapped.settings().mem, 16 * 1024 * 1024);
+    /// assert_eq!(wrapped.settings().time, 3);
+    /// assert_eq!(wrapped.unwrap_key(b"hunter2").unwrap(), key);
+    ///
+    /// // argon2 needs at least 8 KiB of memory per lane
+    /// let options = PwWrapOptions::new().memory(8 * 1024).parallelism(2);
+    /// assert!(key.pw_wrap_with_options(b"hunter2", &options).is_err());
+    /// ```
+    pub fn pw_wrap_with_options(
+        &self,
+        password: &[u8],
+        options: &PwWrapOptions,
+    ) -> Result<PwWrappedKey<V, K>, PasetoError> {
+        self.try_pw_wrap_with_settings(password, V::settings(options)?)
+    }
+
     /// Password wrapped keys
     ///
     /// * Use the settings to configure how strong the derived key should be
     /// * Use the OS RNG to determine a random salt
+    ///
+    /// # Panics
+    ///
+    /// If the settings are invalid. Use [`Argon2State::new`] or [`Pbkdf2State::new`] to check them first,
+    /// or [`try_pw_wrap_with_settings`](Self::try_pw_wrap_with_settings).
     pub fn pw_wrap_with_settings(
         &self,
         password: &[u8],
@@ -122,16 +161,66 @@ impl<V: PwVersion, K: PwWrapType<V>> Key<V, K> {
     ///
     /// * Use the settings to configure how strong the derived key should be
     /// * Use the RNG source to determine a random salt
+    ///
+    /// # Panics
+    ///
+    /// If the settings are invalid. Use [`Argon2State::new`] or [`Pbkdf2State::new`] to check them first,
+    /// or [`try_pw_wrap_with_settings_and_rng`](Self::try_pw_wrap_with_settings_and_rng).
     pub fn pw_wrap_with_settings_and_rng(
         &self,
         password: &[u8],
         settings: V::KdfState,
         rng: &mut (impl RngCore + CryptoRng),
     ) -> PwWrappedKey<V, K> {
+        self.try_pw_wrap_with_settings_and_rng(password, settings, rng)
+            .expect("KDF settings should be valid")
+    }
+
+    /// Password wrapped keys, with settings that may be invalid
+    ///
+    /// * Use the settings to configure how strong the derived key should be
+    /// * Use the OS RNG to determine a random salt
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the settings are invalid, such as settings built
+    /// from their fields rather than with [`Argon2State::new`] or [`Pbkdf2State::new`].
+    ///
+    /// ```
+    /// use rusty_paserk::{Argon2State, Key, Local, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    ///
+    /// // argon2 needs at least 8 KiB of memory per lane
+    /// let settings = Argon2State { mem: 8 * 1024, time: 1, para: 2 };
+    /// assert!(key.try_pw_wrap_with_settings(b"hunter2", settings).is_err());
+    ///
+    /// let settings = Argon2State { mem: 16 * 1024, time: 1, para: 2 };
+    /// let wrapped = key.try_pw_wrap_with_settings(b"hunter2", settings).unwrap();
+    /// assert_eq!(wrapped.unwrap_key(b"hunter2").unwrap(), key);
+    /// ```
+    pub fn try_pw_wrap_with_settings(
+        &self,
+        password: &[u8],
+        settings: V::KdfState,
+    ) -> Result<PwWrappedKey<V, K>, PasetoError> {
+        self.try_pw_wrap_with_settings_and_rng(password, settings, &mut OsRng)
+    }
+
+    /// Password wrapped keys, with settings that may be invalid
+    ///
+    /// * Use the settings to configure how strong the derived key should be
+    /// * Use the RNG source to determine a random salt
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the settings are invalid.
+    pub fn try_pw_wrap_with_settings_and_rng(
+        &self,
+        password: &[u8],
+        settings: V::KdfState,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<PwWrappedKey<V, K>, PasetoError> {
         let mut salt = V::Salt::default();
         rng.fill_bytes(&mut salt);
 
-        let k = V::kdf(password, &salt, &settings);
+        let k = V::kdf(password, &salt, &settings)?;
 
         let ek = <V::KeyHash as Digest>::new()
             .chain_update([0xff])
@@ -163,20 +252,58 @@ impl<V: PwVersion, K: PwWrapType<V>> Key<V, K> {
             .finalize()
             .into_bytes();
 
-        PwWrappedKey {
+        Ok(PwWrappedKey {
             salt,
             state: settings,
             nonce: n,
             edk,
             tag,
-        }
+        })
     }
 }
 
 impl<V: PwVersion, K: PwWrapType<V>> PwWrappedKey<V, K> {
+    /// The algorithm used to password wrap keys for this version
+    pub const ALGORITHM: Algorithm = V::ALGORITHM;
+
     /// Unwrap the password wrapped key
-    pub fn unwrap_key(mut self, password: &[u8]) -> Result<Key<V, K>, PasetoError> {
-        let k = V::kdf(password, &self.salt, &self.state);
+    ///
+    /// The KDF settings are read from the wrapped key, so they are limited to
+    /// [`Argon2State::DEFAULT_LIMIT`] or [`Pbkdf2State::DEFAULT_LIMIT`] to stop untrusted keys
+    /// from using unbounded memory or time. Use [`unwrap_key_with_limit`](Self::unwrap_key_with_limit)
+    /// to choose the limit.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the KDF settings in the wrapped key are invalid or
+    /// over the limit.
+    pub fn unwrap_key(self, password: &[u8]) -> Result<Key<V, K>, PasetoError> {
+        self.unwrap_key_with_limit(password, &V::DEFAULT_LIMIT)
+    }
+
+    /// Unwrap the password wrapped key, if none of its KDF settings are above those in `limit`
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the KDF settings in the wrapped key are invalid or
+    /// over the limit.
+    ///
+    /// ```
+    /// use rusty_paserk::{Argon2State, Key, Local, PwWrappedKey, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    /// let settings = Argon2State::new(16 * 1024 * 1024, 3, 1).unwrap();
+    /// let wrapped = key.pw_wrap_with_settings(b"hunter2", settings).to_string();
+    ///
+    /// let limit = Argon2State::new(8 * 1024 * 1024, 3, 1).unwrap();
+    /// let wrapped: PwWrappedKey<V4, Local> = wrapped.parse().unwrap();
+    /// assert!(wrapped.unwrap_key_with_limit(b"hunter2", &limit).is_err());
+    /// ```
+    pub fn unwrap_key_with_limit(
+        mut self,
+        password: &[u8],
+        limit: &V::KdfState,
+    ) -> Result<Key<V, K>, PasetoError> {
+        if !V::within_limit(&self.state, limit) {
+            return Err(PasetoError::InvalidKey);
+        }
+        let k = V::kdf(password, &self.salt, &self.state)?;
 
         let ak = <V::KeyHash as Digest>::new()
             .chain_update([0xfe])
@@ -213,11 +340,21 @@ impl<V: PwVersion, K: PwWrapType<V>> PwWrappedKey<V, K> {
     /// Return the password KDF settings that were used to encrypt the key.
     /// This is important to check prevent DOS attacks otherwise an attacked can
     /// send a key with arbitrary large memory and iteration counts.
+    /// [`unwrap_key`](Self::unwrap_key) only checks them against a generous default limit.
     pub fn settings(&self) -> &V::KdfState {
         &self.state
     }
 }
 
+impl<V: PwVersion, K: PwWrapType<V>> super::FromPaserkBytes for PwWrappedKey<V, K> {}
+impl<V: PwVersion, K: PwWrapType<V>> super::PaserkLen for PwWrappedKey<V, K> {
+    fn paserk_len(&self) -> usize {
+        use generic_array::typenum::Unsigned;
+        let len = <<K::SaltStateIvEdkTag as GenericSequence<u8>>::Length as Unsigned>::USIZE;
+        V::KEY_HEADER.len() + K::WRAP_HEADER.len() + crate::encoding::encoded_len(len)
+    }
+}
+
 impl<V: PwVersion, K: PwWrapType<V>> FromStr for PwWrappedKey<V, K> {
     type Err = PasetoError;
 
@@ -247,6 +384,30 @@ impl<V: PwVersion, K: PwWrapType<V>> FromStr for PwWrappedKey<V, K> {
     }
 }
 
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "arbitrary")]
+impl<'a, V: PwVersion, K: PwWrapType<V>> arbitrary::Arbitrary<'a> for PwWrappedKey<V, K>
+where
+    V::KdfState: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let total: K::SaltStateIvEdkTag = crate::arbitrary_bytes(u)?;
+
+        let (salt_state_nonce_edk, tag) = total.split();
+        let (salt_state_nonce, edk) = salt_state_nonce_edk.split();
+        let (salt_state, nonce) = salt_state_nonce.split();
+        let (salt, _state) = salt_state.split();
+
+        Ok(Self {
+            salt,
+            state: u.arbitrary()?,
+            nonce,
+            edk,
+            tag,
+        })
+    }
+}
+
 impl<V: PwVersion, K: PwWrapType<V>> fmt::Display for PwWrappedKey<V, K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(V::KEY_HEADER)?;
@@ -266,6 +427,7 @@ impl<V: PwVersion, K: PwWrapType<V>> fmt::Display for PwWrappedKey<V, K> {
 
 #[cfg(feature = "v3")]
 /// PBKDF2 parameters for V3 password wrapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Pbkdf2State {
     /// Defaults to 100,000 according to the PASERK PBKW specifications.
     /// Password hashing recommends 600,000 iterations, but we're not directly storing the output
@@ -273,6 +435,27 @@ pub struct Pbkdf2State {
     pub iterations: u32,
 }
 
+#[cfg(feature = "v3")]
+impl Pbkdf2State {
+    /// PBKDF2 parameters, checking that they are valid.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if `iterations` is 0.
+    pub fn new(iterations: u32) -> Result<Self, PasetoError> {
+        if iterations == 0 {
+            return Err(PasetoError::InvalidKey);
+        }
+        Ok(Self { iterations })
+    }
+}
+
+#[cfg(feature = "v3")]
+impl Pbkdf2State {
+    /// The most iterations that [`PwWrappedKey::unwrap_key`] accepts: 10,000,000
+    pub const DEFAULT_LIMIT: Self = Self {
+        iterations: 10_000_000,
+    };
+}
+
 #[cfg(feature = "v3")]
 impl Default for Pbkdf2State {
     fn default() -> Self {
@@ -282,8 +465,41 @@ impl Default for Pbkdf2State {
     }
 }
 
+/// Small parameters, so that unwrapping arbitrary keys stays fast
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(all(feature = "v3", feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for Pbkdf2State {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            iterations: u.int_in_range(1..=1000)?,
+        })
+    }
+}
+
 #[cfg(feature = "v4")]
 /// Argon2 parameters for V4 password wrapping
+///
+/// The parameters are stored in the wrapped key. [`PwWrappedKey::unwrap_key`] only unwraps keys
+/// with settings up to [`Argon2State::DEFAULT_LIMIT`]. Use
+/// [`PwWrappedKey::unwrap_key_with_limit`] to unwrap keys with more expensive settings, or to
+/// set a lower limit for keys from untrusted sources.
+///
+/// ```
+/// use rusty_paserk::{Argon2State, Key, Local, PwWrappedKey, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let settings = Argon2State::new(16 * 1024 * 1024, 3, 2).unwrap();
+///
+/// let wrapped = key.pw_wrap_with_settings(b"hunter2", settings).to_string();
+///
+/// let wrapped: PwWrappedKey<V4, Local> = wrapped.parse().unwrap();
+/// assert_eq!(*wrapped.settings(), settings);
+/// assert_eq!(wrapped.unwrap_key(b"hunter2").unwrap(), key);
+///
+/// // argon2 needs at least 8 KiB of memory per lane
+/// assert!(Argon2State::new(8 * 1024, 1, 2).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Argon2State {
     /// Defaults to 64 MiB
     pub mem: u32,
@@ -293,6 +509,38 @@ pub struct Argon2State {
     pub para: u32,
 }
 
+#[cfg(feature = "v4")]
+impl Argon2State {
+    /// Argon2 parameters, checking that they are valid.
+    ///
+    /// `mem` is in bytes, and must be a multiple of 1 KiB.
+    /// Returns [`PasetoError::InvalidKey`] if argon2 would reject the parameters.
+    pub fn new(mem: u32, time: u32, para: u32) -> Result<Self, PasetoError> {
+        let state = Self { mem, time, para };
+        if !mem.is_multiple_of(1024) {
+            return Err(PasetoError::InvalidKey);
+        }
+        state.params()?;
+        Ok(state)
+    }
+
+    fn params(&self) -> Result<argon2::Params, PasetoError> {
+        argon2::Params::new(self.mem / 1024, self.time, self.para, Some(32))
+            .map_err(|_| PasetoError::InvalidKey)
+    }
+}
+
+#[cfg(feature = "v4")]
+impl Argon2State {
+    /// The most expensive settings that [`PwWrappedKey::unwrap_key`] accepts:
+    /// 1 GiB of memory, 16 iterations and 16 lanes
+    pub const DEFAULT_LIMIT: Self = Self {
+        mem: 0x4000_0000,
+        time: 16,
+        para: 16,
+    };
+}
+
 #[cfg(feature = "v4")]
 impl Default for Argon2State {
     fn default() -> Self {
@@ -305,11 +553,100 @@ impl Default for Argon2State {
     }
 }
 
+/// Small parameters, so that unwrapping arbitrary keys stays fast
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(all(feature = "v4", feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for Argon2State {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let para = u.int_in_range(1..=4)?;
+        // argon2 needs at least 8 KiB per lane
+        let mem_kib: u32 = u.int_in_range(8 * para..=256)?;
+        Ok(Self {
+            mem: mem_kib * 1024,
+            time: u.int_in_range(1..=4)?,
+            para,
+        })
+    }
+}
+
+/// Options for [`Key::pw_wrap_with_options`], covering the KDF of every version.
+///
+/// Versions only use their own options: V2 and V4 use the Argon2 `memory`, `iterations` and
+/// `parallelism`, and V3 uses `pbkdf2_iterations`. The defaults are the PASERK recommendations.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, PwWrapOptions, V4};
+///
+/// let options = PwWrapOptions::new()
+///     .memory(32 * 1024 * 1024)
+///     .iterations(3)
+///     .parallelism(2);
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let wrapped = key.pw_wrap_with_options(b"hunter2", &options).unwrap();
+/// assert_eq!(wrapped.settings().para, 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PwWrapOptions {
+    memory: u32,
+    iterations: u32,
+    parallelism: u32,
+    pbkdf2_iterations: u32,
+}
+
+impl PwWrapOptions {
+    /// The default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Argon2 memory, in bytes. Must be a multiple of 1 KiB. Defaults to 64 MiB
+    pub fn memory(mut self, bytes: u32) -> Self {
+        self.memory = bytes;
+        self
+    }
+
+    /// Argon2 iterations. Defaults to 2
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Argon2 parallelism. Defaults to 1
+    pub fn parallelism(mut self, lanes: u32) -> Self {
+        self.parallelism = lanes;
+        self
+    }
+
+    /// PBKDF2 iterations. Defaults to 100,000
+    pub fn pbkdf2_iterations(mut self, iterations: u32) -> Self {
+        self.pbkdf2_iterations = iterations;
+        self
+    }
+}
+
+impl Default for PwWrapOptions {
+    fn default() -> Self {
+        Self {
+            memory: 0x0400_0000,
+            iterations: 2,
+            parallelism: 1,
+            pbkdf2_iterations: 100_000,
+        }
+    }
+}
+
 /// Version info for configuring password wrapping
 pub trait PwVersion: Version {
+    /// The algorithm used to password wrap keys
+    const ALGORITHM: Algorithm;
+
     /// The settings that the KDF function uses
     type KdfState: Default;
 
+    /// The most expensive settings that [`PwWrappedKey::unwrap_key`] accepts
+    const DEFAULT_LIMIT: Self::KdfState;
+
     #[doc(hidden)]
     type KdfStateLen: ArrayLength<u8>;
     #[doc(hidden)]
@@ -351,7 +688,17 @@ pub trait PwVersion: Version {
     >;
 
     #[doc(hidden)]
-    fn kdf(pw: &[u8], salt: &Self::Salt, state: &Self::KdfState) -> GenericArray<u8, U32>;
+    fn kdf(
+        pw: &[u8],
+        salt: &Self::Salt,
+        state: &Self::KdfState,
+    ) -> Result<GenericArray<u8, U32>, PasetoError>;
+
+    #[doc(hidden)]
+    fn settings(options: &PwWrapOptions) -> Result<Self::KdfState, PasetoError>;
+
+    #[doc(hidden)]
+    fn within_limit(state: &Self::KdfState, limit: &Self::KdfState) -> bool;
 
     #[doc(hidden)]
     fn split_ek(ek: digest::Output<Self::KeyHash>) -> cipher::Key<Self::Cipher>;
@@ -413,6 +760,8 @@ pub trait PwWrapType<V: PwVersion>: KeyType<V> + PwType {
 
 #[cfg(feature = "v3")]
 impl PwVersion for V3 {
+    const ALGORITHM: Algorithm = Algorithm::PwPbkdf2;
+
     type Cipher = ctr::Ctr64BE<aes::Aes256>;
     type KeyHash = sha2::Sha384;
     type TagMac = hmac::Hmac<sha2::Sha384>;
@@ -420,12 +769,30 @@ impl PwVersion for V3 {
     type KdfStateLen = generic_array::typenum::U4;
     type KdfState = Pbkdf2State;
 
+    const DEFAULT_LIMIT: Pbkdf2State = Pbkdf2State::DEFAULT_LIMIT;
+
     type Salt = GenericArray<u8, generic_array::typenum::U32>;
     type SaltState = GenericArray<u8, generic_array::typenum::U36>;
     type SaltStateIv = GenericArray<u8, generic_array::typenum::U52>;
 
-    fn kdf(pw: &[u8], salt: &Self::Salt, state: &Self::KdfState) -> GenericArray<u8, U32> {
-        pbkdf2::pbkdf2_hmac_array::<sha2::Sha384, 32>(pw, salt.as_slice(), state.iterations).into()
+    fn kdf(
+        pw: &[u8],
+        salt: &Self::Salt,
+        state: &Self::KdfState,
+    ) -> Result<GenericArray<u8, U32>, PasetoError> {
+        let state = Pbkdf2State::new(state.iterations)?;
+        Ok(
+            pbkdf2::pbkdf2_hmac_array::<sha2::Sha384, 32>(pw, salt.as_slice(), state.iterations)
+                .into(),
+        )
+    }
+
+    fn settings(options: &PwWrapOptions) -> Result<Self::KdfState, PasetoError> {
+        Pbkdf2State::new(options.pbkdf2_iterations)
+    }
+
+    fn within_limit(state: &Self::KdfState, limit: &Self::KdfState) -> bool {
+        state.iterations <= limit.iterations
     }
 
     fn split_ek(ek: digest::Output<Self::KeyHash>) -> cipher::Key<Self::Cipher> {
@@ -443,61 +810,101 @@ impl PwVersion for V3 {
 }
 
 #[cfg(feature = "v4")]
-impl PwVersion for V4 {
-    type Cipher = chacha20::XChaCha20;
-    type KeyHash = blake2::Blake2b<U32>;
-    type TagMac = blake2::Blake2bMac<U32>;
-
-    type KdfStateLen = generic_array::typenum::U16;
-    type KdfState = Argon2State;
-
-    type Salt = GenericArray<u8, generic_array::typenum::U16>;
-    type SaltState = GenericArray<u8, generic_array::typenum::U32>;
-    type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
-
-    fn kdf(pw: &[u8], salt: &Self::Salt, state: &Self::KdfState) -> GenericArray<u8, U32> {
-        let mut out = GenericArray::<u8, U32>::default();
-        argon2::Argon2::new(
-            argon2::Algorithm::Argon2id,
-            argon2::Version::V0x13,
-            argon2::Params::new(state.mem / 1024, state.time, state.para, Some(32)).unwrap(),
-        )
-        .hash_password_into(pw, salt.as_slice(), &mut out)
-        .unwrap();
-        out
-    }
+macro_rules! impl_pw_argon2 {
+    ($v:ident) => {
+        impl PwVersion for $v {
+            const ALGORITHM: Algorithm = Algorithm::PwArgon2id;
+
+            type Cipher = chacha20::XChaCha20;
+            type KeyHash = blake2::Blake2b<U32>;
+            type TagMac = blake2::Blake2bMac<U32>;
+
+            type KdfStateLen = generic_array::typenum::U16;
+            type KdfState = Argon2State;
+
+            const DEFAULT_LIMIT: Argon2State = Argon2State::DEFAULT_LIMIT;
+
+            type Salt = GenericArray<u8, generic_array::typenum::U16>;
+            type SaltState = GenericArray<u8, generic_array::typenum::U32>;
+            type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
+
+            fn kdf(
+                pw: &[u8],
+                salt: &Self::Salt,
+                state: &Self::KdfState,
+            ) -> Result<GenericArray<u8, U32>, PasetoError> {
+                let mut out = GenericArray::<u8, U32>::default();
+                argon2::Argon2::new(
+                    argon2::Algorithm::Argon2id,
+                    argon2::Version::V0x13,
+                    state.params()?,
+                )
+                .hash_password_into(pw, salt.as_slice(), &mut out)
+                .map_err(|_| PasetoError::InvalidKey)?;
+                Ok(out)
+            }
 
-    fn split_ek(ek: digest::Output<Self::KeyHash>) -> cipher::Key<Self::Cipher> {
-        ek
-    }
+            fn settings(options: &PwWrapOptions) -> Result<Self::KdfState, PasetoError> {
+                Argon2State::new(options.memory, options.iterations, options.parallelism)
+            }
 
-    fn encode_state(s: &Self::KdfState) -> GenericArray<u8, Self::KdfStateLen> {
-        GenericArray::<u8, generic_array::typenum::U4>::default()
-            .concat(s.mem.to_be_bytes().into())
-            .concat(s.time.to_be_bytes().into())
-            .concat(s.para.to_be_bytes().into())
-    }
-    fn decode_state(s: GenericArray<u8, Self::KdfStateLen>) -> Self::KdfState {
-        let (mem1, b) = s.split();
-        let (mem2, b) = b.split();
-        let (time, para) = b.split();
+            fn within_limit(state: &Self::KdfState, limit: &Self::KdfState) -> bool {
+                state.mem <= limit.mem && state.time <= limit.time && state.para <= limit.para
+            }
 
-        let _mem1: GenericArray<u8, generic_array::typenum::U4> = mem1;
-        let mem = u32::from_be_bytes(mem2.into());
-        let time = u32::from_be_bytes(time.into());
-        let para = u32::from_be_bytes(para.into());
+            fn split_ek(ek: digest::Output<Self::KeyHash>) -> cipher::Key<Self::Cipher> {
+                ek
+            }
 
-        Argon2State { mem, time, para }
-    }
+            fn encode_state(s: &Self::KdfState) -> GenericArray<u8, Self::KdfStateLen> {
+                GenericArray::<u8, generic_array::typenum::U4>::default()
+                    .concat(s.mem.to_be_bytes().into())
+                    .concat(s.time.to_be_bytes().into())
+                    .concat(s.para.to_be_bytes().into())
+            }
+            fn decode_state(s: GenericArray<u8, Self::KdfStateLen>) -> Self::KdfState {
+                let (mem1, b) = s.split();
+                let (mem2, b) = b.split();
+                let (time, para) = b.split();
+
+                let _mem1: GenericArray<u8, generic_array::typenum::U4> = mem1;
+                let mem = u32::from_be_bytes(mem2.into());
+                let time = u32::from_be_bytes(time.into());
+                let para = u32::from_be_bytes(para.into());
+
+                Argon2State { mem, time, para }
+            }
+        }
+    };
 }
 
 #[cfg(feature = "v4")]
-impl PwWrapType<V4> for Local {
-    type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
-    type SaltStateIvEdk = GenericArray<u8, generic_array::typenum::U88>;
-    type SaltStateIvEdkTag = GenericArray<u8, generic_array::typenum::U120>;
+impl_pw_argon2!(V4);
+#[cfg(feature = "v2")]
+impl_pw_argon2!(V2);
+
+#[cfg(feature = "v4")]
+macro_rules! impl_pw_argon2_wrap_types {
+    ($v:ident) => {
+        impl PwWrapType<$v> for Local {
+            type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
+            type SaltStateIvEdk = GenericArray<u8, generic_array::typenum::U88>;
+            type SaltStateIvEdkTag = GenericArray<u8, generic_array::typenum::U120>;
+        }
+
+        impl PwWrapType<$v> for Secret {
+            type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
+            type SaltStateIvEdk = GenericArray<u8, generic_array::typenum::U120>;
+            type SaltStateIvEdkTag = GenericArray<u8, generic_array::typenum::U152>;
+        }
+    };
 }
 
+#[cfg(feature = "v4")]
+impl_pw_argon2_wrap_types!(V4);
+#[cfg(feature = "v2")]
+impl_pw_argon2_wrap_types!(V2);
+
 #[cfg(feature = "v3")]
 impl PwWrapType<V3> for Local {
     type SaltStateIv = GenericArray<u8, generic_array::typenum::U52>;
@@ -505,13 +912,6 @@ impl PwWrapType<V3> for Local {
     type SaltStateIvEdkTag = GenericArray<u8, generic_array::typenum::U132>;
 }
 
-#[cfg(feature = "v4")]
-impl PwWrapType<V4> for Secret {
-    type SaltStateIv = GenericArray<u8, generic_array::typenum::U56>;
-    type SaltStateIvEdk = GenericArray<u8, generic_array::typenum::U120>;
-    type SaltStateIvEdkTag = GenericArray<u8, generic_array::typenum::U152>;
-}
-
 #[cfg(feature = "v3")]
 impl PwWrapType<V3> for Secret {
     type SaltStateIv = GenericArray<u8, generic_array::typenum::U52>;
@@ -15,12 +15,35 @@ use rand::{rngs::OsRng, CryptoRng, RngCore};
 use rusty_paseto::core::PasetoError;
 use subtle::ConstantTimeEq;
 
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
 use rusty_paseto::core::V4;
 
-use crate::{read_b64, write_b64, Key, Local, Public, Secret, Version};
+use crate::{read_b64, write_b64, Algorithm, Key, Local, Public, Secret, Version};
+
+mod compact;
+mod envelope;
+mod escrow;
+#[cfg(feature = "pkcs11")]
+mod hardware;
+mod multi;
+mod reseal;
+#[cfg(feature = "threshold")]
+mod threshold;
+mod timestamped;
+pub use compact::CompactSealedKey;
+pub use envelope::SealedKeyEnvelope;
+pub use escrow::EscrowPolicy;
+#[cfg(feature = "pkcs11")]
+pub use hardware::HardwareUnsealer;
+pub use multi::MultiSealedKey;
+pub use reseal::ResealEvent;
+#[cfg(feature = "threshold")]
+pub use threshold::{KeyShare, PartialUnseal};
+pub use timestamped::TimestampedSealedKey;
 
 /// A local key encrypted with an asymmetric wrapping key.
 ///
@@ -46,7 +69,24 @@ pub struct SealedKey<V: SealedVersion> {
     encrypted_data_key: GenericArray<u8, V::Local>,
 }
 
+impl<V: SealedVersion> Clone for SealedKey<V> {
+    fn clone(&self) -> Self {
+        Self {
+            tag: self.tag.clone(),
+            ephemeral_public_key: self.ephemeral_public_key.clone(),
+            encrypted_data_key: self.encrypted_data_key.clone(),
+        }
+    }
+}
+
 impl<V> super::SafeForFooter for SealedKey<V> where V: SealedVersion {}
+impl<V: SealedVersion> super::FromPaserkBytes for SealedKey<V> {}
+impl<V: SealedVersion> super::PaserkLen for SealedKey<V> {
+    fn paserk_len(&self) -> usize {
+        use generic_array::typenum::Unsigned;
+        V::KEY_HEADER.len() + "seal.".len() + crate::encoding::encoded_len(V::TotalLen::USIZE)
+    }
+}
 
 impl<V: SealedVersion> Key<V, Local> {
     /// A local key encrypted with an asymmetric wrapping key.
@@ -84,6 +124,9 @@ impl<V: SealedVersion> Key<V, Local> {
 }
 
 impl<V: SealedVersion> SealedKey<V> {
+    /// The algorithm used to seal keys for this version
+    pub const ALGORITHM: Algorithm = V::ALGORITHM;
+
     /// Unseal an encrypted local key.
     pub fn unseal(self, unsealing_key: &Key<V, Secret>) -> Result<Key<V, Local>, PasetoError> {
         V::unseal(self, unsealing_key)
@@ -92,6 +135,9 @@ impl<V: SealedVersion> SealedKey<V> {
 
 /// Version info for configuring key sealing
 pub trait SealedVersion: Version + Sized {
+    /// The algorithm used to seal keys
+    const ALGORITHM: Algorithm;
+
     #[doc(hidden)]
     type TagLen: ArrayLength<u8>;
     #[doc(hidden)]
@@ -120,6 +166,8 @@ pub trait SealedVersion: Version + Sized {
 
 #[cfg(feature = "v3")]
 impl SealedVersion for V3 {
+    const ALGORITHM: Algorithm = Algorithm::SealP384;
+
     type TagLen = generic_array::typenum::U48;
     type EpkLen = generic_array::typenum::U49;
 
@@ -198,7 +246,7 @@ impl SealedVersion for V3 {
     }
 
     fn unseal(
-        mut sealed_key: SealedKey<Self>,
+        sealed_key: SealedKey<Self>,
         unsealing_key: &Key<Self, Secret>,
     ) -> Result<Key<Self, Local>, PasetoError> {
         use p384::ecdh::diffie_hellman;
@@ -208,210 +256,251 @@ impl SealedVersion for V3 {
 
         let pk: EncodedPoint = sk.public_key().into();
         let pk = pk.compress();
-        let pk = pk.as_bytes();
 
         let epk = PublicKey::from_sec1_bytes(sealed_key.ephemeral_public_key.as_slice()).unwrap();
 
         let xk = diffie_hellman(sk.to_nonzero_scalar(), epk.as_affine());
 
-        let ak = sha2::Sha384::new()
-            .chain_update([0x02])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.raw_secret_bytes())
-            .chain_update(sealed_key.ephemeral_public_key)
-            .chain_update(pk)
-            .finalize();
-
-        let tag = hmac::Hmac::<sha2::Sha384>::new_from_slice(&ak)
-            .unwrap()
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(sealed_key.ephemeral_public_key)
-            .chain_update(sealed_key.encrypted_data_key)
-            .finalize()
-            .into_bytes();
-
-        // step 6: Compare t2 with t, using a constant-time compare function. If it does not match, abort.
-        if sealed_key.tag.ct_ne(&tag).into() {
-            return Err(PasetoError::InvalidSignature);
-        }
-
-        let (ek, n) = sha2::Sha384::new()
-            .chain_update([0x01])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.raw_secret_bytes())
-            .chain_update(sealed_key.ephemeral_public_key)
-            .chain_update(pk)
-            .finalize()
-            .split();
-
-        ctr::Ctr64BE::<aes::Aes256>::new(&ek, &n)
-            .apply_keystream(&mut sealed_key.encrypted_data_key);
-
-        Ok(Key {
-            key: sealed_key.encrypted_data_key,
-        })
+        unseal_p384(sealed_key, xk.raw_secret_bytes(), pk.as_bytes())
     }
 }
 
-#[cfg(feature = "v4")]
-impl SealedVersion for V4 {
-    type TagLen = generic_array::typenum::U32;
-    type EpkLen = generic_array::typenum::U32;
-
-    type TotalLen = generic_array::typenum::U96;
-    fn split_total(total: GenericArray<u8, Self::TotalLen>) -> SealedKey<Self> {
-        let (tag, rest) = total.split();
-        let (ephemeral_public_key, encrypted_data_key) = rest.split();
-        SealedKey {
-            tag,
-            ephemeral_public_key,
-            encrypted_data_key,
-        }
-    }
-    fn join_total(sealed: &SealedKey<Self>) -> GenericArray<u8, Self::TotalLen> {
-        sealed
-            .tag
-            .concat(sealed.ephemeral_public_key)
-            .concat(sealed.encrypted_data_key)
+/// Finish unsealing a V3 key, given the shared secret `xk` with the ephemeral public key and the
+/// compressed public key `pk` of the recipient.
+#[cfg(feature = "v3")]
+fn unseal_p384(
+    mut sealed_key: SealedKey<V3>,
+    xk: &[u8],
+    pk: &[u8],
+) -> Result<Key<V3, Local>, PasetoError> {
+    let ak = sha2::Sha384::new()
+        .chain_update([0x02])
+        .chain_update(V3::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(xk)
+        .chain_update(sealed_key.ephemeral_public_key)
+        .chain_update(pk)
+        .finalize();
+
+    let tag = hmac::Hmac::<sha2::Sha384>::new_from_slice(&ak)
+        .unwrap()
+        .chain_update(V3::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(sealed_key.ephemeral_public_key)
+        .chain_update(sealed_key.encrypted_data_key)
+        .finalize()
+        .into_bytes();
+
+    // step 6: Compare t2 with t, using a constant-time compare function. If it does not match, abort.
+    if sealed_key.tag.ct_ne(&tag).into() {
+        return Err(PasetoError::InvalidSignature);
     }
 
-    fn seal(
-        plaintext_key: &Key<Self, Local>,
-        sealing_key: &Key<Self, Public>,
-        rng: &mut (impl RngCore + CryptoRng),
-    ) -> SealedKey<Self> {
-        use curve25519_dalek::{
-            edwards::{CompressedEdwardsY, EdwardsPoint},
-            scalar::{clamp_integer, Scalar},
-        };
-
-        // Given a plaintext data key (pdk), and an Ed25519 public key (pk).
-        let pk = CompressedEdwardsY(sealing_key.key.into());
-
-        // step 1: Calculate the birationally-equivalent X25519 public key (xpk) from pk.
-        let xpk = pk.decompress().unwrap().to_montgomery();
-
-        let esk = Scalar::from_bytes_mod_order(clamp_integer({
-            let mut esk = [0; 32];
-            rng.fill_bytes(&mut esk);
-            esk
-        }));
-        let epk = EdwardsPoint::mul_base(&esk).to_montgomery();
-
-        // diffie hellman exchange
-        let xk = esk * xpk;
-
-        let ek = blake2::Blake2b::new()
-            .chain_update([0x01])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.as_bytes())
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
-
-        let ak = blake2::Blake2b::<generic_array::typenum::U32>::new()
-            .chain_update([0x02])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.as_bytes())
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
-
-        let n = blake2::Blake2b::new()
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
-
-        let mut edk = GenericArray::<u8, <Self as Version>::Local>::default();
-        chacha20::XChaCha20::new(&ek, &n)
-            .apply_keystream_inout(InOutBuf::new(plaintext_key.as_ref(), &mut edk).unwrap());
-
-        let tag = blake2::Blake2bMac::new_from_slice(&ak)
-            .unwrap()
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(epk.as_bytes())
-            .chain_update(edk)
-            .finalize()
-            .into_bytes();
-
-        SealedKey {
-            tag,
-            ephemeral_public_key: epk.to_bytes().into(),
-            encrypted_data_key: edk,
-        }
-    }
+    let (ek, n) = sha2::Sha384::new()
+        .chain_update([0x01])
+        .chain_update(V3::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(xk)
+        .chain_update(sealed_key.ephemeral_public_key)
+        .chain_update(pk)
+        .finalize()
+        .split();
+
+    ctr::Ctr64BE::<aes::Aes256>::new(&ek, &n).apply_keystream(&mut sealed_key.encrypted_data_key);
+
+    Ok(Key {
+        key: sealed_key.encrypted_data_key,
+    })
+}
 
-    fn unseal(
-        mut sealed_key: SealedKey<Self>,
-        unsealing_key: &Key<Self, Secret>,
-    ) -> Result<Key<Self, Local>, PasetoError> {
-        use curve25519_dalek::edwards::CompressedEdwardsY;
-        use ed25519_dalek::hazmat::ExpandedSecretKey;
+#[cfg(feature = "v4")]
+macro_rules! impl_x25519_seal {
+    ($v:ident) => {
+        impl SealedVersion for $v {
+            const ALGORITHM: Algorithm = Algorithm::SealX25519;
+
+            type TagLen = generic_array::typenum::U32;
+            type EpkLen = generic_array::typenum::U32;
+
+            type TotalLen = generic_array::typenum::U96;
+            fn split_total(total: GenericArray<u8, Self::TotalLen>) -> SealedKey<Self> {
+                let (tag, rest) = total.split();
+                let (ephemeral_public_key, encrypted_data_key) = rest.split();
+                SealedKey {
+                    tag,
+                    ephemeral_public_key,
+                    encrypted_data_key,
+                }
+            }
+            fn join_total(sealed: &SealedKey<Self>) -> GenericArray<u8, Self::TotalLen> {
+                sealed
+                    .tag
+                    .concat(sealed.ephemeral_public_key)
+                    .concat(sealed.encrypted_data_key)
+            }
 
-        let epk: [u8; 32] = sealed_key.ephemeral_public_key.into();
-        let epk = curve25519_dalek::MontgomeryPoint(epk);
+            fn seal(
+                plaintext_key: &Key<Self, Local>,
+                sealing_key: &Key<Self, Public>,
+                rng: &mut (impl RngCore + CryptoRng),
+            ) -> SealedKey<Self> {
+                use curve25519_dalek::{
+                    edwards::{CompressedEdwardsY, EdwardsPoint},
+                    scalar::{clamp_integer, Scalar},
+                };
+
+                // Given a plaintext data key (pdk), and an Ed25519 public key (pk).
+                let pk = CompressedEdwardsY(sealing_key.key.into());
+
+                // step 1: Calculate the birationally-equivalent X25519 public key (xpk) from pk.
+                let xpk = pk.decompress().unwrap().to_montgomery();
+
+                let esk = Scalar::from_bytes_mod_order(clamp_integer({
+                    let mut esk = [0; 32];
+                    rng.fill_bytes(&mut esk);
+                    esk
+                }));
+                let epk = EdwardsPoint::mul_base(&esk).to_montgomery();
+
+                // diffie hellman exchange
+                let xk = esk * xpk;
+
+                let ek = blake2::Blake2b::new()
+                    .chain_update([0x01])
+                    .chain_update(Self::KEY_HEADER)
+                    .chain_update("seal.")
+                    .chain_update(xk.as_bytes())
+                    .chain_update(epk.as_bytes())
+                    .chain_update(xpk.as_bytes())
+                    .finalize();
+
+                let ak = blake2::Blake2b::<generic_array::typenum::U32>::new()
+                    .chain_update([0x02])
+                    .chain_update(Self::KEY_HEADER)
+                    .chain_update("seal.")
+                    .chain_update(xk.as_bytes())
+                    .chain_update(epk.as_bytes())
+                    .chain_update(xpk.as_bytes())
+                    .finalize();
+
+                let n = blake2::Blake2b::new()
+                    .chain_update(epk.as_bytes())
+                    .chain_update(xpk.as_bytes())
+                    .finalize();
+
+                let mut edk = GenericArray::<u8, <Self as Version>::Local>::default();
+                chacha20::XChaCha20::new(&ek, &n).apply_keystream_inout(
+                    InOutBuf::new(plaintext_key.as_ref(), &mut edk).unwrap(),
+                );
+
+                let tag = blake2::Blake2bMac::new_from_slice(&ak)
+                    .unwrap()
+                    .chain_update(Self::KEY_HEADER)
+                    .chain_update("seal.")
+                    .chain_update(epk.as_bytes())
+                    .chain_update(edk)
+                    .finalize()
+                    .into_bytes();
+
+                SealedKey {
+                    tag,
+                    ephemeral_public_key: epk.to_bytes().into(),
+                    encrypted_data_key: edk,
+                }
+            }
 
-        // expand pk/sk pair from ed25519 to x25519
-        let (sk, pk) = unsealing_key.key.split();
-        let pk = CompressedEdwardsY(pk.into());
-        let xpk = pk.decompress().unwrap().to_montgomery();
+            fn unseal(
+                sealed_key: SealedKey<Self>,
+                unsealing_key: &Key<Self, Secret>,
+            ) -> Result<Key<Self, Local>, PasetoError> {
+                use curve25519_dalek::edwards::CompressedEdwardsY;
+                use ed25519_dalek::hazmat::ExpandedSecretKey;
 
-        let sk: ed25519_dalek::SecretKey = sk.into();
-        let xsk = ExpandedSecretKey::from(&sk);
+                let epk: [u8; 32] = sealed_key.ephemeral_public_key.into();
+                let epk = curve25519_dalek::MontgomeryPoint(epk);
 
-        // diffie hellman exchange
-        let xk = xsk.scalar * epk;
+                // expand pk/sk pair from ed25519 to x25519
+                let (sk, pk) = unsealing_key.key.split();
+                let pk = CompressedEdwardsY(pk.into());
+                let xpk = pk.decompress().unwrap().to_montgomery();
 
-        let ak = blake2::Blake2b::<generic_array::typenum::U32>::new()
-            .chain_update([0x02])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.as_bytes())
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
+                let sk: ed25519_dalek::SecretKey = sk.into();
+                let xsk = ExpandedSecretKey::from(&sk);
 
-        let t2 = blake2::Blake2bMac::<generic_array::typenum::U32>::new_from_slice(&ak)
-            .unwrap()
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(epk.as_bytes())
-            .chain_update(sealed_key.encrypted_data_key)
-            .finalize()
-            .into_bytes();
+                // diffie hellman exchange
+                let xk = xsk.scalar * epk;
 
-        // step 6: Compare t2 with t, using a constant-time compare function. If it does not match, abort.
-        if sealed_key.tag.ct_ne(&t2).into() {
-            return Err(PasetoError::InvalidSignature);
+                unseal_x25519(sealed_key, &xk, &xpk)
+            }
         }
+    };
+}
 
-        let ek = blake2::Blake2b::new()
-            .chain_update([0x01])
-            .chain_update(Self::KEY_HEADER)
-            .chain_update("seal.")
-            .chain_update(xk.as_bytes())
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
-
-        let n = blake2::Blake2b::new()
-            .chain_update(epk.as_bytes())
-            .chain_update(xpk.as_bytes())
-            .finalize();
-
-        chacha20::XChaCha20::new(&ek, &n).apply_keystream(&mut sealed_key.encrypted_data_key);
-        Ok(Key {
-            key: sealed_key.encrypted_data_key,
-        })
+/// Finish unsealing an X25519 sealed key, given the shared secret `xk` with the ephemeral public
+/// key and the X25519 public key `xpk` of the recipient.
+#[cfg(feature = "v4")]
+fn unseal_x25519<V>(
+    mut sealed_key: SealedKey<V>,
+    xk: &curve25519_dalek::MontgomeryPoint,
+    xpk: &curve25519_dalek::MontgomeryPoint,
+) -> Result<Key<V, Local>, PasetoError>
+where
+    V: SealedVersion<
+        TagLen = generic_array::typenum::U32,
+        EpkLen = generic_array::typenum::U32,
+        Local = generic_array::typenum::U32,
+    >,
+{
+    let epk = sealed_key.ephemeral_public_key;
+
+    let ak = blake2::Blake2b::<generic_array::typenum::U32>::new()
+        .chain_update([0x02])
+        .chain_update(V::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(xk.as_bytes())
+        .chain_update(epk)
+        .chain_update(xpk.as_bytes())
+        .finalize();
+
+    let t2 = blake2::Blake2bMac::<generic_array::typenum::U32>::new_from_slice(&ak)
+        .unwrap()
+        .chain_update(V::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(epk)
+        .chain_update(sealed_key.encrypted_data_key)
+        .finalize()
+        .into_bytes();
+
+    // step 6: Compare t2 with t, using a constant-time compare function. If it does not match, abort.
+    if sealed_key.tag.ct_ne(&t2).into() {
+        return Err(PasetoError::InvalidSignature);
     }
+
+    let ek = blake2::Blake2b::new()
+        .chain_update([0x01])
+        .chain_update(V::KEY_HEADER)
+        .chain_update("seal.")
+        .chain_update(xk.as_bytes())
+        .chain_update(epk)
+        .chain_update(xpk.as_bytes())
+        .finalize();
+
+    let n = blake2::Blake2b::new()
+        .chain_update(epk)
+        .chain_update(xpk.as_bytes())
+        .finalize();
+
+    chacha20::XChaCha20::new(&ek, &n).apply_keystream(&mut sealed_key.encrypted_data_key);
+    Ok(Key {
+        key: sealed_key.encrypted_data_key,
+    })
 }
 
+#[cfg(feature = "v4")]
+impl_x25519_seal!(V4);
+#[cfg(feature = "v2")]
+impl_x25519_seal!(V2);
+
 impl<V: SealedVersion> FromStr for SealedKey<V> {
     type Err = PasetoError;
 
@@ -436,13 +525,34 @@ impl<V: SealedVersion> fmt::Display for SealedKey<V> {
     }
 }
 
-#[cfg(any(test, fuzzing))]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "arbitrary")]
+impl<'a, V: SealedVersion> arbitrary::Arbitrary<'a> for SealedKey<V>
+where
+    Key<V, Public>: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let tag = crate::arbitrary_bytes(u)?;
+        let epk = Key::<V, Public>::arbitrary(u)?;
+        let ephemeral_public_key = GenericArray::from_exact_iter(epk.key.iter().copied())
+            .ok_or(arbitrary::Error::IncorrectFormat)?;
+        let encrypted_data_key = crate::arbitrary_bytes(u)?;
+        Ok(Self {
+            tag,
+            ephemeral_public_key,
+            encrypted_data_key,
+        })
+    }
+}
+
+#[cfg(any(test, fuzzing, feature = "testing"))]
 pub mod fuzz_tests {
     use crate::{fuzzing::FakeRng, Key, Local, Secret};
 
     #[cfg(feature = "v3")]
     #[derive(Debug)]
     #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    /// Seals `key` using `ephemeral` as the randomness, then checks it unseals to the same key
     pub struct V3SealInput {
         key: Key<rusty_paseto::core::V3, Local>,
         secret_key: Key<rusty_paseto::core::V3, Secret>,
@@ -451,6 +561,7 @@ pub mod fuzz_tests {
 
     #[cfg(feature = "v3")]
     impl V3SealInput {
+        /// Run the round trip, panicking on failure
         pub fn run(mut self) {
             let x: Option<p384::Scalar> =
                 p384::Scalar::from_bytes(&self.ephemeral.bytes.into()).into();
@@ -472,6 +583,7 @@ pub mod fuzz_tests {
     #[cfg(feature = "v4")]
     #[derive(Debug)]
     #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    /// Seals `key` using `ephemeral` as the randomness, then checks it unseals to the same key
     pub struct V4SealInput {
         key: Key<rusty_paseto::core::V4, Local>,
         secret_key: Key<rusty_paseto::core::V4, Secret>,
@@ -480,6 +592,7 @@ pub mod fuzz_tests {
 
     #[cfg(feature = "v4")]
     impl V4SealInput {
+        /// Run the round trip, panicking on failure
         pub fn run(mut self) {
             let sealed = self
                 .key
@@ -0,0 +1,116 @@
+use generic_array::{typenum::Unsigned, GenericArray};
+use rusty_paseto::core::PasetoError;
+
+use crate::{KeyId, Public, SealedKey, SealedKeyEnvelope};
+
+use super::SealedVersion;
+
+/// The length of a full key ID, in bytes
+const ID_LEN: usize = 33;
+
+/// A [`SealedKeyEnvelope`] in a compact binary form, for transports such as MQTT or LoRa where
+/// every byte counts.
+///
+/// It is encoded as one length byte, the first `n` bytes of the recipient ID, and the raw
+/// sealed key, with no headers and no base64. The version is not encoded, so both ends must
+/// agree on it.
+///
+/// # Collisions
+///
+/// A truncated ID only narrows down the recipient. With `n` bytes and `m` recipients, the
+/// chance that any two recipients share a prefix is about `m² / 2^(8n + 1)`, so 4 bytes is
+/// plenty for a few hundred recipients, and 1 byte is only a routing hint. A collision never
+/// unseals to the wrong key, since the sealed key is bound to the recipient public key and
+/// [`SealedKey::unseal`] fails with any other secret key. It only means trying more than one
+/// key. A prefix of 0 bytes leaves out the recipient entirely.
+///
+/// ```
+/// use rusty_paserk::{CompactSealedKey, Key, Local, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let public_key = secret_key.public_key();
+///
+/// let bytes = key.seal_for(&public_key).into_compact(4).to_bytes();
+/// assert_eq!(bytes.len(), 1 + 4 + 32 + 32 + 32);
+///
+/// let compact = CompactSealedKey::<V4>::from_bytes(&bytes).unwrap();
+/// assert!(compact.may_be_for(&public_key.to_id()));
+///
+/// let envelope = compact.into_envelope(public_key.to_id()).unwrap();
+/// let key2 = envelope.sealed.unseal(&secret_key).unwrap();
+/// assert_eq!(key, key2);
+/// ```
+pub struct CompactSealedKey<V: SealedVersion> {
+    recipient_prefix: Vec<u8>,
+    /// The sealed local key
+    pub sealed: SealedKey<V>,
+}
+
+impl<V: SealedVersion> SealedKeyEnvelope<V> {
+    /// Truncate the recipient ID to `prefix_len` bytes. See [`CompactSealedKey`]
+    ///
+    /// `prefix_len` is capped at the full ID length of 33 bytes.
+    pub fn into_compact(self, prefix_len: usize) -> CompactSealedKey<V> {
+        let prefix = &self.recipient.as_bytes()[..prefix_len.min(ID_LEN)];
+        CompactSealedKey {
+            recipient_prefix: prefix.to_vec(),
+            sealed: self.sealed,
+        }
+    }
+}
+
+impl<V: SealedVersion> CompactSealedKey<V> {
+    /// The truncated recipient ID
+    pub fn recipient_prefix(&self) -> &[u8] {
+        &self.recipient_prefix
+    }
+
+    /// Whether the truncated recipient ID matches `recipient`.
+    ///
+    /// This can be true for more than one recipient. See [Collisions](CompactSealedKey#collisions).
+    pub fn may_be_for(&self, recipient: &KeyId<V, Public>) -> bool {
+        crate::ct::ct_starts_with(recipient.as_bytes(), &self.recipient_prefix).into()
+    }
+
+    /// Restore the full envelope, given the full ID of the recipient.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the truncated ID does not match `recipient`.
+    pub fn into_envelope(
+        self,
+        recipient: KeyId<V, Public>,
+    ) -> Result<SealedKeyEnvelope<V>, PasetoError> {
+        if !self.may_be_for(&recipient) {
+            return Err(PasetoError::InvalidKey);
+        }
+        Ok(SealedKeyEnvelope {
+            recipient,
+            sealed: self.sealed,
+        })
+    }
+
+    /// Encode as the length byte, truncated recipient ID and raw sealed key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sealed = V::join_total(&self.sealed);
+        let mut out = Vec::with_capacity(1 + self.recipient_prefix.len() + sealed.len());
+        out.push(self.recipient_prefix.len() as u8);
+        out.extend_from_slice(&self.recipient_prefix);
+        out.extend_from_slice(&sealed);
+        out
+    }
+
+    /// Decode the output of [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PasetoError> {
+        let (&prefix_len, rest) = bytes.split_first().ok_or(PasetoError::IncorrectSize)?;
+        let prefix_len = prefix_len as usize;
+        if prefix_len > ID_LEN || rest.len() != prefix_len + V::TotalLen::USIZE {
+            return Err(PasetoError::IncorrectSize);
+        }
+        let (prefix, sealed) = rest.split_at(prefix_len);
+
+        Ok(Self {
+            recipient_prefix: prefix.to_vec(),
+            sealed: V::split_total(GenericArray::clone_from_slice(sealed)),
+        })
+    }
+}
@@ -0,0 +1,120 @@
+use std::{fmt, str::FromStr};
+
+use rusty_paseto::core::PasetoError;
+
+use crate::{FromPaserkBytes, Key, KeyId, Local, Public, SealedKey};
+
+use super::SealedVersion;
+
+/// A [`SealedKey`] together with the ID of the public key it was sealed to.
+///
+/// This lets services route sealed keys to the right unsealing backend without trial decryption.
+/// It is serialized as the recipient ID and the sealed key, separated by `:`.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, SealedKeyEnvelope, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let public_key = secret_key.public_key();
+///
+/// let envelope = key.seal_for(&public_key).to_string();
+/// // => "k4.pid.yMgldRRLHBLkhmcp8NG8yZrtyldbYoAjQWPv_Ma1rzRu:k4.seal.23KlrMHZLW4muL75Rnuqtaro9F16mqDNvmCbgDXi2IdNyWmjrbTVBEih1DhSI_5xp7b7mCHSFo1DMv-9GtZUSpyi4646XBxpbFShHjJihF_Af8maWsDqdzOof76ia0Cv"
+///
+/// let envelope: SealedKeyEnvelope<V4> = envelope.parse().unwrap();
+/// assert_eq!(envelope.recipient, public_key.to_id());
+///
+/// let key2 = envelope.sealed.unseal(&secret_key).unwrap();
+/// assert_eq!(key, key2);
+/// ```
+pub struct SealedKeyEnvelope<V: SealedVersion> {
+    /// The ID of the public key that `sealed` was sealed to
+    pub recipient: KeyId<V, Public>,
+    /// The sealed local key
+    pub sealed: SealedKey<V>,
+}
+
+impl<V: SealedVersion> Clone for SealedKeyEnvelope<V> {
+    fn clone(&self) -> Self {
+        Self {
+            recipient: self.recipient,
+            sealed: self.sealed.clone(),
+        }
+    }
+}
+
+impl<V: SealedVersion> crate::SafeForFooter for SealedKeyEnvelope<V> {}
+impl<V: SealedVersion> FromPaserkBytes for SealedKeyEnvelope<V> {}
+
+impl<V: SealedVersion> Key<V, Local>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Seal this key to `sealing_key`, recording the ID of the recipient. See [`SealedKeyEnvelope`]
+    pub fn seal_for(&self, sealing_key: &Key<V, Public>) -> SealedKeyEnvelope<V> {
+        SealedKeyEnvelope {
+            recipient: sealing_key.to_id(),
+            sealed: self.seal(sealing_key),
+        }
+    }
+}
+
+impl<V: SealedVersion> FromStr for SealedKeyEnvelope<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (recipient, rest) = KeyId::from_paserk_prefix(s.as_bytes())?;
+        let sealed = rest.strip_prefix(b":").ok_or(PasetoError::WrongHeader)?;
+        let sealed = SealedKey::from_paserk_bytes(sealed)?;
+
+        Ok(Self { recipient, sealed })
+    }
+}
+
+impl<V: SealedVersion> fmt::Display for SealedKeyEnvelope<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.recipient, self.sealed)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: SealedVersion> serde::Serialize for SealedKeyEnvelope<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: SealedVersion> serde::Deserialize<'de> for SealedKeyEnvelope<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: SealedVersion> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = SealedKeyEnvelope<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a \"{}pid.\" key id and a \"{}seal.\" sealed key",
+                    V::KEY_HEADER,
+                    V::KEY_HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
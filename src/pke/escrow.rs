@@ -0,0 +1,101 @@
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+
+use crate::{Key, KeyId, Local, MultiSealedKey, Public};
+
+use super::SealedVersion;
+
+/// A set of recovery public keys that every sealed key is also sealed to, so that keys can
+/// still be recovered if the primary recipients lose their secret keys.
+///
+/// The recovery keys are added after the primary recipients in the returned
+/// [`MultiSealedKey`], and can be told apart by their IDs.
+///
+/// ```
+/// use rusty_paserk::{EscrowPolicy, Key, Secret, V4};
+///
+/// let recovery = Key::<V4, Secret>::new_os_random();
+/// let policy = EscrowPolicy::new([recovery.public_key()]);
+///
+/// let app_server = Key::<V4, Secret>::new_os_random();
+/// let (key, sealed) = policy.generate(&[app_server.public_key()]);
+///
+/// assert_eq!(sealed.unseal_with(&app_server).unwrap(), key);
+/// assert!(policy.is_escrowed(&sealed));
+///
+/// // if the app server's secret key is lost
+/// assert_eq!(sealed.unseal_with(&recovery).unwrap(), key);
+/// ```
+pub struct EscrowPolicy<V: SealedVersion> {
+    recovery_keys: Vec<Key<V, Public>>,
+}
+
+impl<V: SealedVersion> EscrowPolicy<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Escrow every key to all of `recovery_keys`
+    pub fn new(recovery_keys: impl IntoIterator<Item = Key<V, Public>>) -> Self {
+        Self {
+            recovery_keys: recovery_keys.into_iter().collect(),
+        }
+    }
+
+    /// The recovery keys that every key is sealed to
+    pub fn recovery_keys(&self) -> &[Key<V, Public>] {
+        &self.recovery_keys
+    }
+
+    /// Generate a new local key, sealed to `recipients` and the recovery keys
+    pub fn generate(&self, recipients: &[Key<V, Public>]) -> (Key<V, Local>, MultiSealedKey<V>) {
+        self.generate_with_rng(recipients, &mut OsRng)
+    }
+
+    /// Generate a new local key, sealed to `recipients` and the recovery keys,
+    /// using the provided random source.
+    pub fn generate_with_rng(
+        &self,
+        recipients: &[Key<V, Public>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Key<V, Local>, MultiSealedKey<V>) {
+        let key = Key::<V, Local>::new_random(rng);
+        let sealed = self.seal_with_rng(&key, recipients, rng);
+        (key, sealed)
+    }
+
+    /// Seal `key` to `recipients` and the recovery keys
+    pub fn seal(&self, key: &Key<V, Local>, recipients: &[Key<V, Public>]) -> MultiSealedKey<V> {
+        self.seal_with_rng(key, recipients, &mut OsRng)
+    }
+
+    /// Seal `key` to `recipients` and the recovery keys, using the provided random source.
+    pub fn seal_with_rng(
+        &self,
+        key: &Key<V, Local>,
+        recipients: &[Key<V, Public>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> MultiSealedKey<V> {
+        let all: Vec<_> = recipients
+            .iter()
+            .chain(&self.recovery_keys)
+            .cloned()
+            .collect();
+        key.seal_multi_with_rng(&all, rng)
+    }
+
+    /// Whether `sealed` has an entry for every recovery key, for auditing keys that were
+    /// sealed elsewhere.
+    pub fn is_escrowed(&self, sealed: &MultiSealedKey<V>) -> bool {
+        self.recovery_keys.iter().all(|pk| {
+            let id = pk.to_id();
+            sealed.recipients().any(|r| *r == id)
+        })
+    }
+}
+
+impl<V: SealedVersion> Clone for EscrowPolicy<V> {
+    fn clone(&self) -> Self {
+        Self {
+            recovery_keys: self.recovery_keys.clone(),
+        }
+    }
+}
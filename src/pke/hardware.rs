@@ -0,0 +1,90 @@
+use generic_array::{typenum::U48, GenericArray};
+use rusty_paseto::core::{PasetoError, V3};
+
+use crate::{Key, Local, Public, SealedKey, Secret};
+
+/// A V3 unsealing key held in a hardware token, such as a PKCS#11 HSM.
+///
+/// Only the P-384 ECDH with the ephemeral public key runs on the token. The KDF, tag check and
+/// decryption of the sealed key happen in process memory using the shared secret, so the
+/// private key never leaves the token.
+///
+/// With PKCS#11, [`diffie_hellman`](Self::diffie_hellman) maps to `C_DeriveKey` with
+/// `CKM_ECDH1_DERIVE` and `CKD_NULL`, deriving an extractable generic secret.
+///
+/// ```
+/// use p384::{ecdh::diffie_hellman, PublicKey, SecretKey};
+/// use rusty_paserk::{
+///     internal::generic_array::GenericArray, HardwareUnsealer, Key, Local, PasetoError,
+///     Public, V3,
+/// };
+///
+/// /// Stands in for a handle to a key inside an HSM
+/// struct Token(SecretKey);
+///
+/// impl HardwareUnsealer for Token {
+///     fn public_key(&self) -> Key<V3, Public> {
+///         Key::from_sec1_bytes(self.0.public_key().to_sec1_bytes().as_ref()).unwrap()
+///     }
+///
+///     fn diffie_hellman(
+///         &self,
+///         ephemeral_public_key: &[u8],
+///     ) -> Result<GenericArray<u8, rusty_paserk::internal::generic_array::typenum::U48>, PasetoError> {
+///         let epk = PublicKey::from_sec1_bytes(ephemeral_public_key)
+///             .map_err(|_| PasetoError::InvalidKey)?;
+///         let xk = diffie_hellman(self.0.to_nonzero_scalar(), epk.as_affine());
+///         Ok(GenericArray::clone_from_slice(xk.raw_secret_bytes()))
+///     }
+/// }
+///
+/// let token = Token(SecretKey::random(&mut rand::rngs::OsRng));
+///
+/// let key = Key::<V3, Local>::new_os_random();
+/// let sealed = key.seal(&token.public_key());
+///
+/// assert_eq!(sealed.unseal_with_hardware(&token).unwrap(), key);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "pkcs11")))]
+pub trait HardwareUnsealer {
+    /// The public key of the unsealing key
+    fn public_key(&self) -> Key<V3, Public>;
+
+    /// The x-coordinate of the P-384 ECDH shared point with the compressed SEC1
+    /// `ephemeral_public_key`
+    fn diffie_hellman(
+        &self,
+        ephemeral_public_key: &[u8],
+    ) -> Result<GenericArray<u8, U48>, PasetoError>;
+}
+
+impl HardwareUnsealer for Key<V3, Secret> {
+    fn public_key(&self) -> Key<V3, Public> {
+        Key::<V3, Secret>::public_key(self)
+    }
+
+    fn diffie_hellman(
+        &self,
+        ephemeral_public_key: &[u8],
+    ) -> Result<GenericArray<u8, U48>, PasetoError> {
+        use p384::{ecdh::diffie_hellman, PublicKey, SecretKey};
+
+        let sk = SecretKey::from_bytes(&self.key).map_err(|_| PasetoError::InvalidKey)?;
+        let epk = PublicKey::from_sec1_bytes(ephemeral_public_key)
+            .map_err(|_| PasetoError::InvalidKey)?;
+        let xk = diffie_hellman(sk.to_nonzero_scalar(), epk.as_affine());
+        Ok(GenericArray::clone_from_slice(xk.raw_secret_bytes()))
+    }
+}
+
+impl SealedKey<V3> {
+    /// Unseal an encrypted local key with a key held in hardware. See [`HardwareUnsealer`]
+    pub fn unseal_with_hardware(
+        self,
+        unsealer: &impl HardwareUnsealer,
+    ) -> Result<Key<V3, Local>, PasetoError> {
+        let xk = unsealer.diffie_hellman(&self.ephemeral_public_key)?;
+        let pk = unsealer.public_key();
+        super::unseal_p384(self, &xk, pk.as_ref())
+    }
+}
@@ -0,0 +1,156 @@
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::PasetoError;
+
+use crate::{FromPaserkBytes, Key, KeyId, Local, Public, Secret};
+
+use super::{SealedKeyEnvelope, SealedVersion};
+
+/// One local key sealed to several recipients, such as a set of app servers plus an offline
+/// recovery key.
+///
+/// It is serialized as a `,` separated list of [`SealedKeyEnvelope`]s, keyed by recipient ID.
+///
+/// ```
+/// use rusty_paserk::{Key, Local, MultiSealedKey, Secret, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+///
+/// let app_server = Key::<V4, Secret>::new_os_random();
+/// let recovery = Key::<V4, Secret>::new_os_random();
+///
+/// let sealed = key
+///     .seal_multi(&[app_server.public_key(), recovery.public_key()])
+///     .to_string();
+///
+/// let sealed: MultiSealedKey<V4> = sealed.parse().unwrap();
+/// assert_eq!(sealed.unseal_with(&app_server).unwrap(), key);
+/// assert_eq!(sealed.unseal_with(&recovery).unwrap(), key);
+///
+/// let other = Key::<V4, Secret>::new_os_random();
+/// assert!(sealed.unseal_with(&other).is_err());
+/// ```
+pub struct MultiSealedKey<V: SealedVersion> {
+    entries: Vec<SealedKeyEnvelope<V>>,
+}
+
+impl<V: SealedVersion> crate::SafeForFooter for MultiSealedKey<V> {}
+impl<V: SealedVersion> FromPaserkBytes for MultiSealedKey<V> {}
+
+impl<V: SealedVersion> Key<V, Local>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Seal this key to each of `recipients`. See [`MultiSealedKey`]
+    pub fn seal_multi(&self, recipients: &[Key<V, Public>]) -> MultiSealedKey<V> {
+        self.seal_multi_with_rng(recipients, &mut OsRng)
+    }
+
+    /// Seal this key to each of `recipients`, using the provided random source.
+    pub fn seal_multi_with_rng(
+        &self,
+        recipients: &[Key<V, Public>],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> MultiSealedKey<V> {
+        let entries = recipients
+            .iter()
+            .map(|pk| SealedKeyEnvelope {
+                recipient: pk.to_id(),
+                sealed: self.seal_with_rng(pk, rng),
+            })
+            .collect();
+        MultiSealedKey { entries }
+    }
+}
+
+impl<V: SealedVersion> MultiSealedKey<V> {
+    /// The IDs of the keys that can unseal this key
+    pub fn recipients(&self) -> impl Iterator<Item = &KeyId<V, Public>> {
+        self.entries.iter().map(|e| &e.recipient)
+    }
+
+    /// The sealed key for each recipient
+    pub fn entries(&self) -> &[SealedKeyEnvelope<V>] {
+        &self.entries
+    }
+
+    /// Unseal the key with the entry for `unsealing_key`.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the key was not sealed to `unsealing_key`.
+    pub fn unseal_with(&self, unsealing_key: &Key<V, Secret>) -> Result<Key<V, Local>, PasetoError>
+    where
+        Key<V, Public>: for<'a> From<&'a Key<V, Secret>>,
+        KeyId<V, Public>: From<Key<V, Public>>,
+    {
+        let id = Key::<V, Public>::from(unsealing_key).to_id();
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.recipient == id)
+            .ok_or(PasetoError::InvalidKey)?;
+        entry.sealed.clone().unseal(unsealing_key)
+    }
+}
+
+impl<V: SealedVersion> FromStr for MultiSealedKey<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let entries = s.split(',').map(str::parse).collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+}
+
+impl<V: SealedVersion> fmt::Display for MultiSealedKey<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            entry.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: SealedVersion> serde::Serialize for MultiSealedKey<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: SealedVersion> serde::Deserialize<'de> for MultiSealedKey<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: SealedVersion> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = MultiSealedKey<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a list of \"{}pid.\" key ids and \"{}seal.\" sealed keys",
+                    V::KEY_HEADER,
+                    V::KEY_HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
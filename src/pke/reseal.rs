@@ -0,0 +1,67 @@
+use rusty_paseto::core::PasetoError;
+
+use crate::{Key, KeyId, Local, Public, SealedKey, SealedKeyEnvelope};
+
+use super::SealedVersion;
+
+/// An audit record for one [`SealedKeyEnvelope::reseal`] operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResealEvent<V: SealedVersion> {
+    /// The public key the envelope was sealed to
+    pub from: KeyId<V, Public>,
+    /// The public key the proxy was asked to reseal to
+    pub to: KeyId<V, Public>,
+    /// Whether the key was unsealed and resealed
+    pub succeeded: bool,
+}
+
+impl<V: SealedVersion> SealedKeyEnvelope<V>
+where
+    KeyId<V, Public>: From<Key<V, Public>>,
+{
+    /// Re-encrypt this envelope for `requester`, as a re-encryption proxy.
+    ///
+    /// `unseal` is given the sealed key, and would usually call out to an HSM.
+    /// The plaintext local key only exists for the duration of this call.
+    /// `audit` is called once for each operation, whether it succeeded or not.
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, Local, Secret, V4};
+    ///
+    /// let key = Key::<V4, Local>::new_os_random();
+    /// let hsm_key = Key::<V4, Secret>::new_os_random();
+    /// let requester = Key::<V4, Secret>::new_os_random();
+    ///
+    /// let envelope = key.seal_for(&hsm_key.public_key());
+    ///
+    /// let mut audit_log = vec![];
+    /// let resealed = envelope
+    ///     .reseal(
+    ///         |sealed| sealed.unseal(&hsm_key),
+    ///         &requester.public_key(),
+    ///         |event| audit_log.push(event.clone()),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(resealed.recipient, requester.public_key().to_id());
+    /// assert_eq!(resealed.sealed.unseal(&requester).unwrap(), key);
+    /// assert!(audit_log[0].succeeded);
+    /// ```
+    pub fn reseal(
+        self,
+        unseal: impl FnOnce(SealedKey<V>) -> Result<Key<V, Local>, PasetoError>,
+        requester: &Key<V, Public>,
+        audit: impl FnOnce(&ResealEvent<V>),
+    ) -> Result<SealedKeyEnvelope<V>, PasetoError> {
+        let mut event = ResealEvent {
+            from: self.recipient,
+            to: requester.to_id(),
+            succeeded: false,
+        };
+
+        let result = unseal(self.sealed).map(|key| key.seal_for(requester));
+        event.succeeded = result.is_ok();
+        audit(&event);
+        result
+    }
+}
@@ -0,0 +1,331 @@
+use std::{fmt, str::FromStr};
+
+use curve25519_dalek::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    MontgomeryPoint,
+};
+use generic_array::{typenum::U33, GenericArray};
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+use rusty_paseto::core::{PasetoError, V4};
+use subtle::ConstantTimeEq;
+
+use crate::{read_b64, write_b64, Key, Local, Public, SealedKey, Version};
+
+const SHARE_HEADER: &str = "secret-share.";
+const PARTIAL_HEADER: &str = "partial-unseal.";
+
+/// One share of a V4 unsealing key that is split between `n` holders, any `t` of which are
+/// needed to unseal.
+///
+/// This is not a standard PASERK type. The unsealing key never exists in one place: a dealer
+/// generates it with [`split_unsealing_key`](Self::split_unsealing_key), hands out the shares,
+/// and publishes the public key. Keys are [sealed](Key::seal) to that public key as normal.
+/// To unseal, each share holder computes a [`PartialUnseal`] of the sealed key, and anyone with
+/// `t` partials can [combine](SealedKey::unseal_threshold) them.
+///
+/// Shares are serialized with the custom `k4.secret-share.` header. Partials are not verified
+/// individually: a wrong or missing partial makes unsealing fail with
+/// [`PasetoError::InvalidSignature`].
+///
+/// ```
+/// use rusty_paserk::{Key, KeyShare, Local, PartialUnseal, V4};
+///
+/// // 2 of 3 holders are needed
+/// let (public_key, shares) = KeyShare::split_unsealing_key(2, 3);
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let sealed = key.seal(&public_key);
+///
+/// // each holder computes their partial and sends it to whoever combines them
+/// let partials: Vec<PartialUnseal> = shares[1..]
+///     .iter()
+///     .map(|share| share.partial_unseal(&sealed).unwrap())
+///     .collect();
+///
+/// let key2 = sealed.clone().unseal_threshold(&public_key, &partials).unwrap();
+/// assert_eq!(key, key2);
+///
+/// // one share is not enough
+/// assert!(sealed.unseal_threshold(&public_key, &partials[..1]).is_err());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "threshold")))]
+pub struct KeyShare {
+    index: u8,
+    share: Scalar,
+}
+
+/// A share holder's contribution to unsealing one [`SealedKey`]. See [`KeyShare`]
+///
+/// It is serialized with the custom `k4.partial-unseal.` header.
+#[cfg_attr(docsrs, doc(cfg(feature = "threshold")))]
+#[derive(Clone, PartialEq, Eq)]
+pub struct PartialUnseal {
+    index: u8,
+    point: EdwardsPoint,
+}
+
+impl KeyShare {
+    /// Generate a new unsealing key, split into `shares` shares with `threshold` needed to
+    /// unseal. Returns the public key to seal to, and the shares to distribute.
+    ///
+    /// # Panics
+    ///
+    /// If `threshold` is 0 or greater than `shares`.
+    pub fn split_unsealing_key(threshold: u8, shares: u8) -> (Key<V4, Public>, Vec<KeyShare>) {
+        Self::split_unsealing_key_with_rng(threshold, shares, &mut OsRng)
+    }
+
+    /// Generate a new split unsealing key, using the provided random source.
+    ///
+    /// # Panics
+    ///
+    /// If `threshold` is 0 or greater than `shares`.
+    pub fn split_unsealing_key_with_rng(
+        threshold: u8,
+        shares: u8,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> (Key<V4, Public>, Vec<KeyShare>) {
+        assert!(
+            0 < threshold && threshold <= shares,
+            "threshold must be between 1 and the number of shares"
+        );
+
+        // the secret is the constant term of a random polynomial of degree threshold - 1
+        let coefficients: Vec<Scalar> = (0..threshold).map(|_| random_scalar(rng)).collect();
+        let public_key = Key {
+            key: EdwardsPoint::mul_base(&coefficients[0])
+                .compress()
+                .to_bytes()
+                .into(),
+        };
+
+        let shares = (1..=shares)
+            .map(|index| {
+                let x = Scalar::from(index);
+                let share = coefficients
+                    .iter()
+                    .rev()
+                    .fold(Scalar::ZERO, |acc, c| acc * x + c);
+                KeyShare { index, share }
+            })
+            .collect();
+
+        (public_key, shares)
+    }
+
+    /// The index of this share, from 1 to the number of shares
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Compute this holder's contribution to unsealing `sealed`.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the ephemeral key in `sealed` is not a valid
+    /// curve point in the prime order subgroup. Multiplying the share by a point of small order
+    /// would reveal the share modulo the cofactor to whoever crafted the sealed key.
+    ///
+    /// ```
+    /// use rusty_paserk::{KeyShare, SealedKey, V4};
+    ///
+    /// let (_, shares) = KeyShare::split_unsealing_key(2, 3);
+    ///
+    /// // the ephemeral key is the point of order 2
+    /// let low_order: SealedKey<V4> = format!("k4.seal.{}", "A".repeat(128)).parse().unwrap();
+    /// assert!(shares[0].partial_unseal(&low_order).is_err());
+    /// ```
+    pub fn partial_unseal(&self, sealed: &SealedKey<V4>) -> Result<PartialUnseal, PasetoError> {
+        let epk = MontgomeryPoint(sealed.ephemeral_public_key.into())
+            .to_edwards(0)
+            .filter(|epk| !epk.is_small_order() && epk.is_torsion_free())
+            .ok_or(PasetoError::InvalidKey)?;
+        Ok(PartialUnseal {
+            index: self.index,
+            point: self.share * epk,
+        })
+    }
+}
+
+impl SealedKey<V4> {
+    /// Unseal with the partials from at least `threshold` distinct share holders of the
+    /// unsealing key for `public_key`. See [`KeyShare`]
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if two partials come from the same share, and
+    /// [`PasetoError::InvalidSignature`] if there are too few partials or any of them is wrong.
+    pub fn unseal_threshold(
+        self,
+        public_key: &Key<V4, Public>,
+        partials: &[PartialUnseal],
+    ) -> Result<Key<V4, Local>, PasetoError> {
+        let xpk = CompressedEdwardsY(public_key.key.into())
+            .decompress()
+            .ok_or(PasetoError::InvalidKey)?
+            .to_montgomery();
+
+        let mut xk = EdwardsPoint::default();
+        for (i, partial) in partials.iter().enumerate() {
+            // lagrange coefficient for evaluating the polynomial at 0
+            let xi = Scalar::from(partial.index);
+            let mut lambda = Scalar::ONE;
+            for (j, other) in partials.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if other.index == partial.index {
+                    return Err(PasetoError::InvalidKey);
+                }
+                let xj = Scalar::from(other.index);
+                lambda *= xj * (xj - xi).invert();
+            }
+            xk += lambda * partial.point;
+        }
+
+        super::unseal_x25519(self, &xk.to_montgomery(), &xpk)
+    }
+}
+
+fn random_scalar(rng: &mut (impl RngCore + CryptoRng)) -> Scalar {
+    let mut bytes = [0; 64];
+    rng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+impl FromStr for KeyShare {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V4::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(SHARE_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let bytes: GenericArray<u8, U33> = read_b64(s)?;
+        let share = Option::from(Scalar::from_canonical_bytes(
+            bytes[1..].try_into().expect("share should be 32 bytes"),
+        ))
+        .ok_or(PasetoError::InvalidKey)?;
+        if bytes[0] == 0 {
+            return Err(PasetoError::InvalidKey);
+        }
+
+        Ok(Self {
+            index: bytes[0],
+            share,
+        })
+    }
+}
+
+impl fmt::Display for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V4::KEY_HEADER)?;
+        f.write_str(SHARE_HEADER)?;
+
+        let mut bytes = [0; 33];
+        bytes[0] = self.index;
+        bytes[1..].copy_from_slice(self.share.as_bytes());
+        write_b64(&bytes, f)
+    }
+}
+
+impl fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for KeyShare {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && bool::from(self.share.ct_eq(&other.share))
+    }
+}
+
+impl Eq for KeyShare {}
+
+impl FromStr for PartialUnseal {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V4::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(PARTIAL_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let bytes: GenericArray<u8, U33> = read_b64(s)?;
+        let point = CompressedEdwardsY::from_slice(&bytes[1..])
+            .ok()
+            .and_then(|p| p.decompress())
+            .ok_or(PasetoError::InvalidKey)?;
+        if bytes[0] == 0 {
+            return Err(PasetoError::InvalidKey);
+        }
+
+        Ok(Self {
+            index: bytes[0],
+            point,
+        })
+    }
+}
+
+impl fmt::Display for PartialUnseal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V4::KEY_HEADER)?;
+        f.write_str(PARTIAL_HEADER)?;
+
+        let mut bytes = [0; 33];
+        bytes[0] = self.index;
+        bytes[1..].copy_from_slice(self.point.compress().as_bytes());
+        write_b64(&bytes, f)
+    }
+}
+
+impl fmt::Debug for PartialUnseal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl crate::SafeForFooter for PartialUnseal {}
+impl crate::FromPaserkBytes for KeyShare {}
+impl crate::FromPaserkBytes for PartialUnseal {}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl serde::Serialize for PartialUnseal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PartialUnseal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor;
+        impl serde::de::Visitor<'_> for FromStrVisitor {
+            type Value = PartialUnseal;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a \"k4.{PARTIAL_HEADER}\" serialized value")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor)
+    }
+}
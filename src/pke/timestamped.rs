@@ -0,0 +1,233 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use digest::{Mac, Output};
+use rusty_paseto::core::PasetoError;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    read_b64, wrap::PieVersion, write_b64, Clock, FromPaserkBytes, Key, Local, Public, SealedKey,
+    Secret, SystemClock,
+};
+
+use super::SealedVersion;
+
+/// A [`SealedKey`] bound to the time it was created and how long it may be used for.
+///
+/// The timestamps are authenticated with a MAC keyed by the sealed local key, so they cannot
+/// be changed without the recipient noticing. This lets consumers refuse stale sealed keys
+/// without keeping a separate database of when they were issued.
+///
+/// It is serialized as the creation time and max age in seconds, the MAC, and the sealed key,
+/// separated by `:`.
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use rusty_paserk::{Key, Local, Secret, TimestampedSealedKey, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+///
+/// let secret_key = Key::<V4, Secret>::new_os_random();
+/// let public_key = secret_key.public_key();
+///
+/// let sealed = key.seal_timestamped(&public_key, Duration::from_secs(3600)).to_string();
+/// // => "1700000000:3600:zLoE0a-ng2L7ztBkD1r5ifnAvmQmjNaj1TW2K_FXVvU:k4.seal.23KlrMHZLW4muL75Rnuqtaro9F16mqDNvmCbgDXi2IdNyWmjrbTVBEih1DhSI_5xp7b7mCHSFo1DMv-9GtZUSpyi4646XBxpbFShHjJihF_Af8maWsDqdzOof76ia0Cv"
+///
+/// let sealed: TimestampedSealedKey<V4> = sealed.parse().unwrap();
+/// let key2 = sealed.unseal(&secret_key, SystemTime::now()).unwrap();
+/// assert_eq!(key, key2);
+///
+/// let sealed = key.seal_timestamped(&public_key, Duration::from_secs(3600));
+/// let later = SystemTime::now() + Duration::from_secs(7200);
+/// assert!(sealed.unseal(&secret_key, later).is_err());
+///
+/// // creation times that can't be represented as a `SystemTime` are rejected
+/// let sealed = key.seal_timestamped(&public_key, Duration::from_secs(3600)).to_string();
+/// let (_, rest) = sealed.split_once(':').unwrap();
+/// let sealed = format!("{}:{rest}", u64::MAX);
+/// assert!(sealed.parse::<TimestampedSealedKey<V4>>().is_err());
+/// ```
+pub struct TimestampedSealedKey<V: SealedVersion + PieVersion> {
+    created: u64,
+    max_age: u64,
+    tag: Output<V::TagMac>,
+    sealed: SealedKey<V>,
+}
+
+impl<V: SealedVersion + PieVersion> crate::SafeForFooter for TimestampedSealedKey<V> {}
+impl<V: SealedVersion + PieVersion> FromPaserkBytes for TimestampedSealedKey<V> {}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn from_unix_secs(secs: u64) -> Option<SystemTime> {
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
+
+fn tag<V: SealedVersion + PieVersion>(
+    key: &Key<V, Local>,
+    created: u64,
+    max_age: u64,
+    sealed: &str,
+) -> Output<V::TagMac> {
+    <V::TagMac as Mac>::new_from_slice(&key.key)
+        .expect("key should be valid hmac key")
+        .chain_update(b"paserk-sealed-timestamp.")
+        .chain_update(created.to_be_bytes())
+        .chain_update(max_age.to_be_bytes())
+        .chain_update(sealed)
+        .finalize()
+        .into_bytes()
+}
+
+impl<V: SealedVersion + PieVersion> Key<V, Local> {
+    /// Seal this key to `sealing_key`, valid for `max_age` from now. See [`TimestampedSealedKey`]
+    pub fn seal_timestamped(
+        &self,
+        sealing_key: &Key<V, Public>,
+        max_age: Duration,
+    ) -> TimestampedSealedKey<V> {
+        self.seal_timestamped_with_clock(sealing_key, max_age, &SystemClock)
+    }
+
+    /// Seal this key to `sealing_key`, valid for `max_age` from the time given by `clock`
+    pub fn seal_timestamped_with_clock(
+        &self,
+        sealing_key: &Key<V, Public>,
+        max_age: Duration,
+        clock: &impl Clock,
+    ) -> TimestampedSealedKey<V> {
+        let created = unix_secs(clock.now());
+        let max_age = max_age.as_secs();
+        let sealed = self.seal(sealing_key);
+        let tag = tag(self, created, max_age, &sealed.to_string());
+
+        TimestampedSealedKey {
+            created,
+            max_age,
+            tag,
+            sealed,
+        }
+    }
+}
+
+impl<V: SealedVersion + PieVersion> TimestampedSealedKey<V> {
+    /// When the key was sealed. This is not authenticated until the key is unsealed.
+    pub fn created(&self) -> SystemTime {
+        from_unix_secs(self.created).expect("creation time should be checked when parsing")
+    }
+
+    /// How long after [`created`](Self::created) the key may be unsealed
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age)
+    }
+
+    /// Unseal the local key, checking the timestamps against `now`.
+    ///
+    /// Returns [`PasetoError::InvalidKey`] if the key is older than its max age, or was created in the future.
+    pub fn unseal(
+        self,
+        unsealing_key: &Key<V, Secret>,
+        now: SystemTime,
+    ) -> Result<Key<V, Local>, PasetoError> {
+        let sealed = self.sealed.to_string();
+        let key = self.sealed.unseal(unsealing_key)?;
+
+        let expected = tag(&key, self.created, self.max_age, &sealed);
+        if expected.ct_ne(&self.tag).into() {
+            return Err(PasetoError::Cryption);
+        }
+
+        let now = unix_secs(now);
+        if now < self.created || now - self.created > self.max_age {
+            return Err(PasetoError::InvalidKey);
+        }
+
+        Ok(key)
+    }
+
+    /// Unseal the local key, checking the timestamps against the time given by `clock`.
+    pub fn unseal_with_clock(
+        self,
+        unsealing_key: &Key<V, Secret>,
+        clock: &impl Clock,
+    ) -> Result<Key<V, Local>, PasetoError> {
+        self.unseal(unsealing_key, clock.now())
+    }
+}
+
+impl<V: SealedVersion + PieVersion> FromStr for TimestampedSealedKey<V> {
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(4, ':');
+        let mut next = || parts.next().ok_or(PasetoError::IncorrectSize);
+
+        let created = next()?.parse().map_err(|_| PasetoError::IncorrectSize)?;
+        if from_unix_secs(created).is_none() {
+            return Err(PasetoError::IncorrectSize);
+        }
+        let max_age = next()?.parse().map_err(|_| PasetoError::IncorrectSize)?;
+        let tag = read_b64(next()?)?;
+        let sealed = next()?.parse()?;
+
+        Ok(Self {
+            created,
+            max_age,
+            tag,
+            sealed,
+        })
+    }
+}
+
+impl<V: SealedVersion + PieVersion> fmt::Display for TimestampedSealedKey<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:", self.created, self.max_age)?;
+        write_b64(&self.tag, f)?;
+        write!(f, ":{}", self.sealed)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<V: SealedVersion + PieVersion> serde::Serialize for TimestampedSealedKey<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+impl<'de, V: SealedVersion + PieVersion> serde::Deserialize<'de> for TimestampedSealedKey<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FromStrVisitor<V>(std::marker::PhantomData<V>);
+        impl<'de, V: SealedVersion + PieVersion> serde::de::Visitor<'de> for FromStrVisitor<V> {
+            type Value = TimestampedSealedKey<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "timestamps and a \"{}seal.\" sealed key",
+                    V::KEY_HEADER
+                )
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(FromStrVisitor(std::marker::PhantomData))
+    }
+}
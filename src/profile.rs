@@ -0,0 +1,45 @@
+//! Grouping the parsing options into strict and compatible profiles.
+
+use crate::{Base64Mode, DEFAULT_MAX_PASERK_LEN};
+
+/// How permissive to be when parsing PASERK values.
+///
+/// Internal services should stay [`Strict`](ParseProfile::Strict), while gateways that accept
+/// values from many partners can use [`Compat`](ParseProfile::Compat).
+/// See [`FromPaserkBytes::from_paserk_profile`](crate::FromPaserkBytes::from_paserk_profile).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseProfile {
+    /// Only accept exactly what the spec allows, up to [`DEFAULT_MAX_PASERK_LEN`] bytes
+    #[default]
+    Strict,
+    /// Ignore surrounding whitespace, allow inputs up to 4 times longer, and with the
+    /// `lenient-base64` feature, accept standard base64
+    Compat,
+}
+
+impl ParseProfile {
+    /// The base64 strictness used by this profile
+    pub const fn base64_mode(self) -> Base64Mode {
+        match self {
+            ParseProfile::Strict => Base64Mode::Strict,
+            #[cfg(feature = "lenient-base64")]
+            ParseProfile::Compat => Base64Mode::Lenient,
+            #[cfg(not(feature = "lenient-base64"))]
+            ParseProfile::Compat => Base64Mode::Strict,
+        }
+    }
+
+    /// The longest input accepted by this profile, before trimming
+    pub const fn max_len(self) -> usize {
+        match self {
+            ParseProfile::Strict => DEFAULT_MAX_PASERK_LEN,
+            ParseProfile::Compat => DEFAULT_MAX_PASERK_LEN * 4,
+        }
+    }
+
+    /// Whether surrounding whitespace is ignored
+    pub const fn trims_whitespace(self) -> bool {
+        matches!(self, ParseProfile::Compat)
+    }
+}
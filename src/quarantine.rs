@@ -0,0 +1,98 @@
+//! Holding imported keys until a policy approves them.
+
+use std::str::FromStr;
+
+use rusty_paseto::core::PasetoError;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::{Key, KeyId, KeyType, PlaintextKey, Version};
+
+/// An imported key that cannot be used until it is released by an explicit policy check.
+///
+/// Keys parsed from plaintext or other external formats can be wrapped in this type so
+/// that they cannot reach `seal`, `unseal` or any other operation before they have been
+/// pinned to a known [`KeyId`], or approved by an attestation or operator callback.
+///
+/// ```
+/// use rusty_paserk::{Key, KeyId, Local, PlaintextKey, Quarantined, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let trusted: Vec<KeyId<V4, Local>> = vec![key.to_id()];
+///
+/// let imported = PlaintextKey(key).to_string();
+///
+/// let quarantined: Quarantined<V4, Local> = imported.parse().unwrap();
+/// let key2 = quarantined.release_pinned(&trusted).unwrap();
+/// assert_eq!(key, key2);
+///
+/// let other = Quarantined::new(Key::<V4, Local>::new_os_random());
+/// assert!(other.release_pinned(&trusted).is_err());
+///
+/// let other = Quarantined::new(Key::<V4, Local>::new_os_random());
+/// assert!(other.release_with(|_id| false).is_err());
+/// ```
+pub struct Quarantined<V: Version, K: KeyType<V>> {
+    key: Key<V, K>,
+}
+
+impl<V: Version, K: KeyType<V>> Quarantined<V, K>
+where
+    KeyId<V, K>: From<Key<V, K>>,
+{
+    /// Quarantine a key
+    pub fn new(key: Key<V, K>) -> Self {
+        Self { key }
+    }
+
+    /// The ID of the quarantined key, for logging or showing to an operator
+    pub fn id(&self) -> KeyId<V, K> {
+        self.key.to_id()
+    }
+
+    /// Release the key if its ID is one of the pinned IDs.
+    ///
+    /// The IDs are compared in constant time.
+    pub fn release_pinned(self, pinned: &[KeyId<V, K>]) -> Result<Key<V, K>, PasetoError> {
+        let mut found = Choice::from(0);
+        for id in pinned {
+            found.conditional_assign(&Choice::from(1), id.is_id_of(&self.key));
+        }
+        if bool::from(found) {
+            Ok(self.key)
+        } else {
+            Err(PasetoError::InvalidKey)
+        }
+    }
+
+    /// Release the key if `approve` accepts its ID.
+    ///
+    /// Use this for attestation checks or operator approval.
+    pub fn release_with(
+        self,
+        approve: impl FnOnce(&KeyId<V, K>) -> bool,
+    ) -> Result<Key<V, K>, PasetoError> {
+        if approve(&self.id()) {
+            Ok(self.key)
+        } else {
+            Err(PasetoError::InvalidKey)
+        }
+    }
+}
+
+impl<V: Version, K: KeyType<V>> From<Key<V, K>> for Quarantined<V, K> {
+    fn from(key: Key<V, K>) -> Self {
+        Self { key }
+    }
+}
+
+impl<V: Version, K: KeyType<V>> FromStr for Quarantined<V, K> {
+    type Err = PasetoError;
+
+    /// Parse a [`PlaintextKey`] into quarantine
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let PlaintextKey(key) = s.parse()?;
+        Ok(Self { key })
+    }
+}
+
+impl<V: Version, K: KeyType<V>> crate::FromPaserkBytes for Quarantined<V, K> {}
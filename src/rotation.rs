@@ -0,0 +1,279 @@
+//! Helpers for rotating keys without breaking tokens that are still in flight.
+
+use std::time::{Duration, SystemTime};
+
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::{internal::KeyRingType, Clock, Key, KeyId, KeyRing, KeyType, SystemClock, Version};
+
+/// Which of the keys in a [`DualKey`] was able to open a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyUsed {
+    /// The current key.
+    Current,
+    /// The previous key. Once this stops being reported, the previous key can be retired.
+    Previous,
+}
+
+/// The current key, plus at most one previous key that is still accepted while the
+/// tokens issued under it drain.
+///
+/// ```
+/// use rusty_paserk::{DualKey, Key, KeyUsed, Local, V4};
+/// use rusty_paseto::core::{Key as RawKey, Local as LocalPurpose, Paseto, PasetoNonce, Payload};
+///
+/// let old_key = Key::<V4, Local>::new_os_random();
+/// let new_key = Key::<V4, Local>::new_os_random();
+///
+/// // a token issued before the rotation
+/// let nonce = RawKey::<32>::try_new_random().unwrap();
+/// let token = Paseto::<V4, LocalPurpose>::builder()
+///     .set_payload(Payload::from("hello"))
+///     .try_encrypt(&old_key.into(), &PasetoNonce::from(&nonce))
+///     .unwrap();
+///
+/// let keys = DualKey::with_previous(new_key, old_key);
+/// let (payload, used) = keys
+///     .try_open(|key| Paseto::<V4, LocalPurpose>::try_decrypt(&token, &(*key).into(), None, None))
+///     .unwrap();
+///
+/// assert_eq!(payload, "hello");
+/// // report this to your metrics so you know when the old key can be removed
+/// assert_eq!(used, KeyUsed::Previous);
+/// ```
+pub struct DualKey<V: Version, K: KeyType<V>> {
+    current: Key<V, K>,
+    previous: Option<Key<V, K>>,
+}
+
+impl<V: Version, K: KeyType<V>> DualKey<V, K> {
+    /// Only accept the current key.
+    pub fn new(current: Key<V, K>) -> Self {
+        Self {
+            current,
+            previous: None,
+        }
+    }
+
+    /// Accept the current key, falling back to the previous key.
+    pub fn with_previous(current: Key<V, K>, previous: Key<V, K>) -> Self {
+        Self {
+            current,
+            previous: Some(previous),
+        }
+    }
+
+    /// The key that should be used for any new tokens
+    pub fn current(&self) -> &Key<V, K> {
+        &self.current
+    }
+
+    /// The key that is still accepted for old tokens, if any
+    pub fn previous(&self) -> Option<&Key<V, K>> {
+        self.previous.as_ref()
+    }
+
+    /// Stop accepting the previous key.
+    pub fn retire_previous(&mut self) -> Option<Key<V, K>> {
+        self.previous.take()
+    }
+
+    /// Run `open` with the current key, and then with the previous key if that failed.
+    ///
+    /// `open` is called at most twice. On success, this reports which key was used
+    /// so that callers can record when the fallback is still being hit.
+    /// If both keys fail, the error from the current key is returned.
+    pub fn try_open<T, E>(
+        &self,
+        mut open: impl FnMut(&Key<V, K>) -> Result<T, E>,
+    ) -> Result<(T, KeyUsed), E> {
+        let err = match open(&self.current) {
+            Ok(t) => return Ok((t, KeyUsed::Current)),
+            Err(err) => err,
+        };
+        match &self.previous {
+            Some(previous) => match open(previous) {
+                Ok(t) => Ok((t, KeyUsed::Previous)),
+                Err(_) => Err(err),
+            },
+            None => Err(err),
+        }
+    }
+}
+
+/// The current key for new tokens, plus previous keys that are still accepted for a grace
+/// period after each [`rotate`](Self::rotate).
+///
+/// Keys are stored in a [`KeyRing`] and looked up by the `kid` in the token footer. Unlike
+/// [`DualKey`], any number of previous keys can be draining at once.
+///
+/// ```
+/// use std::{cell::Cell, time::{Duration, SystemTime}};
+/// use rusty_paserk::{Clock, Key, Local, RotationManager, V4};
+///
+/// struct TestClock(Cell<SystemTime>);
+/// impl Clock for TestClock {
+///     fn now(&self) -> SystemTime {
+///         self.0.get()
+///     }
+/// }
+/// let clock = TestClock(Cell::new(SystemTime::UNIX_EPOCH));
+///
+/// let first = Key::<V4, Local>::new_os_random();
+/// let grace = Duration::from_secs(3600);
+/// let mut keys = RotationManager::with_clock(first, grace, &clock);
+///
+/// let second = Key::<V4, Local>::new_os_random();
+/// keys.rotate(second);
+/// assert_eq!(keys.current(), &second);
+///
+/// // tokens issued with the first key can still be opened during the grace period
+/// assert_eq!(keys.get(&first.to_id()), Some(&first));
+///
+/// clock.0.set(SystemTime::UNIX_EPOCH + Duration::from_secs(7200));
+/// assert_eq!(keys.get(&first.to_id()), None);
+/// assert_eq!(keys.expired(), vec![first.to_id()]);
+/// assert_eq!(keys.prune(), vec![first.to_id()]);
+/// assert!(keys.expired().is_empty());
+///
+/// // keep accepting the second key indefinitely
+/// keys.rotate_with_grace(Key::<V4, Local>::new_os_random(), Duration::MAX);
+/// assert_eq!(keys.get(&second.to_id()), Some(&second));
+/// ```
+pub struct RotationManager<V: Version, K: KeyRingType<V>, C: Clock = SystemClock> {
+    ring: KeyRing<V>,
+    current: KeyId<V, K>,
+    previous: Vec<(KeyId<V, K>, Option<SystemTime>)>,
+    grace_period: Duration,
+    clock: C,
+}
+
+impl<V: Version, K: KeyRingType<V>> RotationManager<V, K> {
+    /// Start with `current`, keeping previous keys for `grace_period` after each rotation
+    pub fn new(current: Key<V, K>, grace_period: Duration) -> Self {
+        Self::with_clock(current, grace_period, SystemClock)
+    }
+}
+
+impl<V: Version, K: KeyRingType<V>, C: Clock> RotationManager<V, K, C> {
+    /// Start with `current`, using the given clock to time the grace periods
+    pub fn with_clock(current: Key<V, K>, grace_period: Duration, clock: C) -> Self {
+        let mut ring = KeyRing::new();
+        let current = ring.insert(current);
+        Self {
+            ring,
+            current,
+            previous: Vec::new(),
+            grace_period,
+            clock,
+        }
+    }
+
+    /// The key that should be used for any new tokens
+    pub fn current(&self) -> &Key<V, K> {
+        self.ring
+            .get(&self.current)
+            .expect("current key should be in the key ring")
+    }
+
+    /// The ID of the current key, to put in the footer of new tokens
+    pub fn current_id(&self) -> &KeyId<V, K> {
+        &self.current
+    }
+
+    /// The default grace period for [`rotate`](Self::rotate)
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    /// Change the default grace period for future rotations
+    pub fn set_grace_period(&mut self, grace_period: Duration) {
+        self.grace_period = grace_period;
+    }
+
+    /// Make `next` the current key, accepting the old current key for the default grace period.
+    pub fn rotate(&mut self, next: Key<V, K>) -> KeyId<V, K> {
+        self.rotate_with_grace(next, self.grace_period)
+    }
+
+    /// Make `next` the current key, accepting the old current key for `grace_period`.
+    ///
+    /// If the end of the grace period can't be represented, such as for [`Duration::MAX`], the
+    /// old key is accepted indefinitely.
+    pub fn rotate_with_grace(&mut self, next: Key<V, K>, grace_period: Duration) -> KeyId<V, K> {
+        let expires = self.clock.now().checked_add(grace_period);
+        let next = self.ring.insert(next);
+        let previous = std::mem::replace(&mut self.current, next);
+        if previous != next {
+            self.previous.retain(|(id, _)| *id != next);
+            self.previous.push((previous, expires));
+        }
+        next
+    }
+
+    /// Find the key with this ID, if it is the current key or a previous key that is still in
+    /// its grace period.
+    ///
+    /// `id` is compared with every known ID in constant time, as in [`KeyRing::get`].
+    pub fn get(&self, id: &KeyId<V, K>) -> Option<&Key<V, K>> {
+        let now = self.clock.now();
+        let mut allowed = self.current.as_bytes().ct_eq(id.as_bytes());
+        for (prev, expires) in &self.previous {
+            allowed |= prev.as_bytes().ct_eq(id.as_bytes())
+                & Choice::from(u8::from(accepted(now, *expires)));
+        }
+        let key = self.ring.get(id);
+        if allowed.into() {
+            key
+        } else {
+            None
+        }
+    }
+
+    /// The previous keys that are still accepted, and when they stop being accepted, if ever
+    pub fn previous(&self) -> impl Iterator<Item = (&KeyId<V, K>, Option<SystemTime>)> {
+        let now = self.clock.now();
+        self.previous
+            .iter()
+            .filter(move |(_, expires)| accepted(now, *expires))
+            .map(|(id, expires)| (id, *expires))
+    }
+
+    /// The previous keys whose grace period has ended, which [`prune`](Self::prune) would
+    /// remove
+    pub fn expired(&self) -> Vec<KeyId<V, K>> {
+        let now = self.clock.now();
+        self.previous
+            .iter()
+            .filter(|(_, expires)| !accepted(now, *expires))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Remove the previous keys whose grace period has ended, returning their IDs
+    pub fn prune(&mut self) -> Vec<KeyId<V, K>> {
+        let now = self.clock.now();
+        let mut removed = Vec::new();
+        self.previous.retain(|(id, expires)| {
+            if accepted(now, *expires) {
+                return true;
+            }
+            removed.push(*id);
+            false
+        });
+        for id in &removed {
+            self.ring.remove(id);
+        }
+        removed
+    }
+
+    /// All the keys that have not been pruned, including expired previous keys
+    pub fn key_ring(&self) -> &KeyRing<V> {
+        &self.ring
+    }
+}
+
+/// Whether a previous key whose grace period ends at `expires` is still accepted at `now`
+fn accepted(now: SystemTime, expires: Option<SystemTime>) -> bool {
+    expires.is_none_or(|expires| now < expires)
+}
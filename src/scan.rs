@@ -0,0 +1,68 @@
+//! Finding PASERK values inside footers.
+
+use crate::DEFAULT_MAX_PASERK_LEN;
+
+/// Find every PASERK value in a footer, such as a JSON object or a raw `;` separated list.
+///
+/// This makes a single pass over `footer`, without regular expressions or parsing the JSON,
+/// so the work is linear in the footer length. Values are runs of base64url and `.` that start
+/// with a `k3.` style version header. Runs longer than [`DEFAULT_MAX_PASERK_LEN`] are skipped.
+///
+/// The values are only located, not validated. Parse the ones you need with
+/// [`FromPaserkBytes`](crate::FromPaserkBytes).
+///
+/// ```
+/// use rusty_paserk::{scan_paserk, FromPaserkBytes, Key, KeyId, Local, V4};
+///
+/// let key = Key::<V4, Local>::new_os_random();
+/// let footer = format!(r#"{{"kid":"{}","note":"k9 is not a key"}}"#, key.to_id());
+///
+/// let values: Vec<&str> = scan_paserk(footer.as_bytes()).collect();
+/// assert_eq!(values.len(), 1);
+///
+/// let kid = KeyId::<V4, Local>::from_paserk_bytes(values[0].as_bytes()).unwrap();
+/// assert_eq!(kid, key.to_id());
+/// ```
+pub fn scan_paserk(footer: &[u8]) -> PaserkScan<'_> {
+    PaserkScan { footer }
+}
+
+/// Iterator returned by [`scan_paserk`]
+#[derive(Debug, Clone)]
+pub struct PaserkScan<'a> {
+    footer: &'a [u8],
+}
+
+fn is_value_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.')
+}
+
+fn has_version_header(value: &[u8]) -> bool {
+    let Some(rest) = value.strip_prefix(b"k") else {
+        return false;
+    };
+    let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    digits > 0 && rest.get(digits) == Some(&b'.')
+}
+
+impl<'a> Iterator for PaserkScan<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.footer.iter().position(|b| is_value_byte(*b))?;
+            let rest = &self.footer[start..];
+            let len = rest
+                .iter()
+                .position(|b| !is_value_byte(*b))
+                .unwrap_or(rest.len());
+            let (value, rest) = rest.split_at(len);
+            self.footer = rest;
+
+            if len <= DEFAULT_MAX_PASERK_LEN && has_version_header(value) {
+                // value bytes are all ascii
+                return std::str::from_utf8(value).ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Adapters for `#[serde(with = "...")]`, for embedding keys in configuration structs.
+//!
+//! All of the PASERK types in this crate already implement `Serialize` and `Deserialize`
+//! as their string form, so `Vec<KeyId<V4, Public>>` or `Option<SealedKey<V4>>` fields
+//! need no annotations. These modules cover the remaining cases:
+//!
+//! * [`plaintext`] - raw [`Key`]s, which are deliberately not serializable on their own.
+//! * [`as_paserk_string`] - any type with `Display` and `FromStr`, such as your own newtypes.
+//!
+//! ```
+//! use rusty_paserk::{Key, KeyId, Local, PlaintextKey, Public, SealedKey, Secret, V4};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "rusty_paserk::serde::plaintext")]
+//!     signing_key: Key<V4, Secret>,
+//!     #[serde(default, with = "rusty_paserk::serde::plaintext::option")]
+//!     fallback_key: Option<Key<V4, Local>>,
+//!     trusted_keys: Vec<KeyId<V4, Public>>,
+//!     session_key: Option<SealedKey<V4>>,
+//! }
+//!
+//! let signing_key = Key::<V4, Secret>::new_os_random();
+//! let config = serde_json::json!({
+//!     "signing_key": PlaintextKey(signing_key).to_string(),
+//!     "trusted_keys": [signing_key.public_key().to_id().to_string()],
+//!     "session_key": null,
+//! });
+//!
+//! let config: Config = serde_json::from_value(config).unwrap();
+//! assert!(config.fallback_key.is_none());
+//! assert_eq!(config.trusted_keys[0], config.signing_key.public_key().to_id());
+//!
+//! let json = serde_json::to_value(&config).unwrap();
+//! assert_eq!(json["signing_key"], PlaintextKey(signing_key).to_string());
+//! assert_eq!(json["fallback_key"], serde_json::Value::Null);
+//! ```
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use ::serde::{de, Deserializer, Serializer};
+
+use crate::{Key, KeyType, PlaintextKey, Version};
+
+struct FromStrVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for FromStrVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a PASERK string")
+    }
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+struct OptionVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for OptionVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an optional PASERK string")
+    }
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_str(FromStrVisitor(PhantomData))
+            .map(Some)
+    }
+}
+
+/// Serialize any `Display + FromStr` type as a string
+pub mod as_paserk_string {
+    use std::{fmt, marker::PhantomData, str::FromStr};
+
+    use ::serde::{Deserializer, Serializer};
+
+    /// Serialize the value with `Display`
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    /// Deserialize the value with `FromStr`
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(super::FromStrVisitor(PhantomData))
+    }
+
+    /// Serialize an optional value as a string or null
+    pub mod option {
+        use std::{fmt, marker::PhantomData, str::FromStr};
+
+        use ::serde::{Deserializer, Serializer};
+
+        /// Serialize the value with `Display`
+        pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: fmt::Display,
+            S: Serializer,
+        {
+            match value {
+                Some(value) => serializer.collect_str(value),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserialize the value with `FromStr`
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            T: FromStr,
+            T::Err: fmt::Display,
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_option(super::super::OptionVisitor(PhantomData))
+        }
+    }
+}
+
+/// Serialize a [`Key`] as its [`PlaintextKey`] string. This is not a secure serialization.
+pub mod plaintext {
+    use ::serde::{Deserializer, Serializer};
+
+    use crate::{Key, KeyType, Version};
+
+    /// Serialize the key as a [`PlaintextKey`](crate::PlaintextKey)
+    pub fn serialize<V: Version, K: KeyType<V>, S: Serializer>(
+        key: &Key<V, K>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize_plaintext(key, serializer)
+    }
+
+    /// Deserialize the key from a [`PlaintextKey`](crate::PlaintextKey)
+    pub fn deserialize<'de, V: Version, K: KeyType<V>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Key<V, K>, D::Error> {
+        super::as_paserk_string::deserialize(deserializer).map(super::unwrap_plaintext)
+    }
+
+    /// Serialize an optional key as a [`PlaintextKey`](crate::PlaintextKey) string or null
+    pub mod option {
+        use ::serde::{Deserializer, Serializer};
+
+        use crate::{Key, KeyType, Version};
+
+        /// Serialize the key as a [`PlaintextKey`](crate::PlaintextKey)
+        pub fn serialize<V: Version, K: KeyType<V>, S: Serializer>(
+            key: &Option<Key<V, K>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match key {
+                Some(key) => super::super::serialize_plaintext(key, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserialize the key from a [`PlaintextKey`](crate::PlaintextKey)
+        pub fn deserialize<'de, V: Version, K: KeyType<V>, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Key<V, K>>, D::Error> {
+            super::super::as_paserk_string::option::deserialize(deserializer)
+                .map(|key| key.map(super::super::unwrap_plaintext))
+        }
+    }
+
+    /// Serialize a [`KeyRing`](crate::KeyRing) as a list of [`PlaintextKey`](crate::PlaintextKey)
+    /// strings, in the order described on [`KeyRing`](crate::KeyRing)
+    ///
+    /// ```
+    /// use rusty_paserk::{Key, KeyRing, Local, V4};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Config {
+    ///     #[serde(with = "rusty_paserk::serde::plaintext::key_ring")]
+    ///     keys: KeyRing<V4>,
+    /// }
+    ///
+    /// let mut keys = KeyRing::new();
+    /// let lid = keys.insert(Key::<V4, Local>::new_os_random());
+    ///
+    /// let json = serde_json::to_string(&Config { keys }).unwrap();
+    /// let config: Config = serde_json::from_str(&json).unwrap();
+    /// assert!(config.keys.get_local(&lid).is_some());
+    ///
+    /// // the output does not depend on the order the keys were inserted in
+    /// let (a, b) = (Key::<V4, Local>::new_os_random(), Key::<V4, Local>::new_os_random());
+    /// let (mut ab, mut ba) = (KeyRing::new(), KeyRing::new());
+    /// ab.insert(a);
+    /// ab.insert(b);
+    /// ba.insert(b);
+    /// ba.insert(a);
+    /// assert_eq!(
+    ///     serde_json::to_string(&Config { keys: ab }).unwrap(),
+    ///     serde_json::to_string(&Config { keys: ba }).unwrap(),
+    /// );
+    /// ```
+    pub mod key_ring {
+        use std::{fmt, marker::PhantomData};
+
+        use ::serde::{de, ser::SerializeSeq, Deserializer, Serializer};
+
+        use crate::{internal::KeyRingType, KeyRing, Local, PlaintextKey, Public, Secret, Version};
+
+        /// Serialize the keys as [`PlaintextKey`]s
+        pub fn serialize<V: Version, S: Serializer>(
+            ring: &KeyRing<V>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            Local: KeyRingType<V>,
+            Public: KeyRingType<V>,
+            Secret: KeyRingType<V>,
+        {
+            let mut seq = serializer.serialize_seq(Some(ring.len()))?;
+            for (_, key) in ring.iter::<Local>() {
+                seq.serialize_element(&PlaintextKey(key.clone()))?;
+            }
+            for (_, key) in ring.iter::<Public>() {
+                seq.serialize_element(&PlaintextKey(key.clone()))?;
+            }
+            for (_, key) in ring.iter::<Secret>() {
+                seq.serialize_element(&PlaintextKey(key.clone()))?;
+            }
+            seq.end()
+        }
+
+        /// Deserialize the keys from [`PlaintextKey`]s of any type
+        pub fn deserialize<'de, V: Version, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<KeyRing<V>, D::Error>
+        where
+            Local: KeyRingType<V>,
+            Public: KeyRingType<V>,
+            Secret: KeyRingType<V>,
+        {
+            struct KeyRingVisitor<V>(PhantomData<V>);
+
+            impl<'de, V: Version> de::Visitor<'de> for KeyRingVisitor<V>
+            where
+                Local: KeyRingType<V>,
+                Public: KeyRingType<V>,
+                Secret: KeyRingType<V>,
+            {
+                type Value = KeyRing<V>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a list of PASERK plaintext keys")
+                }
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: de::SeqAccess<'de>,
+                {
+                    let mut ring = KeyRing::new();
+                    while let Some(s) = seq.next_element::<std::borrow::Cow<'de, str>>()? {
+                        ring.insert_plaintext(&s).map_err(de::Error::custom)?;
+                    }
+                    Ok(ring)
+                }
+            }
+
+            deserializer.deserialize_seq(KeyRingVisitor(PhantomData))
+        }
+    }
+}
+
+fn serialize_plaintext<V: Version, K: KeyType<V>, S: Serializer>(
+    key: &Key<V, K>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&PlaintextKey::<V, K>(key.clone()))
+}
+
+fn unwrap_plaintext<V: Version, K: KeyType<V>>(key: PlaintextKey<V, K>) -> Key<V, K> {
+    key.0
+}
@@ -0,0 +1,207 @@
+//! Publishing public key IDs to a key transparency log, so that keys issued without the
+//! owner's knowledge can be detected.
+//!
+//! The log is an append-only Merkle tree, as in [RFC 9162](https://www.rfc-editor.org/rfc/rfc9162).
+//! Each leaf is the `pid` string of a public key. A [`TransparencyLog`] implementation talks to
+//! the log service, and [`TransparencyClient`] checks every inclusion proof it returns, so a
+//! misbehaving log can't claim to have recorded a key that it left out.
+//!
+//! ```
+//! use std::{future::Future, pin::pin, sync::Mutex, task::{Context, Poll, Waker}};
+//! use rusty_paserk::{transparency::*, Key, PasetoError, Secret, V4};
+//!
+//! /// An in-memory log, for illustration only
+//! #[derive(Default)]
+//! struct MemoryLog(Mutex<Vec<[u8; 32]>>);
+//!
+//! impl TransparencyLog for MemoryLog {
+//!     async fn submit(&self, entry: &[u8]) -> Result<InclusionProof, PasetoError> {
+//!         let mut leaves = self.0.lock().unwrap();
+//!         leaves.push(leaf_hash(entry));
+//!         let leaf_index = leaves.len() as u64 - 1;
+//!         Ok(InclusionProof {
+//!             leaf_index,
+//!             tree_size: leaves.len() as u64,
+//!             path: path(leaf_index as usize, &leaves),
+//!         })
+//!     }
+//!
+//!     async fn root(&self, tree_size: u64) -> Result<[u8; 32], PasetoError> {
+//!         Ok(root(&self.0.lock().unwrap()[..tree_size as usize]))
+//!     }
+//! }
+//!
+//! # fn split(n: usize) -> usize { n.next_power_of_two() / 2 }
+//! # fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+//! #     match leaves {
+//! #         [leaf] => *leaf,
+//! #         _ => {
+//! #             let (l, r) = leaves.split_at(split(leaves.len()));
+//! #             node_hash(&root(l), &root(r))
+//! #         }
+//! #     }
+//! # }
+//! # fn path(m: usize, leaves: &[[u8; 32]]) -> Vec<[u8; 32]> {
+//! #     if leaves.len() == 1 {
+//! #         return vec![];
+//! #     }
+//! #     let k = split(leaves.len());
+//! #     let (l, r) = leaves.split_at(k);
+//! #     let (mut path, sibling) = if m < k { (path(m, l), root(r)) } else { (path(m - k, r), root(l)) };
+//! #     path.push(sibling);
+//! #     path
+//! # }
+//! # fn block_on<F: Future>(f: F) -> F::Output {
+//! #     let mut f = pin!(f);
+//! #     loop {
+//! #         if let Poll::Ready(out) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+//! #             return out;
+//! #         }
+//! #     }
+//! # }
+//! # block_on(async {
+//! let client = TransparencyClient::new(MemoryLog::default());
+//!
+//! let mut proofs = vec![];
+//! let mut ids = vec![];
+//! for _ in 0..5 {
+//!     let pid = Key::<V4, Secret>::new_os_random().public_key().to_id();
+//!     proofs.push(client.publish(&pid).await.unwrap());
+//!     ids.push(pid);
+//! }
+//!
+//! // auditors can check the proof later
+//! assert!(client.verify(&ids[2], &proofs[2]).await.is_ok());
+//! assert!(client.verify(&ids[3], &proofs[2]).await.is_err());
+//! # });
+//! ```
+
+use std::future::Future;
+
+use rusty_paseto::core::PasetoError;
+use sha2::{Digest, Sha256};
+
+use crate::{KeyId, Public, Version};
+
+/// A key transparency log service.
+///
+/// Implementations make the requests to the log. They are responsible for checking the log's
+/// signature on any tree head before returning its root hash from [`root`](Self::root).
+#[cfg_attr(docsrs, doc(cfg(feature = "transparency")))]
+pub trait TransparencyLog {
+    /// Append `entry` to the log, returning the proof that it is included
+    fn submit(
+        &self,
+        entry: &[u8],
+    ) -> impl Future<Output = Result<InclusionProof, PasetoError>> + Send;
+
+    /// The root hash of the log when it had `tree_size` entries
+    fn root(&self, tree_size: u64) -> impl Future<Output = Result<[u8; 32], PasetoError>> + Send;
+}
+
+/// A proof that a leaf is included in a log of `tree_size` entries
+#[cfg_attr(docsrs, doc(cfg(feature = "transparency")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// The position of the leaf in the log, from 0
+    pub leaf_index: u64,
+    /// The number of entries in the log that the proof is for
+    pub tree_size: u64,
+    /// The hashes of the sibling subtrees, from the leaf up to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    /// The root hash of the tree that this proof places `entry` in, or `None` if the proof is
+    /// malformed. See RFC 9162, section 2.1.3.2.
+    pub fn root_for(&self, entry: &[u8]) -> Option<[u8; 32]> {
+        if self.leaf_index >= self.tree_size {
+            return None;
+        }
+        let mut f = self.leaf_index;
+        let mut s = self.tree_size - 1;
+        let mut r = leaf_hash(entry);
+        for p in &self.path {
+            if s == 0 {
+                return None;
+            }
+            if f & 1 == 1 || f == s {
+                r = node_hash(p, &r);
+                while f & 1 == 0 && f != 0 {
+                    f >>= 1;
+                    s >>= 1;
+                }
+            } else {
+                r = node_hash(&r, p);
+            }
+            f >>= 1;
+            s >>= 1;
+        }
+        (s == 0).then_some(r)
+    }
+}
+
+/// Submits public key IDs to a [`TransparencyLog`], and checks the inclusion proofs it returns
+#[cfg_attr(docsrs, doc(cfg(feature = "transparency")))]
+pub struct TransparencyClient<L> {
+    log: L,
+}
+
+impl<L: TransparencyLog> TransparencyClient<L> {
+    /// A client for `log`
+    pub fn new(log: L) -> Self {
+        Self { log }
+    }
+
+    /// The log this client submits to
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+
+    /// Record a newly generated public key in the log.
+    ///
+    /// Returns [`PasetoError::InvalidSignature`] if the log's inclusion proof does not match its
+    /// root hash.
+    pub async fn publish<V: Version>(
+        &self,
+        id: &KeyId<V, Public>,
+    ) -> Result<InclusionProof, PasetoError> {
+        let proof = self.log.submit(id.to_string().as_bytes()).await?;
+        self.verify(id, &proof).await?;
+        Ok(proof)
+    }
+
+    /// Check that `proof` shows `id` is included in the log.
+    ///
+    /// Returns [`PasetoError::InvalidSignature`] if it does not.
+    pub async fn verify<V: Version>(
+        &self,
+        id: &KeyId<V, Public>,
+        proof: &InclusionProof,
+    ) -> Result<(), PasetoError> {
+        let root = self.log.root(proof.tree_size).await?;
+        match proof.root_for(id.to_string().as_bytes()) {
+            Some(r) if r == root => Ok(()),
+            _ => Err(PasetoError::InvalidSignature),
+        }
+    }
+}
+
+/// The hash of a log entry: `SHA-256(0x00 || entry)`
+pub fn leaf_hash(entry: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0x00])
+        .chain_update(entry)
+        .finalize()
+        .into()
+}
+
+/// The hash of an interior node: `SHA-256(0x01 || left || right)`
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0x01])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
@@ -15,13 +15,22 @@ use generic_array::{
 use rand::{rngs::OsRng, CryptoRng, RngCore};
 use rusty_paseto::core::PasetoError;
 
+#[cfg(feature = "v2")]
+use rusty_paseto::core::V2;
 #[cfg(feature = "v3")]
 use rusty_paseto::core::V3;
 #[cfg(feature = "v4")]
 use rusty_paseto::core::V4;
 use subtle::ConstantTimeEq;
 
-use crate::{read_b64, write_b64, Key, KeyType, Local, Secret, Version};
+use crate::{read_b64, write_b64, Algorithm, Key, KeyType, Local, Secret, Version};
+
+mod custom;
+#[cfg(feature = "async")]
+mod remote;
+pub use custom::{CustomWrappedKey, WrapProtocol};
+#[cfg(feature = "async")]
+pub use remote::{RemoteWrappedKey, RemoteWrapper};
 
 /// Paragon Initiative Enterprises standard key-wrapping
 ///
@@ -68,6 +77,17 @@ where
     K: PieWrapType<V>,
 {
 }
+impl<V: PieVersion, K: PieWrapType<V>> super::FromPaserkBytes for PieWrappedKey<V, K> {}
+impl<V: PieVersion, K: PieWrapType<V>> super::PaserkLen for PieWrappedKey<V, K> {
+    fn paserk_len(&self) -> usize {
+        use generic_array::typenum::Unsigned;
+        let len = <<K::Output as GenericSequence<u8>>::Length as Unsigned>::USIZE;
+        V::KEY_HEADER.len()
+            + K::WRAP_HEADER.len()
+            + "pie.".len()
+            + crate::encoding::encoded_len(len)
+    }
+}
 
 impl<V: PieVersion, K: PieWrapType<V>> Key<V, K> {
     /// Paragon Initiative Enterprises standard key-wrapping
@@ -168,6 +188,9 @@ where
     K: PieWrapType<V>,
     V: PieVersion,
 {
+    /// The algorithm used to wrap keys for this version
+    pub const ALGORITHM: Algorithm = V::ALGORITHM;
+
     /// Paragon Initiative Enterprises standard key-wrapping
     ///
     /// # Local Wrapping
@@ -294,6 +317,9 @@ impl<V: PieVersion, K: PieWrapType<V>> fmt::Display for PieWrappedKey<V, K> {
 
 /// Version info for configuring PIE Key wrapping
 pub trait PieVersion: Version {
+    /// The algorithm used to wrap keys
+    const ALGORITHM: Algorithm;
+
     #[doc(hidden)]
     type Cipher: StreamCipher + KeyIvInit;
     #[doc(hidden)]
@@ -326,6 +352,8 @@ pub trait PieVersion: Version {
 
 #[cfg(feature = "v3")]
 impl PieVersion for V3 {
+    const ALGORITHM: Algorithm = Algorithm::WrapPieAesCtr;
+
     type Cipher = ctr::Ctr64BE<aes::Aes256>;
     type AuthKeyMac = hmac::Hmac<sha2::Sha384>;
     type EncKeyMac = hmac::Hmac<sha2::Sha384>;
@@ -342,22 +370,33 @@ impl PieVersion for V3 {
 }
 
 #[cfg(feature = "v4")]
-impl PieVersion for V4 {
-    type Cipher = chacha20::XChaCha20;
-    type AuthKeyMac = blake2::Blake2bMac<U32>;
-    type EncKeyMac = blake2::Blake2bMac<generic_array::typenum::U56>;
-    type TagMac = blake2::Blake2bMac<U32>;
-
-    type Tag = digest::Output<Self::TagMac>;
-    type TagIv = GenericArray<u8, generic_array::typenum::U64>;
-
-    fn split_enc_key(
-        ek: digest::Output<Self::EncKeyMac>,
-    ) -> (cipher::Key<Self::Cipher>, cipher::Iv<Self::Cipher>) {
-        ek.split()
-    }
+macro_rules! impl_pie_xchacha20 {
+    ($v:ident) => {
+        impl PieVersion for $v {
+            const ALGORITHM: Algorithm = Algorithm::WrapPieXChaCha20;
+
+            type Cipher = chacha20::XChaCha20;
+            type AuthKeyMac = blake2::Blake2bMac<U32>;
+            type EncKeyMac = blake2::Blake2bMac<generic_array::typenum::U56>;
+            type TagMac = blake2::Blake2bMac<U32>;
+
+            type Tag = digest::Output<Self::TagMac>;
+            type TagIv = GenericArray<u8, generic_array::typenum::U64>;
+
+            fn split_enc_key(
+                ek: digest::Output<Self::EncKeyMac>,
+            ) -> (cipher::Key<Self::Cipher>, cipher::Iv<Self::Cipher>) {
+                ek.split()
+            }
+        }
+    };
 }
 
+#[cfg(feature = "v4")]
+impl_pie_xchacha20!(V4);
+#[cfg(feature = "v2")]
+impl_pie_xchacha20!(V2);
+
 /// Key wrapping type. Can be either `local-wrap.` or `secret-wrap.`
 pub trait WrapType {
     /// The header used when wrapping
@@ -402,25 +441,52 @@ impl PieWrapType<V3> for Secret {
 }
 
 #[cfg(feature = "v4")]
-impl PieWrapType<V4> for Local {
-    // 32 + 32 + 32 = 96
-    type Output = GenericArray<u8, generic_array::typenum::U96>;
-    type TagIv = <V4 as PieVersion>::TagIv;
+macro_rules! impl_pie_xchacha20_wrap_types {
+    ($v:ident) => {
+        impl PieWrapType<$v> for Local {
+            // 32 + 32 + 32 = 96
+            type Output = GenericArray<u8, generic_array::typenum::U96>;
+            type TagIv = <$v as PieVersion>::TagIv;
+        }
+
+        impl PieWrapType<$v> for Secret {
+            // 32 + 32 + 64 = 128
+            type Output = GenericArray<u8, generic_array::typenum::U128>;
+            type TagIv = <$v as PieVersion>::TagIv;
+        }
+    };
 }
 
 #[cfg(feature = "v4")]
-impl PieWrapType<V4> for Secret {
-    // 32 + 32 + 64 = 128
-    type Output = GenericArray<u8, generic_array::typenum::U128>;
-    type TagIv = <V4 as PieVersion>::TagIv;
+impl_pie_xchacha20_wrap_types!(V4);
+#[cfg(feature = "v2")]
+impl_pie_xchacha20_wrap_types!(V2);
+
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+#[cfg(feature = "arbitrary")]
+impl<'a, V: PieVersion, K: PieWrapType<V>> arbitrary::Arbitrary<'a> for PieWrappedKey<V, K> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let total: K::Output = crate::arbitrary_bytes(u)?;
+
+        let (tagiv, wrapped_key) = total.split();
+        let (tag, nonce) = tagiv.split();
+
+        Ok(Self {
+            wrapped_key,
+            nonce,
+            tag,
+        })
+    }
 }
 
-#[cfg(any(test, fuzzing))]
+#[cfg(any(test, fuzzing, feature = "testing"))]
 pub mod fuzz_tests {
     use crate::{fuzzing::FakeRng, Key, Local};
 
     use super::{PieVersion, PieWrapType};
 
+    /// Wraps `key` using `ephemeral` as the randomness, then checks it round-trips
+    /// through a string and unwraps to the same key
     #[derive(Debug)]
     pub struct FuzzInput<V: PieVersion, K: PieWrapType<V>> {
         wrapping_key: Key<V, Local>,
@@ -444,6 +510,7 @@ pub mod fuzz_tests {
     }
 
     impl<V: PieVersion, K: PieWrapType<V>> FuzzInput<V, K> {
+        /// Run the round trip, panicking on failure
         pub fn run(mut self) {
             let mut wrapped = self
                 .key
@@ -0,0 +1,148 @@
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use base64::Engine;
+use generic_array::GenericArray;
+use rusty_paseto::core::PasetoError;
+
+use crate::{write_b64, FromPaserkBytes, Key, KeyType, Local, Version};
+
+use super::WrapType;
+
+/// A custom PASERK wrap protocol, for crates that need to wrap keys with something other than
+/// [`pie`](crate::PieWrappedKey).
+///
+/// Implementations only deal with raw bytes. [`CustomWrappedKey`] takes care of the headers,
+/// base64 and key types.
+pub trait WrapProtocol<V: Version, K: KeyType<V> + WrapType> {
+    /// The protocol header, such as `"pie."`. It must end with `.`, which is checked at
+    /// compile time.
+    const PROTOCOL: &'static str;
+
+    /// Wrap the raw key bytes with the wrapping key
+    fn wrap(key: &[u8], wrapping_key: &Key<V, Local>) -> Vec<u8>;
+
+    /// Unwrap the raw key bytes with the wrapping key.
+    ///
+    /// This must authenticate `wrapped` before decrypting it.
+    fn unwrap(
+        wrapped: &[u8],
+        wrapping_key: &Key<V, Local>,
+    ) -> Result<GenericArray<u8, K::KeyLen>, PasetoError>;
+}
+
+/// A key wrapped with a custom [`WrapProtocol`].
+///
+/// It is serialized with the `local-wrap.` or `secret-wrap.` header, then the protocol header.
+///
+/// ```
+/// use rusty_paserk::{
+///     internal::generic_array::GenericArray, CustomWrappedKey, Key, Local, PasetoError,
+///     WrapProtocol, V4,
+/// };
+///
+/// /// Not secure, for illustration only
+/// struct Reverse;
+///
+/// impl WrapProtocol<V4, Local> for Reverse {
+///     const PROTOCOL: &'static str = "reverse.";
+///
+///     fn wrap(key: &[u8], _wrapping_key: &Key<V4, Local>) -> Vec<u8> {
+///         key.iter().rev().copied().collect()
+///     }
+///
+///     fn unwrap(
+///         wrapped: &[u8],
+///         _wrapping_key: &Key<V4, Local>,
+///     ) -> Result<GenericArray<u8, <Local as rusty_paserk::KeyType<V4>>::KeyLen>, PasetoError> {
+///         let key: Vec<u8> = wrapped.iter().rev().copied().collect();
+///         GenericArray::from_exact_iter(key).ok_or(PasetoError::IncorrectSize)
+///     }
+/// }
+///
+/// let wrapping_key = Key::<V4, Local>::new_os_random();
+/// let local_key = Key::<V4, Local>::new_os_random();
+///
+/// let wrapped = local_key.wrap_with::<Reverse>(&wrapping_key).to_string();
+/// assert!(wrapped.starts_with("k4.local-wrap.reverse."));
+///
+/// let wrapped: CustomWrappedKey<V4, Local, Reverse> = wrapped.parse().unwrap();
+/// assert_eq!(wrapped.unwrap_key(&wrapping_key).unwrap(), local_key);
+/// ```
+pub struct CustomWrappedKey<V: Version, K: KeyType<V> + WrapType, P: WrapProtocol<V, K>> {
+    wrapped: Vec<u8>,
+    _protocol: PhantomData<(V, K, P)>,
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, P: WrapProtocol<V, K>> FromPaserkBytes
+    for CustomWrappedKey<V, K, P>
+{
+}
+
+impl<V: Version, K: KeyType<V> + WrapType> Key<V, K> {
+    /// Wrap this key with a custom protocol. See [`CustomWrappedKey`]
+    pub fn wrap_with<P: WrapProtocol<V, K>>(
+        &self,
+        wrapping_key: &Key<V, Local>,
+    ) -> CustomWrappedKey<V, K, P> {
+        const {
+            assert!(
+                crate::headers::is_type_header(P::PROTOCOL),
+                "wrap protocol headers must end with '.'"
+            )
+        };
+
+        CustomWrappedKey {
+            wrapped: P::wrap(&self.key, wrapping_key),
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, P: WrapProtocol<V, K>> CustomWrappedKey<V, K, P> {
+    /// Unwrap the key with the custom protocol
+    pub fn unwrap_key(self, wrapping_key: &Key<V, Local>) -> Result<Key<V, K>, PasetoError> {
+        Ok(Key {
+            key: P::unwrap(&self.wrapped, wrapping_key)?,
+        })
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, P: WrapProtocol<V, K>> FromStr
+    for CustomWrappedKey<V, K, P>
+{
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(K::WRAP_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(P::PROTOCOL)
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let wrapped = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(|_| PasetoError::PayloadBase64Decode {
+                source: base64::DecodeError::InvalidLength(s.len()),
+            })?;
+
+        Ok(Self {
+            wrapped,
+            _protocol: PhantomData,
+        })
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, P: WrapProtocol<V, K>> fmt::Display
+    for CustomWrappedKey<V, K, P>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V::KEY_HEADER)?;
+        f.write_str(K::WRAP_HEADER)?;
+        f.write_str(P::PROTOCOL)?;
+        write_b64(&self.wrapped, f)
+    }
+}
@@ -0,0 +1,161 @@
+use std::{fmt, future::Future, marker::PhantomData, str::FromStr};
+
+use base64::Engine;
+use generic_array::GenericArray;
+use rusty_paseto::core::PasetoError;
+
+use crate::{write_b64, FromPaserkBytes, Key, KeyType, Version};
+
+use super::WrapType;
+
+/// A remote key-wrapping service, such as a cloud KMS, that protects keys without the wrapping
+/// key ever leaving the service.
+///
+/// Implementations only deal with raw bytes. [`RemoteWrappedKey`] takes care of the headers,
+/// base64 and key types, so the wrapped blob is still a self-describing PASERK.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub trait RemoteWrapper<V: Version> {
+    /// The protocol header, such as `"awskms."`. It must end with `.`, which is checked at
+    /// compile time.
+    const PROTOCOL: &'static str;
+
+    /// Wrap the raw key bytes
+    fn wrap_key(&self, key: &[u8]) -> impl Future<Output = Result<Vec<u8>, PasetoError>> + Send;
+
+    /// Unwrap the raw key bytes.
+    ///
+    /// The service must authenticate `wrapped` before returning the key.
+    fn unwrap_key(
+        &self,
+        wrapped: &[u8],
+    ) -> impl Future<Output = Result<Vec<u8>, PasetoError>> + Send;
+}
+
+/// A key wrapped by a [`RemoteWrapper`].
+///
+/// It is serialized with the `local-wrap.` or `secret-wrap.` header, then the protocol header.
+///
+/// ```
+/// use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+/// use rusty_paserk::{Key, Local, PasetoError, RemoteWrappedKey, RemoteWrapper, V4};
+///
+/// /// Not secure, for illustration only
+/// struct ReverseKms;
+///
+/// impl RemoteWrapper<V4> for ReverseKms {
+///     const PROTOCOL: &'static str = "reverse-kms.";
+///
+///     async fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, PasetoError> {
+///         Ok(key.iter().rev().copied().collect())
+///     }
+///
+///     async fn unwrap_key(&self, wrapped: &[u8]) -> Result<Vec<u8>, PasetoError> {
+///         Ok(wrapped.iter().rev().copied().collect())
+///     }
+/// }
+///
+/// # fn block_on<F: Future>(f: F) -> F::Output {
+/// #     let mut f = pin!(f);
+/// #     loop {
+/// #         if let Poll::Ready(out) = f.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+/// #             return out;
+/// #         }
+/// #     }
+/// # }
+/// # block_on(async {
+/// let local_key = Key::<V4, Local>::new_os_random();
+///
+/// let wrapped = local_key.wrap_remote(&ReverseKms).await.unwrap().to_string();
+/// assert!(wrapped.starts_with("k4.local-wrap.reverse-kms."));
+///
+/// let wrapped: RemoteWrappedKey<V4, Local, ReverseKms> = wrapped.parse().unwrap();
+/// assert_eq!(wrapped.unwrap_key(&ReverseKms).await.unwrap(), local_key);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct RemoteWrappedKey<V: Version, K: KeyType<V> + WrapType, W: RemoteWrapper<V>> {
+    wrapped: Vec<u8>,
+    _protocol: PhantomData<(V, K, W)>,
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, W: RemoteWrapper<V>> FromPaserkBytes
+    for RemoteWrappedKey<V, K, W>
+{
+}
+
+impl<V: Version, K: KeyType<V> + WrapType> Key<V, K> {
+    /// Wrap this key with a remote service. See [`RemoteWrappedKey`]
+    pub async fn wrap_remote<W: RemoteWrapper<V>>(
+        &self,
+        wrapper: &W,
+    ) -> Result<RemoteWrappedKey<V, K, W>, PasetoError> {
+        const {
+            assert!(
+                crate::headers::is_type_header(W::PROTOCOL),
+                "wrap protocol headers must end with '.'"
+            )
+        };
+
+        Ok(RemoteWrappedKey {
+            wrapped: wrapper.wrap_key(&self.key).await?,
+            _protocol: PhantomData,
+        })
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, W: RemoteWrapper<V>> RemoteWrappedKey<V, K, W> {
+    /// The wrapped blob, as returned by the service
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.wrapped
+    }
+
+    /// Unwrap the key with the remote service.
+    ///
+    /// Returns [`PasetoError::IncorrectSize`] if the service returns the wrong number of bytes.
+    pub async fn unwrap_key(self, wrapper: &W) -> Result<Key<V, K>, PasetoError> {
+        let key = wrapper.unwrap_key(&self.wrapped).await?;
+        Ok(Key {
+            key: GenericArray::from_exact_iter(key).ok_or(PasetoError::IncorrectSize)?,
+        })
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, W: RemoteWrapper<V>> FromStr
+    for RemoteWrappedKey<V, K, W>
+{
+    type Err = PasetoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix(V::KEY_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(K::WRAP_HEADER)
+            .ok_or(PasetoError::WrongHeader)?;
+        let s = s
+            .strip_prefix(W::PROTOCOL)
+            .ok_or(PasetoError::WrongHeader)?;
+
+        let wrapped = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(|_| PasetoError::PayloadBase64Decode {
+                source: base64::DecodeError::InvalidLength(s.len()),
+            })?;
+
+        Ok(Self {
+            wrapped,
+            _protocol: PhantomData,
+        })
+    }
+}
+
+impl<V: Version, K: KeyType<V> + WrapType, W: RemoteWrapper<V>> fmt::Display
+    for RemoteWrappedKey<V, K, W>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(V::KEY_HEADER)?;
+        f.write_str(K::WRAP_HEADER)?;
+        f.write_str(W::PROTOCOL)?;
+        write_b64(&self.wrapped, f)
+    }
+}
@@ -0,0 +1,112 @@
+//! Errors from parsing secret material must never echo the input, only lengths and positions.
+
+use std::{error::Error, fmt::Write, str::FromStr};
+
+use rusty_paserk::PasetoError;
+
+/// The error, its debug form and every source in its chain
+fn describe(err: &(dyn Error + 'static)) -> String {
+    let mut out = String::new();
+    let mut err = Some(err);
+    while let Some(e) = err {
+        write!(out, "{e} {e:?} ").unwrap();
+        err = e.source();
+    }
+    out
+}
+
+fn assert_redacted<T>(input: &str, payload: &str)
+where
+    T: FromStr<Err = PasetoError>,
+{
+    let Err(err) = input.parse::<T>() else {
+        panic!("{input:?} should not parse");
+    };
+    let described = describe(&err);
+
+    for window in payload.as_bytes().windows(6) {
+        let window = std::str::from_utf8(window).unwrap();
+        assert!(
+            !described.contains(window),
+            "error for {input:?} leaked {window:?}: {described}"
+        );
+    }
+}
+
+/// Invalid versions of `header` + `payload`: bad characters, truncated, too long and a wrong header
+fn corruptions(header: &str, payload: &str) -> Vec<String> {
+    let mut bad_char = payload.to_owned();
+    bad_char.replace_range(10..11, "*");
+    let mut bad_tail = payload.to_owned();
+    bad_tail.pop();
+    bad_tail.push('!');
+
+    vec![
+        format!("{header}{bad_char}"),
+        format!("{header}{bad_tail}"),
+        format!("{header}{}", &payload[..payload.len() - 4]),
+        format!("{header}{payload}AAAA"),
+        format!("k9.secret.{payload}"),
+    ]
+}
+
+#[cfg(feature = "v4")]
+#[test]
+fn v4_errors_do_not_leak_input() {
+    use rusty_paserk::{Local, PieWrappedKey, PlaintextKey, PwWrappedKey, SealedKey, Secret, V4};
+
+    // "ZZZ" repeated, so any echoed fragment is easy to spot
+    let secret = "Wlpa".repeat(22);
+    for input in corruptions("k4.secret.", &secret[..86]) {
+        assert_redacted::<PlaintextKey<V4, Secret>>(&input, &secret[..86]);
+    }
+    for input in corruptions("k4.local.", &secret[..43]) {
+        assert_redacted::<PlaintextKey<V4, Local>>(&input, &secret[..43]);
+    }
+    for input in corruptions("k4.local-wrap.pie.", &secret[..86]) {
+        assert_redacted::<PieWrappedKey<V4, Local>>(&input, &secret[..86]);
+    }
+    for input in corruptions("k4.secret-pw.", &secret) {
+        assert_redacted::<PwWrappedKey<V4, Secret>>(&input, &secret);
+    }
+    for input in corruptions("k4.seal.", &secret[..86]) {
+        assert_redacted::<SealedKey<V4>>(&input, &secret[..86]);
+    }
+}
+
+#[cfg(feature = "v3")]
+#[test]
+fn v3_errors_do_not_leak_input() {
+    use rusty_paserk::{Local, PieWrappedKey, PlaintextKey, SealedKey, Secret, V3};
+
+    let secret = "Wlpa".repeat(22);
+    for input in corruptions("k3.secret.", &secret[..64]) {
+        assert_redacted::<PlaintextKey<V3, Secret>>(&input, &secret[..64]);
+    }
+    for input in corruptions("k3.local.", &secret[..43]) {
+        assert_redacted::<PlaintextKey<V3, Local>>(&input, &secret[..43]);
+    }
+    for input in corruptions("k3.local-wrap.pie.", &secret[..86]) {
+        assert_redacted::<PieWrappedKey<V3, Local>>(&input, &secret[..86]);
+    }
+    for input in corruptions("k3.seal.", &secret[..86]) {
+        assert_redacted::<SealedKey<V3>>(&input, &secret[..86]);
+    }
+}
+
+#[cfg(feature = "v4")]
+#[test]
+fn line_errors_do_not_leak_input() {
+    use rusty_paserk::{read_paserk_lines, PlaintextKey, Secret, V4};
+
+    let secret = "Wlpa".repeat(22);
+    let file = format!("k4.secret.{}*\n", &secret[..85]);
+
+    let err = read_paserk_lines::<PlaintextKey<V4, Secret>, _>(file.as_bytes())
+        .next()
+        .unwrap()
+        .err()
+        .unwrap();
+    let described = describe(&err);
+    assert!(!described.contains("WlpaWl"), "{described}");
+}
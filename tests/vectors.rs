@@ -3,8 +3,8 @@ use std::{fs, str::FromStr};
 use libtest_mimic::{Arguments, Failed, Trial};
 use rusty_paserk::{
     internal::{PieVersion, PieWrapType, PwVersion, PwWrapType, SealedVersion},
-    Key, KeyId, KeyType, Local, PasetoError, PieWrappedKey, PlaintextKey, Public, PwWrappedKey,
-    SealedKey, Secret, Version,
+    Key, KeyId, KeyType, Local, PaserkLen, PasetoError, PieWrappedKey, PlaintextKey, Public,
+    PwWrappedKey, SealedKey, Secret, Version,
 };
 use serde::{de::DeserializeOwned, Deserialize};
 
@@ -22,10 +22,31 @@ fn main() {
     libtest_mimic::run(&args, tests).exit();
 }
 
-fn read_test<Test: DeserializeOwned>(v: &str) -> TestFile<Test> {
+fn read_test<Test: DeserializeOwned>(v: &str) -> Option<TestFile<Test>> {
     let path = format!("tests/test-vectors/{v}");
-    let file = fs::read_to_string(path).unwrap();
-    serde_json::from_str(&file).unwrap()
+    match fs::read_to_string(path) {
+        Ok(file) => Some(serde_json::from_str(&file).unwrap()),
+        // only the upstream k2 vectors may be used, and they are not bundled yet
+        Err(_) if v.starts_with("k2.") => None,
+        Err(err) => panic!("{v}: {err}"),
+    }
+}
+
+/// Add a trial for every test in the vector file `v`, or one ignored trial if it is a missing
+/// `k2` file, so the gap shows up in the test output.
+fn add_file<Test: DeserializeOwned + Send + 'static>(
+    tests: &mut Vec<Trial>,
+    v: &str,
+    run: fn(Test) -> Result<(), Failed>,
+) {
+    let Some(test_file) = read_test::<Test>(v) else {
+        tests.push(Trial::test(format!("{v} (not bundled)"), || Ok(())).with_ignored_flag(true));
+        return;
+    };
+    for test in test_file.tests {
+        let data = test.test_data;
+        tests.push(Trial::test(test.name, move || run(data)));
+    }
 }
 
 #[derive(Deserialize)]
@@ -48,6 +69,13 @@ struct IdTest {
 
 impl IdTest {
     fn add_all_tests(tests: &mut Vec<Trial>) {
+        #[cfg(feature = "v2")]
+        {
+            use rusty_paserk::V2;
+            Self::add_tests::<V2, Local>(tests);
+            Self::add_tests::<V2, Secret>(tests);
+            Self::add_tests::<V2, Public>(tests);
+        }
         #[cfg(feature = "v3")]
         {
             use rusty_paserk::V3;
@@ -69,10 +97,8 @@ impl IdTest {
         Key<V, K>: NewKey,
         KeyId<V, K>: From<Key<V, K>>,
     {
-        let test_file: TestFile<Self> = read_test(&format!("{}{}json", V::KEY_HEADER, K::ID));
-        for test in test_file.tests {
-            tests.push(Trial::test(test.name, || test.test_data.test::<V, K>()));
-        }
+        let v = format!("{}{}json", V::KEY_HEADER, K::ID);
+        add_file(tests, &v, |test: Self| test.test::<V, K>());
     }
 
     fn test<V: Version, K: KeyType<V>>(self) -> Result<(), Failed>
@@ -91,6 +117,14 @@ impl IdTest {
             if kid.to_string() != paserk {
                 return Err("encode failed".into());
             }
+            if kid.paserk_len() != paserk.len() {
+                return Err("paserk_len mismatch".into());
+            }
+
+            let kid3 = rusty_paserk::id_of_paserk(&PlaintextKey(key).to_string())?;
+            if kid3.to_string() != paserk {
+                return Err("id_of_paserk failed".into());
+            }
         }
         Ok(())
     }
@@ -105,6 +139,16 @@ struct KeyTest {
 
 impl KeyTest {
     fn add_all_tests(tests: &mut Vec<Trial>) {
+        #[cfg(feature = "v2")]
+        {
+            use rusty_paserk::V2;
+            Self::add_tests::<V2, Local>(tests);
+            Self::add_tests::<V2, Secret>(tests);
+            Self::add_tests::<V2, Public>(tests);
+            Self::add_v2_tests_from_v4::<Local>(tests);
+            Self::add_v2_tests_from_v4::<Secret>(tests);
+            Self::add_v2_tests_from_v4::<Public>(tests);
+        }
         #[cfg(feature = "v3")]
         {
             use rusty_paserk::V3;
@@ -125,9 +169,36 @@ impl KeyTest {
     where
         Key<V, K>: NewKey,
     {
-        let test_file: TestFile<Self> = read_test(&format!("{}{}json", V::KEY_HEADER, K::HEADER));
+        let v = format!("{}{}json", V::KEY_HEADER, K::HEADER);
+        add_file(tests, &v, |test: Self| test.test::<V, K>());
+    }
+
+    /// V2 and V4 keys have the same encoding, so every valid canonical `k4` plaintext key is
+    /// also a `k2` key once the header is swapped.
+    #[cfg(feature = "v2")]
+    fn add_v2_tests_from_v4<K>(tests: &mut Vec<Trial>)
+    where
+        K: KeyType<rusty_paserk::V2> + KeyType<rusty_paserk::V4>,
+        Key<rusty_paserk::V2, K>: NewKey,
+    {
+        use rusty_paserk::V4;
+
+        let v = format!("{}{}json", V4::KEY_HEADER, <K as KeyType<V4>>::HEADER);
+        let test_file: TestFile<Self> = read_test(&v).unwrap();
         for test in test_file.tests {
-            tests.push(Trial::test(test.name, || test.test_data.test::<V, K>()));
+            let (Some(key), Some(paserk)) = (test.test_data.key, test.test_data.paserk) else {
+                continue;
+            };
+            let paserk = format!("k2.{}", &paserk[3..]);
+            let name = test.name.replacen("k4", "k2-from-k4", 1);
+            tests.push(Trial::test(name, move || {
+                KeyTest {
+                    paserk: Some(paserk),
+                    key: Some(key),
+                    comment: None,
+                }
+                .test::<rusty_paserk::V2, K>()
+            }));
         }
     }
 
@@ -171,6 +242,12 @@ struct PbkwTest {
 
 impl PbkwTest {
     fn add_all_tests(tests: &mut Vec<Trial>) {
+        #[cfg(feature = "v2")]
+        {
+            use rusty_paserk::V2;
+            Self::add_tests::<V2, Local>(tests);
+            Self::add_tests::<V2, Secret>(tests);
+        }
         #[cfg(feature = "v3")]
         {
             use rusty_paserk::V3;
@@ -186,11 +263,8 @@ impl PbkwTest {
     }
 
     fn add_tests<V: PwVersion, K: PwWrapType<V>>(tests: &mut Vec<Trial>) {
-        let test_file: TestFile<Self> =
-            read_test(&format!("{}{}json", V::KEY_HEADER, K::WRAP_HEADER));
-        for test in test_file.tests {
-            tests.push(Trial::test(test.name, || test.test_data.test::<V, K>()));
-        }
+        let v = format!("{}{}json", V::KEY_HEADER, K::WRAP_HEADER);
+        add_file(tests, &v, |test: Self| test.test::<V, K>());
     }
 
     fn test<V: PwVersion, K: PwWrapType<V>>(self) -> Result<(), Failed> {
@@ -199,6 +273,9 @@ impl PbkwTest {
             Err(_) if self.expect_fail => return Ok(()),
             Err(e) => return Err(e.to_string().into()),
         };
+        if wrapped_key.paserk_len() != self.paserk.len() {
+            return Err("paserk_len mismatch".into());
+        }
 
         if self.expect_fail {
             match wrapped_key.unwrap_key(self.password.as_bytes()) {
@@ -230,6 +307,8 @@ struct PkeTest {
 
 impl PkeTest {
     fn add_all_tests(tests: &mut Vec<Trial>) {
+        #[cfg(feature = "v2")]
+        Self::add_tests::<rusty_paserk::V2>(tests);
         #[cfg(feature = "v3")]
         Self::add_tests::<rusty_paserk::V3>(tests);
         #[cfg(feature = "v4")]
@@ -240,10 +319,8 @@ impl PkeTest {
     where
         Key<V, Secret>: NewKey2,
     {
-        let test_file: TestFile<Self> = read_test(&format!("{}seal.json", V::KEY_HEADER));
-        for test in test_file.tests {
-            tests.push(Trial::test(test.name, || test.test_data.test::<V>()));
-        }
+        let v = format!("{}seal.json", V::KEY_HEADER);
+        add_file(tests, &v, |test: Self| test.test::<V>());
     }
 
     fn test<V: SealedVersion>(self) -> Result<(), Failed>
@@ -257,6 +334,9 @@ impl PkeTest {
             Err(_) if self.expect_fail => return Ok(()),
             Err(e) => return Err(e.to_string().into()),
         };
+        if sealed_key.paserk_len() != self.paserk.len() {
+            return Err("paserk_len mismatch".into());
+        }
 
         let ssk = Key::from_key2(&self.sealing_secret_key);
         let key = match sealed_key.unseal(&ssk) {
@@ -287,6 +367,12 @@ struct PieWrapTest {
 
 impl PieWrapTest {
     fn add_all_tests(tests: &mut Vec<Trial>) {
+        #[cfg(feature = "v2")]
+        {
+            use rusty_paserk::V2;
+            Self::add_tests::<V2, Local>(tests);
+            Self::add_tests::<V2, Secret>(tests);
+        }
         #[cfg(feature = "v3")]
         {
             use rusty_paserk::V3;
@@ -305,11 +391,8 @@ impl PieWrapTest {
     where
         Key<V, Local>: NewKey,
     {
-        let test_file: TestFile<Self> =
-            read_test(&format!("{}{}pie.json", V::KEY_HEADER, K::WRAP_HEADER));
-        for test in test_file.tests {
-            tests.push(Trial::test(test.name, || test.test_data.test::<V, K>()));
-        }
+        let v = format!("{}{}pie.json", V::KEY_HEADER, K::WRAP_HEADER);
+        add_file(tests, &v, |test: Self| test.test::<V, K>());
     }
 
     fn test<V: PieVersion, K: PieWrapType<V>>(self) -> Result<(), Failed>
@@ -323,6 +406,9 @@ impl PieWrapTest {
             Err(_) if self.expect_fail => return Ok(()),
             Err(e) => return Err(e.to_string().into()),
         };
+        if wrapped_key.paserk_len() != self.paserk.len() {
+            return Err("paserk_len mismatch".into());
+        }
 
         if self.expect_fail {
             match wrapped_key.unwrap_key(&wrapping_key) {
@@ -414,3 +500,36 @@ mod v4_impls {
         }
     }
 }
+
+#[cfg(feature = "v2")]
+mod v2_impls {
+    use super::*;
+    use rusty_paserk::V2;
+
+    impl NewKey for Key<V2, Local> {
+        fn from_key(s: &str) -> Self {
+            let b = hex::decode(s).unwrap();
+            Self::from_bytes(b.try_into().unwrap())
+        }
+    }
+    impl NewKey for Key<V2, Secret> {
+        fn from_key(s: &str) -> Self {
+            let b = hex::decode(s).unwrap();
+            Self::from_keypair_bytes(&b).unwrap()
+        }
+    }
+
+    impl NewKey for Key<V2, Public> {
+        fn from_key(s: &str) -> Self {
+            let b = hex::decode(s).unwrap();
+            Self::from_public_key(&b).unwrap()
+        }
+    }
+
+    impl NewKey2 for Key<V2, Secret> {
+        fn from_key2(s: &str) -> Self {
+            let b = hex::decode(s).unwrap();
+            Self::from_keypair_bytes(&b).unwrap()
+        }
+    }
+}